@@ -0,0 +1,284 @@
+//! Entry point for using yt-chill from other Rust programs
+//!
+//! ```no_run
+//! # async fn demo() -> yt_chill::error::Result<()> {
+//! let yt = yt_chill::YtChill::builder()
+//!     .state_dir("/tmp/my-app/state")
+//!     .build()
+//!     .await?;
+//! for video in yt.search("lofi hip hop", 5).await? {
+//!     println!("{} - {}", video.title, video.author);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Settings are process-wide, like they are for the CLI: the first client
+//! built in a process decides the directories, backend and HTTP client.
+//! Building another client with different settings is an error rather than
+//! a client that quietly uses the first one's.
+
+use crate::core::blocklist::Blocklist;
+use crate::core::http::{HttpBackend, ReqwestBackend};
+use crate::core::youtube;
+use crate::core::{downloader, events, player, ytdlp};
+use crate::error::{Result, YtChillError};
+use crate::storage::history::History;
+use crate::storage::{self, cache, config, favorites, queue, subscriptions};
+use crate::types::{
+    Channel, Config, DownloadOptions, Favorite, PlayOptions, PlaybackOutcome, StorageBackend, Subscription, Video, VideoMetadata,
+};
+use crate::utils::paths::{self, AppDirs};
+use futures_util::{Stream, StreamExt, future};
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+/// The settings the first build applied, to compare later builds against
+static APPLIED: OnceLock<Value> = OnceLock::new();
+
+/// Configures and builds a [`YtChill`]
+#[derive(Default)]
+pub struct YtChillBuilder {
+    dirs: AppDirs,
+    config: Option<Config>,
+    backend: Option<StorageBackend>,
+    http: Option<Arc<dyn HttpBackend>>,
+    no_cache: bool,
+}
+
+impl YtChillBuilder {
+    /// Where config.toml and subscriptions live (default: the XDG config dir)
+    pub fn config_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.config = Some(dir.into());
+        self
+    }
+
+    /// Where cached results and thumbnails go (default: the XDG cache dir)
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.cache = Some(dir.into());
+        self
+    }
+
+    /// Where history, favorites and the queue live (default: the XDG state dir)
+    pub fn state_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.state = Some(dir.into());
+        self
+    }
+
+    /// Use this config instead of loading config.toml
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Override the config's `storage` backend
+    pub fn backend(mut self, backend: StorageBackend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Make YouTube requests with this client (its own proxy and timeouts
+    /// apply instead of the config's)
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        self.http_backend(ReqwestBackend::new(client))
+    }
+
+    /// Send YouTube requests through something other than reqwest, e.g.
+    /// recorded responses in tests
+    pub fn http_backend(mut self, backend: impl HttpBackend + 'static) -> Self {
+        self.http = Some(Arc::new(backend));
+        self
+    }
+
+    /// Always fetch fresh results, like `--no-cache`
+    pub fn no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Create the directories, load the config if none was given, and apply
+    /// the settings. Fails if another client in this process was built with
+    /// different ones, since they can only be applied once.
+    pub async fn build(self) -> Result<YtChill> {
+        let dirs = json!([self.dirs.config, self.dirs.cache, self.dirs.state]);
+        paths::set_dirs(self.dirs);
+        paths::ensure_app_dirs().await?;
+
+        let mut config = match self.config {
+            Some(config) => config,
+            None => config::load_config().await?,
+        };
+        if let Some(backend) = self.backend {
+            config.storage = backend;
+        }
+
+        let settings = json!({ "dirs": dirs, "config": config, "no_cache": self.no_cache });
+        if let Err(settings) = APPLIED.set(settings) {
+            // A backend can't be compared, so a second one is never the same
+            if self.http.is_some() || APPLIED.get() != Some(&settings) {
+                return Err(YtChillError::InvalidConfig(
+                    "settings are process-wide, and another client was already built with different ones".into(),
+                ));
+            }
+            config.storage = storage::backend();
+            return Ok(YtChill {
+                blocklist: Blocklist::from_config(&config),
+                config,
+            });
+        }
+
+        config.storage = storage::set_backend(config.storage);
+        youtube::set_proxy(Some(config.proxy.clone()).filter(|p| !p.is_empty()));
+        youtube::set_network(config.network.clone());
+        if let Some(http) = self.http {
+            youtube::set_http_backend(http);
+        }
+        youtube::init_backend()?;
+        ytdlp::set_path(Some(config.yt_dlp_path.clone()).filter(|p| !p.is_empty()));
+        #[cfg(unix)]
+        crate::core::now_playing_file::set_path(Some(config.now_playing_file.clone()).filter(|p| !p.is_empty()));
+        cache::configure(config.cache.clone(), self.no_cache);
+        events::configure(config.events.clone());
+
+        Ok(YtChill {
+            blocklist: Blocklist::from_config(&config),
+            config,
+        })
+    }
+}
+
+/// Search, play, download and the stored lists, with the settings of one
+/// yt-chill setup
+#[derive(Debug, Clone)]
+pub struct YtChill {
+    config: Config,
+    blocklist: Blocklist,
+}
+
+impl YtChill {
+    pub fn builder() -> YtChillBuilder {
+        YtChillBuilder::default()
+    }
+
+    /// The settings in effect
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Search YouTube for videos, minus blocked channels and keywords
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Video>> {
+        let mut videos = youtube::search_videos(query, limit).await?;
+        self.blocklist.filter(&mut videos);
+        Ok(videos)
+    }
+
+    /// Like [`YtChill::search`], but yields each video as soon as its page is
+    /// parsed and keeps going through later pages for as long as it's polled
+    pub fn search_stream(&self, query: &str) -> impl Stream<Item = Result<Video>> + use<> {
+        let blocklist = self.blocklist.clone();
+        youtube::search_videos_stream(query)
+            .filter(move |video| future::ready(!matches!(video, Ok(v) if blocklist.blocks(v))))
+    }
+
+    /// Search YouTube for channels
+    pub async fn search_channels(&self, query: &str, limit: usize) -> Result<Vec<Channel>> {
+        youtube::search_channels(query, limit).await
+    }
+
+    /// Recent uploads from a channel ("@handle", "/channel/UC..." or the
+    /// UC... ID), newest first, minus blocked ones
+    pub async fn channel_videos(&self, handle: &str, limit: usize) -> Result<Vec<Video>> {
+        let id = match subscriptions::channel_id_in(handle) {
+            Some(id) => id,
+            None => youtube::resolve_channel_id(handle).await?,
+        };
+        let mut videos = youtube::fetch_channel_videos(&id, limit).await?;
+        self.blocklist.filter(&mut videos);
+        Ok(videos)
+    }
+
+    /// Formats, chapters, exact upload date and more, from yt-dlp
+    pub async fn metadata(&self, url: &str) -> Result<VideoMetadata> {
+        downloader::fetch_metadata(url).await
+    }
+
+    /// Watch history, loaded
+    pub async fn history(&self) -> Result<History> {
+        let mut history = History::new(&paths::get_history_path(), self.config.max_history_entries);
+        history.load().await?;
+        Ok(history)
+    }
+
+    pub async fn favorites(&self) -> Result<Vec<Favorite>> {
+        favorites::load_favorites().await
+    }
+
+    pub async fn subscriptions(&self) -> Result<Vec<Subscription>> {
+        subscriptions::load_subscriptions().await
+    }
+
+    /// Videos waiting in the play queue
+    pub async fn queue(&self) -> Result<Vec<Video>> {
+        queue::load_queue().await
+    }
+
+    /// Add videos to the end of the play queue
+    pub async fn enqueue(&self, videos: &[Video]) -> Result<()> {
+        queue::enqueue(videos).await
+    }
+
+    /// Play a video with the configured player
+    pub async fn play(&self, video: &Video, options: &PlayOptions) -> Result<PlaybackOutcome> {
+        player::play_video(video, options).await
+    }
+
+    /// Download a video with yt-dlp
+    pub async fn download(&self, video: &Video, options: &DownloadOptions) -> Result<()> {
+        downloader::download(video, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builder_uses_given_dirs() {
+        let root = std::env::temp_dir().join(format!("yt-chill-client-{}", std::process::id()));
+        let yt = YtChill::builder()
+            .config_dir(root.join("config"))
+            .cache_dir(root.join("cache"))
+            .state_dir(root.join("state"))
+            .config(Config::default())
+            .build()
+            .await
+            .unwrap();
+
+        let video = Video {
+            id: "abc123".into(),
+            title: "Lofi".into(),
+            duration: "3:45".into(),
+            duration_secs: Some(225),
+            ..Default::default()
+        };
+        yt.enqueue(std::slice::from_ref(&video)).await.unwrap();
+        assert!(root.join("state/queue.json").exists());
+        assert_eq!(yt.queue().await.unwrap()[0].id, "abc123");
+
+        // Settings only apply once per process: the same ones are fine again,
+        // different ones are refused instead of being ignored
+        let again = || {
+            YtChill::builder()
+                .config_dir(root.join("config"))
+                .cache_dir(root.join("cache"))
+                .state_dir(root.join("state"))
+                .config(Config::default())
+        };
+        assert!(again().build().await.is_ok());
+        assert!(again().no_cache(true).build().await.is_err());
+        assert!(again().state_dir(root.join("elsewhere")).build().await.is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,53 @@
+//! `yt-chill archive <channel>`: keep a local copy of a channel's uploads
+
+use crate::commands::download::print_summary;
+use crate::core::downloader;
+use crate::storage::subscriptions::{find_subscription, load_subscriptions};
+use crate::types::{DownloadOptions, OrganizeBy};
+use crate::utils::time::parse_duration;
+use chrono::{NaiveDate, Utc};
+use colored::Colorize;
+
+/// Parse `--since`: a date like "2024-01-31" or an age like "30d"
+pub fn parse_since(s: &str) -> Result<i64, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp());
+    }
+    parse_duration(s)
+        .map(|age| Utc::now().timestamp() - age.as_secs() as i64)
+        .ok_or_else(|| format!("invalid date or age '{}' (try 2024-01-31, 30d, 6w)", s))
+}
+
+/// Download the channel's uploads that aren't in the download archive yet,
+/// into a folder named after the channel
+pub async fn run(
+    channel: &str,
+    limit: Option<usize>,
+    since: Option<i64>,
+    defaults: &DownloadOptions,
+    jobs: usize,
+) -> anyhow::Result<()> {
+    // Subscriptions can be named instead of spelling out the handle
+    let subs = load_subscriptions().await?;
+    let handle = find_subscription(&subs, channel).map_or(channel, |s| s.handle.as_str());
+
+    println!("{}", format!("Listing uploads from {}...", handle).dimmed());
+    let videos = downloader::fetch_channel_uploads(handle, limit, since).await?;
+    if videos.is_empty() {
+        println!("{}", "No uploads to archive.".dimmed());
+        return Ok(());
+    }
+    println!("{} {} uploads", "Archive:".bold(), videos.len());
+
+    let options = DownloadOptions {
+        // Artist folders would scatter one channel across many
+        organize: OrganizeBy::Channel,
+        ..defaults.clone()
+    };
+    let summary = downloader::download_many(&videos, &options, jobs).await?;
+    print_summary(&summary);
+    if !summary.failed.is_empty() {
+        anyhow::bail!("{} download(s) failed; run the archive again to retry them", summary.failed.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! `yt-chill cache info`: show how much disk the cache uses
+
+use crate::storage::cache::{list_cache_files, max_size_bytes};
+use crate::utils::paths::{get_cache_dir, get_partial_downloads_dir, get_thumbnails_dir};
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::Path;
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Show cache location, size and limit
+    Info,
+}
+
+pub async fn run(action: CacheCommand) -> anyhow::Result<()> {
+    match action {
+        CacheCommand::Info => info().await,
+    }
+}
+
+async fn info() -> anyhow::Result<()> {
+    let files = list_cache_files().await?;
+    let used: u64 = files.iter().map(|f| f.size).sum();
+    let max = max_size_bytes();
+    let limit = match max {
+        0 => "no limit".to_string(),
+        max => format!("limit {}", format_size(max)),
+    };
+
+    println!("{} {}", "Location:".bold(), get_cache_dir());
+    println!(
+        "{} {} in {} entries ({})",
+        "Cached results:".bold(),
+        format_size(used),
+        files.len(),
+        limit.dimmed()
+    );
+    if max > 0 && used > max {
+        println!(
+            "  {} over the limit; the least recently used entries go the next time results are cached",
+            "→".dimmed()
+        );
+    }
+
+    let thumbnails = dir_size(Path::new(&get_thumbnails_dir()));
+    if thumbnails > 0 {
+        println!("{} {}", "Thumbnails:".bold(), format_size(thumbnails));
+    }
+
+    let partial = dir_size(Path::new(&get_partial_downloads_dir()));
+    if partial > 0 {
+        println!(
+            "{} {} {}",
+            "Partial downloads:".bold(),
+            format_size(partial),
+            "(not counted; finish them with `yt-chill download --resume`, or discard them with `--clean`)".dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Total size of the files under `dir`
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Human-readable size, e.g. "12.3 MB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,86 @@
+//! `yt-chill ctl ...`: control a running `yt-chill daemon` (or `--serve`),
+//! e.g. from hotkeys
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+/// Sent to the daemon as one line of JSON, e.g. `{"action":"pause"}`
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum CtlCommand {
+    /// Show what's playing
+    Status,
+
+    /// Pause/resume
+    Pause,
+
+    /// Skip to the next track
+    Next,
+
+    /// Show what's left in the queue
+    Queue,
+
+    /// Queue a video by URL, or the first result for a search
+    Add {
+        /// Video URL or search terms
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+}
+
+/// Send a command to the daemon and print its answer
+#[cfg(unix)]
+pub async fn run(command: CtlCommand) -> anyhow::Result<()> {
+    use crate::types::Video;
+    use crate::ui::format::{format_video_label, terminal_width};
+    use crate::ui::now_playing::Status;
+    use crate::utils::paths::get_control_socket_path;
+    use colored::Colorize;
+    use serde_json::Value;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixStream;
+
+    let stream = UnixStream::connect(get_control_socket_path())
+        .await
+        .map_err(|_| anyhow::anyhow!("No yt-chill daemon is running (start one with `yt-chill daemon`)"))?;
+    let (read, mut write) = stream.into_split();
+    write.write_all(format!("{}\n", serde_json::to_string(&command)?).as_bytes()).await?;
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+    let reply: Value = serde_json::from_str(&line)?;
+    if let Some(error) = reply.get("error").and_then(Value::as_str) {
+        anyhow::bail!("{}", error);
+    }
+
+    match command {
+        CtlCommand::Status => {
+            if reply.get("video").is_none_or(Value::is_null) {
+                println!("{}", "Nothing playing.".yellow());
+            } else {
+                let status: Status = serde_json::from_value(reply)?;
+                println!("{}", status.render(terminal_width().unwrap_or(80)));
+            }
+        }
+        CtlCommand::Pause => println!("{} Paused/resumed", "✓".green()),
+        CtlCommand::Next => println!("{} Skipped", "✓".green()),
+        CtlCommand::Queue => {
+            let mut queue: Vec<Video> = serde_json::from_value(reply)?;
+            // Added by URL and not played yet, so mpv hasn't told us the title
+            for video in queue.iter_mut().filter(|v| v.title.is_empty()) {
+                video.title = crate::core::player::build_video_url(&video.id);
+            }
+            if queue.is_empty() {
+                println!("{}", "Queue is empty.".yellow());
+            }
+            for (i, video) in queue.iter().enumerate() {
+                println!("{:>3}. {}", i + 1, format_video_label(video));
+            }
+        }
+        CtlCommand::Add { .. } => {
+            let video: Video = serde_json::from_value(reply["queued"].clone())?;
+            let name = if video.title.is_empty() { &video.id } else { &video.title };
+            println!("{} Queued {}", "✓".green(), name);
+        }
+    }
+    Ok(())
+}
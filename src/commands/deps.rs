@@ -0,0 +1,37 @@
+//! `yt-chill deps update`: keep yt-dlp current
+
+use crate::core::ytdlp;
+use clap::Subcommand;
+use colored::Colorize;
+
+#[derive(Subcommand, Debug)]
+pub enum DepsCommand {
+    /// Update yt-dlp to the latest release (`yt-dlp -U`)
+    Update,
+}
+
+pub async fn run(action: DepsCommand) -> anyhow::Result<()> {
+    match action {
+        DepsCommand::Update => update().await,
+    }
+}
+
+async fn update() -> anyhow::Result<()> {
+    let before = ytdlp::version().await;
+    println!("{}", format!("Updating {}...", ytdlp::program()).dimmed());
+    if !ytdlp::update().await? {
+        anyhow::bail!(
+            "yt-dlp couldn't update itself. If it came from pip or a package manager, update it there \
+             (e.g. `pip install -U yt-dlp` or `brew upgrade yt-dlp`), or point yt_dlp_path at a standalone copy."
+        );
+    }
+
+    match (before, ytdlp::version().await) {
+        (Some(before), Some(after)) if before != after => {
+            println!("{} yt-dlp {} → {}", "✓".green(), before, after)
+        }
+        (_, Some(version)) => println!("{} yt-dlp {} is up to date", "✓".green(), version),
+        (_, None) => println!("{} yt-dlp updated", "✓".green()),
+    }
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! `yt-chill doctor`: check dependencies, network, directories and config
+
+use crate::core::{youtube, ytdlp};
+use crate::types::{Config, ConfigIssue};
+use crate::utils::paths::{expand_tilde, find_executable, get_cache_dir, get_config_dir, get_state_dir};
+use colored::Colorize;
+use std::path::Path;
+use tokio::process::Command;
+
+/// How a check turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    /// Works, but something optional is missing
+    Warn,
+    Fail,
+}
+
+/// One line of the report
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.into(), status: Status::Pass, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.into(), status: Status::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { name: name.into(), status: Status::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Run every check, print the report, and fail if anything is broken
+pub async fn run(cfg: &Config, issues: &[ConfigIssue]) -> anyhow::Result<()> {
+    let mut checks = vec![
+        check_tool("mpv", "playback", true).await,
+        check_ytdlp().await,
+        check_tool("ffmpeg", "audio conversion and tags", true).await,
+        check_tool("fzf", "nicer menus", false).await,
+        check_network().await,
+        check_dir("config dir", &get_config_dir()).await,
+        check_dir("cache dir", &get_cache_dir()).await,
+        check_dir("state dir", &get_state_dir()).await,
+        check_dir("download dir", &expand_tilde(&cfg.download_dir)).await,
+    ];
+    checks.extend(check_config(issues));
+
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in &checks {
+        let mark = match check.status {
+            Status::Pass => "✓".green(),
+            Status::Warn => "!".yellow(),
+            Status::Fail => "✗".red(),
+        };
+        println!("{} {:width$}  {}", mark, check.name, check.detail, width = width);
+        if let Some(ref fix) = check.fix {
+            println!("  {:width$}  {} {}", "", "→".dimmed(), fix, width = width);
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    println!("\n{} Everything looks good", "✓".green());
+    Ok(())
+}
+
+/// Look for a binary in PATH and report its version
+async fn check_tool(name: &str, purpose: &str, required: bool) -> Check {
+    let Some(path) = find_executable(name) else {
+        let fix = format!("Install it for {} (e.g. `brew install {}` or your package manager)", purpose, name);
+        return if required {
+            Check::fail(name, "not found in PATH", fix)
+        } else {
+            Check::warn(name, "not found (optional)", fix)
+        };
+    };
+
+    let version = tool_version(name).await.unwrap_or_default();
+    Check::pass(name, format!("{} {}", path.display(), version.dimmed()))
+}
+
+/// yt-dlp, wherever it's configured to be, and whether it's recent enough
+async fn check_ytdlp() -> Check {
+    let Some(path) = find_executable(ytdlp::program()) else {
+        return Check::fail(
+            "yt-dlp",
+            format!("{} not found", ytdlp::program()),
+            "Install it for streams and downloads (e.g. `brew install yt-dlp`), or set yt_dlp_path in the config",
+        );
+    };
+
+    let version = ytdlp::version().await.unwrap_or_default();
+    if ytdlp::is_outdated(&version) {
+        return Check::warn(
+            "yt-dlp",
+            format!("{} {} (older than {})", path.display(), version, ytdlp::MIN_VERSION),
+            "Update it with `yt-chill deps update`; old versions often can't play YouTube videos",
+        );
+    }
+    Check::pass("yt-dlp", format!("{} {}", path.display(), version.dimmed()))
+}
+
+/// First line of `<tool> --version` (ffmpeg only understands `-version`)
+async fn tool_version(name: &str) -> Option<String> {
+    let flag = if name == "ffmpeg" { "-version" } else { "--version" };
+    let output = Command::new(name).arg(flag).output().await.ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|l| l.trim().to_string())
+}
+
+/// Can we reach YouTube with the configured proxy and timeouts?
+async fn check_network() -> Check {
+    match youtube::check_reachable().await {
+        Ok(elapsed) => Check::pass("youtube.com", format!("reachable ({} ms)", elapsed.as_millis())),
+        Err(e) => Check::fail(
+            "youtube.com",
+            format!("unreachable: {}", e),
+            "Check your connection, or `proxy` and `[network]` timeouts in the config",
+        ),
+    }
+}
+
+/// Does the directory exist, and can we write to it?
+async fn check_dir(name: &str, dir: &str) -> Check {
+    let path = Path::new(dir);
+    if !path.exists() {
+        return Check::warn(name, format!("{} doesn't exist yet", dir), "It's created on first use");
+    }
+
+    let probe = path.join(".yt-chill-doctor");
+    match tokio::fs::write(&probe, b"").await {
+        Ok(()) => {
+            let _ = tokio::fs::remove_file(&probe).await;
+            Check::pass(name, dir)
+        }
+        Err(e) => Check::fail(
+            name,
+            format!("{} is not writable: {}", dir, e),
+            format!("Fix the permissions, e.g. `chmod u+w {}`", dir),
+        ),
+    }
+}
+
+/// Problems found while loading the config (fields fell back to defaults)
+fn check_config(issues: &[ConfigIssue]) -> Vec<Check> {
+    if issues.is_empty() {
+        return vec![Check::pass("config", "valid")];
+    }
+
+    issues
+        .iter()
+        .map(|issue| {
+            let name = if issue.field.is_empty() { "config".into() } else { format!("config: {}", issue.field) };
+            let fix = issue.suggestion.clone().unwrap_or_else(|| "Edit it with `yt-chill -e`".into());
+            Check::fail(&name, &issue.problem, fix)
+        })
+        .collect()
+}
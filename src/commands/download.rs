@@ -0,0 +1,117 @@
+//! `yt-chill download [--resume | --clean | --playlist URL]`: list, finish
+//! or discard interrupted downloads, or download a whole playlist
+
+use crate::core::downloader::{self, DownloadSummary};
+use crate::storage::downloads::{clear_all_pending, load_pending};
+use crate::types::DownloadOptions;
+use crate::utils::notify;
+use crate::utils::paths::get_partial_downloads_dir;
+use crate::utils::time::format_timestamp;
+use colored::Colorize;
+
+/// List incomplete downloads, or finish them with `resume`
+pub async fn run(resume: bool, defaults: &DownloadOptions) -> anyhow::Result<()> {
+    let pending = load_pending().await?;
+
+    if pending.is_empty() {
+        println!("{}", "No unfinished downloads.".dimmed());
+        return Ok(());
+    }
+
+    if !resume {
+        for p in &pending {
+            let kind = if p.include_video { "video" } else { "audio" };
+            println!(
+                "{} {} {}",
+                p.video.title,
+                format!("[{}]", kind).cyan(),
+                format!("started {}", format_timestamp(p.started)).dimmed()
+            );
+        }
+        println!(
+            "{}",
+            "Run `yt-chill download --resume` to finish them, or `--clean` to throw them away.".dimmed()
+        );
+        return Ok(());
+    }
+
+    let total = pending.len();
+    let mut failed = 0;
+    for (i, p) in pending.into_iter().enumerate() {
+        println!("{} {}", format!("[{}/{}]", i + 1, total).dimmed(), p.video.title);
+        let opts = DownloadOptions {
+            video: p.include_video,
+            output_dir: p.output_dir,
+            force: true,
+            // One notification for the lot, below
+            notify: false,
+            ..defaults.clone()
+        };
+        if let Err(e) = downloader::download(&p.video, &opts).await {
+            eprintln!("{} {}", "Error:".red(), e);
+            failed += 1;
+        }
+    }
+
+    if defaults.notify {
+        let body = match failed {
+            0 => format!("{} resumed downloads finished", total),
+            _ => format!("{} of {} resumed downloads failed", failed, total),
+        };
+        notify::send("Downloads finished", &body).await;
+    }
+    if failed > 0 {
+        anyhow::bail!("{} download(s) still incomplete", failed);
+    }
+    Ok(())
+}
+
+/// Forget unfinished downloads and delete their partial files
+pub async fn clean() -> anyhow::Result<()> {
+    let count = clear_all_pending().await?;
+    let staging_dir = get_partial_downloads_dir();
+    match tokio::fs::remove_dir_all(&staging_dir).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => anyhow::bail!("Couldn't delete {}: {}", staging_dir, e),
+    }
+    match count {
+        0 => println!("{}", "No unfinished downloads; partial files cleared.".dimmed()),
+        n => println!("{} Discarded {} unfinished download(s)", "✓".green(), n),
+    }
+    Ok(())
+}
+
+/// Download every video in a playlist, `jobs` at a time
+pub async fn playlist(url: &str, defaults: &DownloadOptions, jobs: usize) -> anyhow::Result<()> {
+    let videos = downloader::fetch_playlist(url).await?;
+    if videos.is_empty() {
+        println!("{}", "That playlist is empty.".dimmed());
+        return Ok(());
+    }
+    println!("{} {} videos", "Playlist:".bold(), videos.len());
+
+    let summary = downloader::download_many(&videos, defaults, jobs).await?;
+    print_summary(&summary);
+    if !summary.failed.is_empty() {
+        anyhow::bail!("{} download(s) failed", summary.failed.len());
+    }
+    Ok(())
+}
+
+/// The downloaded files, one line with the totals, then what went wrong
+pub fn print_summary(summary: &DownloadSummary) {
+    downloader::print_downloads(&summary.downloaded);
+    let mut parts = vec![format!("{} downloaded", summary.downloaded.len())];
+    if summary.skipped > 0 {
+        parts.push(format!("{} already downloaded", summary.skipped));
+    }
+    if !summary.failed.is_empty() {
+        parts.push(format!("{} failed", summary.failed.len()).red().to_string());
+    }
+    let mark = if summary.failed.is_empty() { "✓".green() } else { "✗".red() };
+    println!("{} {}", mark, parts.join(", "));
+    for (video, e) in &summary.failed {
+        println!("  {} {}", video.title, format!("({})", e).dimmed());
+    }
+}
@@ -0,0 +1,91 @@
+//! `yt-chill fav ...`
+
+use crate::storage::favorites::{add_favorite, load_favorites, remove_favorite, with_tag};
+use crate::commands::write_output;
+use crate::storage::history::History;
+use crate::storage::m3u::to_m3u;
+use crate::types::Video;
+use crate::ui::format::format_video_label;
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+#[derive(Subcommand, Debug)]
+pub enum FavoritesCommand {
+    /// Star a video from history (default: the one playing right now)
+    Add {
+        /// Video ID (default: most recently played)
+        id: Option<String>,
+
+        /// Tag to attach, repeatable (e.g. -t focus -t sleep)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+    },
+
+    /// Unstar a video
+    Remove {
+        /// Video ID
+        id: String,
+    },
+
+    /// List favorites
+    List {
+        /// Only show favorites with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+    },
+
+    /// Write favorites (optionally one tag) as an .m3u8 playlist
+    Export {
+        /// Only export favorites with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+
+        /// File to write (default: stdout)
+        path: Option<PathBuf>,
+    },
+}
+
+/// Run a favorites subcommand
+pub async fn run(action: FavoritesCommand, history: &History) -> anyhow::Result<()> {
+    match action {
+        FavoritesCommand::Add { id, tags } => {
+            let entries = history.get_all();
+            let entry = match id {
+                Some(ref id) => entries.iter().find(|e| &e.video.id == id),
+                None => entries.first(),
+            };
+
+            let Some(entry) = entry else {
+                anyhow::bail!("That video isn't in your history; play it first");
+            };
+
+            add_favorite(&entry.video, &tags).await?;
+            println!("{} Starred {}", "⭐".yellow(), entry.video.title);
+        }
+        FavoritesCommand::Remove { id } => {
+            remove_favorite(&id).await?;
+            println!("{} Removed {} from favorites", "✓".green(), id);
+        }
+        FavoritesCommand::List { tag } => {
+            let favorites = load_favorites().await?;
+            for fav in with_tag(&favorites, tag.as_deref()) {
+                let tags = if fav.tags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" #{}", fav.tags.join(" #"))
+                };
+                println!("{}  {}{}", fav.video.id.dimmed(), format_video_label(&fav.video), tags.magenta());
+            }
+        }
+        FavoritesCommand::Export { tag, path } => {
+            let favorites = load_favorites().await?;
+            let videos: Vec<Video> = with_tag(&favorites, tag.as_deref())
+                .into_iter()
+                .map(|f| f.video.clone())
+                .collect();
+            write_output(&to_m3u(&videos), path).await?;
+        }
+    }
+    Ok(())
+}
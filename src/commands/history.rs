@@ -0,0 +1,204 @@
+//! `yt-chill history ...`
+
+use crate::core::player::build_video_url;
+use crate::storage::history::History;
+use crate::storage::import::parse_history;
+use crate::types::HistoryEntry;
+use crate::ui::format::{format_history_label, format_video_label};
+use crate::utils::time::{format_duration, format_timestamp, parse_duration};
+use chrono::Utc;
+use clap::{Subcommand, ValueEnum};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// History export format
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommand {
+    /// Fuzzy-search history by title or channel
+    Search {
+        /// Search terms
+        #[arg(required = true)]
+        terms: Vec<String>,
+
+        /// Print matches as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Export full history (with timestamps and play counts)
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: ExportFormat,
+
+        /// File to write (default: stdout)
+        path: Option<PathBuf>,
+    },
+
+    /// Delete all history, or only entries older than a given age
+    Clear {
+        /// Only remove entries last played longer ago than this, e.g. "30d"
+        #[arg(long, value_name = "AGE", value_parser = parse_age)]
+        older_than: Option<Duration>,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Pick individual entries to delete
+    Delete,
+
+    /// Import and merge history from a yt-chill export, FreeTube or NewPipe
+    Import {
+        /// File to import
+        file: PathBuf,
+    },
+}
+
+/// Run a history subcommand
+pub async fn run(action: HistoryCommand, history: &mut History) -> anyhow::Result<()> {
+    match action {
+        HistoryCommand::Search { terms, json } => search(history, &terms.join(" "), json),
+        HistoryCommand::Export { format, path } => export(history, format, path).await,
+        HistoryCommand::Import { file } => import(history, &file).await,
+        HistoryCommand::Clear { older_than, yes } => clear(history, older_than, yes).await,
+        HistoryCommand::Delete => delete(history).await,
+    }
+}
+
+/// clap value parser for `--older-than`
+fn parse_age(s: &str) -> Result<Duration, String> {
+    parse_duration(s).ok_or_else(|| format!("invalid age '{}' (try 30d, 2w, 12h)", s))
+}
+
+/// Clear everything, or prune old entries
+async fn clear(history: &mut History, older_than: Option<Duration>, yes: bool) -> anyhow::Result<()> {
+    let prompt = match older_than {
+        Some(age) => format!("Delete history older than {}?", format_duration(age)),
+        None => format!("Delete all {} history entries?", history.get_all().len()),
+    };
+
+    if !yes
+        && !dialoguer::Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?
+    {
+        return Ok(());
+    }
+
+    match older_than {
+        Some(age) => {
+            let cutoff = Utc::now().timestamp() - age.as_secs() as i64;
+            let removed = history.prune_older_than(cutoff).await?;
+            println!("{} Removed {} entries", "✓".green(), removed);
+        }
+        None => {
+            history.clear().await?;
+            println!("{} History cleared", "✓".green());
+        }
+    }
+    Ok(())
+}
+
+/// Multi-select entries to remove
+async fn delete(history: &mut History) -> anyhow::Result<()> {
+    let entries = history.get_all();
+    if entries.is_empty() {
+        println!("{}", "No history yet.".yellow());
+        return Ok(());
+    }
+
+    let labels: Vec<String> = entries.iter().map(format_history_label).collect();
+    let chosen = dialoguer::MultiSelect::new()
+        .with_prompt("Select entries to delete (space = toggle, enter = confirm)")
+        .items(&labels)
+        .interact_opt()?
+        .unwrap_or_default();
+
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<String> = chosen.iter().map(|&i| entries[i].video.id.clone()).collect();
+    let removed = history.remove(&ids).await?;
+    println!("{} Removed {} entries", "✓".green(), removed);
+    Ok(())
+}
+
+/// Merge entries from another export into history
+async fn import(history: &mut History, file: &Path) -> anyhow::Result<()> {
+    let content = tokio::fs::read_to_string(file).await?;
+    let entries = parse_history(&content)?;
+    let found = entries.len();
+    let stats = history.merge(entries).await?;
+
+    println!(
+        "{} Imported {} entries: {} new, {} updated",
+        "✓".green(),
+        found,
+        stats.added,
+        stats.updated
+    );
+    Ok(())
+}
+
+/// Write history to a file or stdout
+async fn export(history: &History, format: ExportFormat, path: Option<PathBuf>) -> anyhow::Result<()> {
+    let content = match format {
+        ExportFormat::Csv => history.to_csv(),
+        ExportFormat::Json => serde_json::to_string_pretty(history.get_all())?,
+    };
+
+    match path {
+        Some(path) => {
+            tokio::fs::write(&path, content).await?;
+            eprintln!(
+                "{} Exported {} entries to {}",
+                "✓".green(),
+                history.get_all().len(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// Print history entries matching `query`
+fn search(history: &History, query: &str, json: bool) -> anyhow::Result<()> {
+    let matches = history.search(query);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+        return Ok(());
+    }
+
+    if matches.is_empty() {
+        println!("{}", format!("Nothing in history matches \"{}\".", query).yellow());
+        return Ok(());
+    }
+
+    for entry in matches {
+        print_entry(entry);
+    }
+    Ok(())
+}
+
+/// Print one history line: when, what, and the URL to replay it
+fn print_entry(entry: &HistoryEntry) {
+    println!(
+        "{}  {}  {}",
+        format_timestamp(entry.timestamp).dimmed(),
+        format_video_label(&entry.video),
+        build_video_url(&entry.video.id).dimmed()
+    );
+}
@@ -0,0 +1,77 @@
+//! `yt-chill info <video>`: what yt-dlp knows about a video
+
+use crate::core::downloader::fetch_metadata;
+use crate::core::player::build_video_url;
+use crate::types::{LiveStatus, VideoMetadata};
+use crate::utils::time::format_clock;
+use colored::Colorize;
+use indicatif::HumanBytes;
+
+/// Print a video's details, formats and chapters (or all of it as JSON)
+pub async fn run(video: &str, json: bool) -> anyhow::Result<()> {
+    let url = if video.contains("://") {
+        video.to_string()
+    } else {
+        build_video_url(video)
+    };
+    let meta = fetch_metadata(&url).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&meta)?);
+        return Ok(());
+    }
+    print_details(&meta);
+    Ok(())
+}
+
+fn print_details(meta: &VideoMetadata) {
+    println!("{}", meta.title.bold());
+    let row = |label: &str, value: String| println!("  {:<10} {}", label.dimmed(), value);
+    row("Channel", meta.uploader.clone());
+    if let Some(date) = meta.upload_date {
+        row("Uploaded", date.format("%Y-%m-%d").to_string());
+    }
+    if let Some(duration) = meta.duration {
+        row("Length", format_clock(duration));
+    }
+    if let Some(views) = meta.view_count {
+        row("Views", views.to_string());
+    }
+    let live = match meta.live_status {
+        LiveStatus::IsLive => Some("live now"),
+        LiveStatus::IsUpcoming => Some("upcoming"),
+        LiveStatus::WasLive | LiveStatus::PostLive => Some("was live"),
+        LiveStatus::NotLive => None,
+    };
+    if let Some(live) = live {
+        row("Stream", live.to_string());
+    }
+
+    if !meta.chapters.is_empty() {
+        println!("\n{}", "Chapters".bold());
+        for chapter in &meta.chapters {
+            println!("  {:>8}  {}", format_clock(chapter.start as u64).dimmed(), chapter.title);
+        }
+    }
+
+    if !meta.formats.is_empty() {
+        println!("\n{}", "Formats".bold());
+        for format in &meta.formats {
+            let fps = format.fps.filter(|_| format.has_video).map(|fps| format!("{}fps", fps));
+            let bitrate = format.bitrate.map(|b| format!("{:.0}k", b));
+            let size = format.filesize.map(|s| HumanBytes(s).to_string());
+            // "audio only" is already in the resolution column
+            let kind = if format.has_video && !format.has_audio { "video only" } else { "" };
+            println!(
+                "  {:<8} {:<5} {:<11} {:<6} {:<6} {:>10}  {}",
+                format.code,
+                format.ext,
+                format.resolution,
+                fps.unwrap_or_default(),
+                bitrate.unwrap_or_default(),
+                size.unwrap_or_default(),
+                kind.dimmed()
+            );
+        }
+    }
+}
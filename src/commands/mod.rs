@@ -0,0 +1,185 @@
+//! Subcommands, e.g. `yt-chill history search <terms>`
+
+pub mod archive;
+pub mod cache;
+pub mod ctl;
+pub mod deps;
+pub mod doctor;
+pub mod download;
+pub mod favorites;
+pub mod history;
+pub mod info;
+pub mod queue;
+pub mod schedule;
+pub mod searches;
+#[cfg(unix)]
+pub mod serve;
+pub mod subs;
+pub mod takeout;
+pub mod watch;
+
+use chrono::NaiveTime;
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Top-level subcommands
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Browse and manage watch history (no action = browse interactively)
+    History {
+        #[command(subcommand)]
+        action: Option<history::HistoryCommand>,
+    },
+
+    /// List downloads that didn't finish (e.g. dropped Wi-Fi), or download a playlist
+    Download {
+        /// Finish them, continuing from the partial files
+        #[arg(long, conflicts_with = "playlist")]
+        resume: bool,
+
+        /// Give up on them instead, deleting the partial files
+        #[arg(long, conflicts_with_all = ["resume", "playlist", "schedule"])]
+        clean: bool,
+
+        /// Download every video in this playlist (already downloaded ones are skipped)
+        #[arg(long, value_name = "URL")]
+        playlist: Option<String>,
+
+        /// Videos to download at once (default: download_jobs from the config)
+        #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+
+        /// Start at this time of day instead of now, e.g. "02:00" (with --playlist or --resume)
+        #[arg(long, value_name = "HH:MM", value_parser = schedule::parse_time)]
+        schedule: Option<NaiveTime>,
+    },
+
+    /// Download a channel's uploads into its own folder, skipping ones already downloaded
+    Archive {
+        /// Channel handle (e.g. @LofiGirl), URL, or the name of a subscription
+        channel: String,
+
+        /// Only the newest N uploads
+        #[arg(short = 'n', long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Only uploads since this date or age, e.g. "2024-01-31" or "30d"
+        #[arg(long, value_name = "DATE|AGE", value_parser = archive::parse_since)]
+        since: Option<i64>,
+
+        /// Download video instead of audio
+        #[arg(long)]
+        video: bool,
+
+        /// Videos to download at once (default: download_jobs from the config)
+        #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: Option<u16>,
+
+        /// Start at this time of day instead of now, e.g. "02:00"
+        #[arg(long, value_name = "HH:MM", value_parser = schedule::parse_time)]
+        schedule: Option<NaiveTime>,
+    },
+
+    /// Show a video's details, chapters and available formats
+    Info {
+        /// Video URL or ID
+        video: String,
+
+        /// Print everything as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Star videos with tags (no action = browse interactively)
+    #[command(alias = "favorites")]
+    Fav {
+        #[command(subcommand)]
+        action: Option<favorites::FavoritesCommand>,
+    },
+
+    /// List, remove and rename subscriptions
+    Subs {
+        #[command(subcommand)]
+        action: subs::SubsCommand,
+    },
+
+    /// Import subscriptions and watch history from a Google Takeout export
+    Takeout {
+        /// Extracted Takeout directory, subscriptions.csv or watch-history.json
+        path: PathBuf,
+    },
+
+    /// Poll subscriptions and send desktop notifications for new uploads
+    Watch {
+        /// How often to check (e.g. 15m, 1h)
+        #[arg(short, long, default_value = "15m", value_parser = watch::parse_interval)]
+        interval: Duration,
+
+        /// Check once, print new uploads and exit (for cron/systemd timers)
+        #[arg(long)]
+        once: bool,
+    },
+
+    /// Keep a player running in the background that plays the queue and
+    /// takes commands from `yt-chill ctl`
+    Daemon,
+
+    /// Control a running daemon, e.g. `yt-chill ctl next` from a hotkey
+    Ctl {
+        #[command(subcommand)]
+        action: ctl::CtlCommand,
+    },
+
+    /// Manage and play the queue
+    Queue {
+        #[command(subcommand)]
+        action: queue::QueueCommand,
+    },
+
+    /// List or clear past search queries (recalled with ↑ at the search prompt)
+    Searches {
+        #[command(subcommand)]
+        action: searches::SearchesCommand,
+    },
+
+    /// List, cancel or start downloads saved with `--schedule`
+    Schedule {
+        #[command(subcommand)]
+        action: schedule::ScheduleCommand,
+    },
+
+    /// Inspect the results cache
+    Cache {
+        #[command(subcommand)]
+        action: cache::CacheCommand,
+    },
+
+    /// Check dependencies, network, directories and config, with fix suggestions
+    Doctor,
+
+    /// Update dependencies, e.g. `yt-chill deps update` for a yt-dlp that stopped working
+    Deps {
+        #[command(subcommand)]
+        action: deps::DepsCommand,
+    },
+
+    /// Print the path of a video's cached thumbnail, downloading it first (used by fzf previews)
+    #[command(hide = true)]
+    Thumbnail {
+        id: String,
+    },
+}
+
+/// Write exported content to a file, or stdout when no path is given
+pub async fn write_output(content: &str, path: Option<PathBuf>) -> anyhow::Result<()> {
+    match path {
+        Some(path) => {
+            tokio::fs::write(&path, content).await?;
+            eprintln!("{} Wrote {}", "✓".green(), path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! `yt-chill queue ...`
+
+use crate::commands::write_output;
+use crate::core::player;
+use crate::storage::history::History;
+use crate::storage::m3u::to_m3u;
+use crate::storage::queue::{clear_queue, dequeue, enqueue, load_queue};
+use crate::types::{PlayOptions, PlaybackOutcome, PlayerType, Video};
+use crate::ui::format::format_video_label;
+use crate::ui::theme::{Icon, with_icon};
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+#[derive(Subcommand, Debug)]
+pub enum QueueCommand {
+    /// Add a video from history (default: the one playing right now)
+    Add {
+        /// Video ID (default: most recently played)
+        id: Option<String>,
+    },
+
+    /// Show queued videos
+    List,
+
+    /// Play the queue in order, removing items as they finish
+    Play,
+
+    /// Empty the queue
+    Clear,
+
+    /// Write the queue as an .m3u8 playlist
+    Export {
+        /// File to write (default: stdout)
+        path: Option<PathBuf>,
+    },
+}
+
+/// Run a queue subcommand
+pub async fn run(action: QueueCommand, history: &mut History, opts: &PlayOptions) -> anyhow::Result<()> {
+    match action {
+        QueueCommand::Add { id } => {
+            let entries = history.get_all();
+            let entry = match id {
+                Some(ref id) => entries.iter().find(|e| &e.video.id == id),
+                None => entries.first(),
+            };
+
+            let Some(entry) = entry else {
+                anyhow::bail!("That video isn't in your history; play it first");
+            };
+
+            enqueue(std::slice::from_ref(&entry.video)).await?;
+            println!("{} Queued {}", "✓".green(), entry.video.title);
+        }
+        QueueCommand::List => {
+            let queue = load_queue().await?;
+            if queue.is_empty() {
+                println!("{}", "Queue is empty.".yellow());
+            }
+            for (i, video) in queue.iter().enumerate() {
+                println!("{:>3}. {}", i + 1, format_video_label(video));
+            }
+        }
+        QueueCommand::Play => {
+            #[cfg(unix)]
+            let gapless = opts.player == PlayerType::Mpv;
+            #[cfg(not(unix))]
+            let gapless = false;
+
+            if gapless {
+                #[cfg(unix)]
+                play_gapless(history, opts).await?;
+            } else {
+                loop {
+                    let queue = load_queue().await?;
+                    let Some(video) = queue.first().cloned() else {
+                        break;
+                    };
+                    // Resolve the next stream while this one plays
+                    if let Some(next) = queue.get(1) {
+                        player::prefetch(next, opts);
+                    }
+                    println!("{} {}", "Playing:".dimmed(), video.title);
+                    history.add(&video).await?;
+                    let outcome = player::play_video(&video, opts).await?;
+
+                    // Drop the item we just played
+                    dequeue(&video.id).await?;
+
+                    if outcome == PlaybackOutcome::Quit {
+                        break;
+                    }
+                }
+            }
+            println!("{}", with_icon(Icon::Goodbye, "Thanks for chilling."));
+        }
+        QueueCommand::Clear => {
+            clear_queue().await?;
+            println!("{} Queue cleared", "✓".green());
+        }
+        QueueCommand::Export { path } => write_output(&to_m3u(&load_queue().await?), path).await?,
+    }
+    Ok(())
+}
+
+/// Play the whole queue in one mpv so tracks follow each other without a gap.
+/// Items are dropped from the queue as they finish, and videos queued while
+/// playing are appended to mpv's playlist.
+#[cfg(unix)]
+async fn play_gapless(history: &mut History, opts: &PlayOptions) -> anyhow::Result<()> {
+    use crate::core::events::{self, Event};
+    use crate::core::mpv_ipc::{self, MpvIpc};
+    use crate::ui::now_playing;
+
+    let mut playlist: Vec<Video> = load_queue().await?;
+    let Some(first) = playlist.first().cloned() else {
+        return Ok(());
+    };
+
+    let _player = crate::utils::shutdown::player_running();
+    let socket = mpv_ipc::socket_path();
+    let mut child = player::spawn_mpv_playlist(&playlist, opts, &socket).await?;
+    let line = now_playing::enabled().then(|| tokio::spawn(now_playing::show(socket.clone())));
+    println!("{} {}", "Playing:".dimmed(), first.title);
+    history.add(&first).await?;
+    events::emit(Event::TrackStarted { video: &first }).await;
+    let mut current = 0;
+
+    // Without the socket we can't follow along, but mpv still plays everything
+    let ipc = tokio::select! {
+        ipc = MpvIpc::connect(&socket) => ipc.ok(),
+        _ = child.wait() => None,
+    };
+    if let Some(mut ipc) = ipc {
+        ipc.observe(1, "playlist-pos").await?;
+        while let Some(event) = ipc.next_event().await {
+            let Some(pos) = mpv_ipc::property_change(&event, "playlist-pos").and_then(|p| p.as_u64()) else {
+                continue;
+            };
+            let pos = pos as usize;
+            if pos == current || pos >= playlist.len() {
+                continue;
+            }
+
+            dequeue(&playlist[current].id).await?;
+            events::emit(Event::TrackFinished { video: &playlist[current], quit: false }).await;
+            current = pos;
+            now_playing::clear();
+            println!("{} {}", "Playing:".dimmed(), playlist[current].title);
+            history.add(&playlist[current]).await?;
+            events::emit(Event::TrackStarted { video: &playlist[current] }).await;
+
+            // Pick up anything queued since we started
+            for video in load_queue().await? {
+                if !playlist.iter().any(|v| v.id == video.id) {
+                    ipc.command(&["loadfile", &player::build_video_url(&video.id), "append"]).await?;
+                    playlist.push(video);
+                }
+            }
+        }
+    }
+
+    let outcome = player::wait_mpv(&mut child).await;
+    if let Some(line) = line {
+        let _ = line.await;
+    }
+    let _ = std::fs::remove_file(&socket);
+
+    // Reaching the end means everything played; after quitting, the track
+    // that was on counts as played
+    let outcome = outcome?;
+    let quit = outcome == PlaybackOutcome::Quit;
+    events::emit(Event::TrackFinished { video: &playlist[current], quit }).await;
+    let played = match outcome {
+        PlaybackOutcome::Finished => &playlist[..],
+        PlaybackOutcome::Quit => &playlist[current..=current],
+    };
+    for video in played {
+        dequeue(&video.id).await?;
+    }
+    Ok(())
+}
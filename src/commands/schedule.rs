@@ -0,0 +1,145 @@
+//! `yt-chill schedule ...`: downloads saved with `--schedule` to run later
+
+use crate::commands::{archive, download};
+use crate::storage::schedule::{add_scheduled, load_schedule, remove_scheduled};
+use crate::types::{DownloadJob, DownloadOptions, ScheduledDownload};
+use crate::utils::time::{format_duration, format_timestamp, next_time_of_day};
+use chrono::{Local, NaiveTime, Utc};
+use clap::Subcommand;
+use colored::Colorize;
+use std::time::Duration;
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommand {
+    /// Show scheduled downloads and when they start
+    List,
+
+    /// Cancel a scheduled download
+    Remove {
+        /// Its number in `yt-chill schedule list`
+        number: usize,
+    },
+
+    /// Start downloads whose time has come (e.g. from cron after a reboot)
+    Run {
+        /// Stay running and start the rest as their times come
+        #[arg(long)]
+        wait: bool,
+    },
+}
+
+/// Parse `--schedule`: a time of day like "02:00"
+pub fn parse_time(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").map_err(|_| format!("invalid time '{}' (try 02:00, 23:30)", s))
+}
+
+pub async fn run(action: ScheduleCommand, defaults: &DownloadOptions, default_jobs: usize) -> anyhow::Result<()> {
+    match action {
+        ScheduleCommand::List => list().await,
+        ScheduleCommand::Remove { number } => remove(number).await,
+        ScheduleCommand::Run { wait } => run_due(wait, defaults, default_jobs).await,
+    }
+}
+
+/// Save `job` to start the next time the clock shows `time`, then wait for it
+pub async fn schedule(
+    job: DownloadJob,
+    time: NaiveTime,
+    defaults: &DownloadOptions,
+    jobs: Option<usize>,
+    default_jobs: usize,
+) -> anyhow::Result<()> {
+    let at = next_time_of_day(time, &Local::now()).timestamp();
+    println!(
+        "{} Scheduled {} for {} {}",
+        "✓".green(),
+        job.describe(),
+        format_timestamp(at),
+        format!("(in {})", until(at)).dimmed()
+    );
+    add_scheduled(ScheduledDownload { at, job, video: defaults.video, jobs }).await?;
+    println!(
+        "{}",
+        "Leave this running, or stop it and start it later with `yt-chill schedule run`.".dimmed()
+    );
+    run_due(true, defaults, default_jobs).await
+}
+
+async fn list() -> anyhow::Result<()> {
+    let schedule = load_schedule().await?;
+    if schedule.is_empty() {
+        println!("{}", "No scheduled downloads.".dimmed());
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+    for (i, d) in schedule.iter().enumerate() {
+        let kind = if d.video { "video" } else { "audio" };
+        let when = if d.at <= now {
+            "due now".yellow().to_string()
+        } else {
+            format!("{} (in {})", format_timestamp(d.at), until(d.at)).dimmed().to_string()
+        };
+        println!("{:>3}. {} {} {}", i + 1, d.job.describe(), format!("[{}]", kind).cyan(), when);
+    }
+    Ok(())
+}
+
+async fn remove(number: usize) -> anyhow::Result<()> {
+    let schedule = load_schedule().await?;
+    let Some(download) = number.checked_sub(1).and_then(|i| schedule.get(i)) else {
+        anyhow::bail!("No scheduled download #{} (see `yt-chill schedule list`)", number);
+    };
+    remove_scheduled(download).await?;
+    println!("{} Cancelled {}", "✓".green(), download.job.describe());
+    Ok(())
+}
+
+/// Start everything that's due; with `wait`, keep going until the schedule is empty
+async fn run_due(wait: bool, defaults: &DownloadOptions, default_jobs: usize) -> anyhow::Result<()> {
+    let mut failed = 0;
+    loop {
+        let schedule = load_schedule().await?;
+        let now = Utc::now().timestamp();
+        let Some(next) = schedule.first() else { break };
+
+        if next.at > now {
+            if !wait {
+                break;
+            }
+            // Re-read the schedule afterwards, it may have been edited meanwhile
+            let secs = (next.at - now).min(60) as u64;
+            tokio::time::sleep(Duration::from_secs(secs)).await;
+            continue;
+        }
+
+        // Off the schedule first, so a crash mid-download doesn't repeat it forever;
+        // the download archive makes a manual rerun cheap anyway
+        remove_scheduled(next).await?;
+        println!("{} {}", "Starting".bold(), next.job.describe());
+        if let Err(e) = start(next, defaults, default_jobs).await {
+            eprintln!("{} {}", "Error:".red(), e);
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} scheduled download(s) had failures", failed);
+    }
+    Ok(())
+}
+
+async fn start(download: &ScheduledDownload, defaults: &DownloadOptions, default_jobs: usize) -> anyhow::Result<()> {
+    let options = DownloadOptions { video: download.video, ..defaults.clone() };
+    let jobs = download.jobs.unwrap_or(default_jobs);
+    match download.job {
+        DownloadJob::Playlist { ref url } => download::playlist(url, &options, jobs).await,
+        DownloadJob::Archive { ref channel, limit, since } => archive::run(channel, limit, since, &options, jobs).await,
+        DownloadJob::Resume => download::run(true, &options).await,
+    }
+}
+
+/// How long until a unix timestamp, e.g. "5h 12m"
+fn until(at: i64) -> String {
+    format_duration(Duration::from_secs((at - Utc::now().timestamp()).max(0) as u64))
+}
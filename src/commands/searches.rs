@@ -0,0 +1,34 @@
+//! `yt-chill searches ...`
+
+use crate::storage::searches::{SearchHistory, clear_searches};
+use clap::Subcommand;
+use colored::Colorize;
+
+#[derive(Subcommand, Debug)]
+pub enum SearchesCommand {
+    /// Show past search queries, newest first
+    List,
+
+    /// Forget all past search queries
+    Clear,
+}
+
+/// Run a searches subcommand
+pub async fn run(action: SearchesCommand, max_entries: usize) -> anyhow::Result<()> {
+    match action {
+        SearchesCommand::List => {
+            let searches = SearchHistory::load(max_entries).await?;
+            if searches.queries().is_empty() {
+                println!("{}", "No searches yet.".yellow());
+            }
+            for query in searches.queries() {
+                println!("{}", query);
+            }
+        }
+        SearchesCommand::Clear => {
+            clear_searches().await?;
+            println!("{} Search history cleared", "✓".green());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,560 @@
+//! `yt-chill daemon` and `yt-chill --serve ADDR`: play the queue in mpv and
+//! take commands from `yt-chill ctl` over a Unix socket, plus (with `--serve`)
+//! a tiny REST API, e.g. for a phone or a Stream Deck
+//!
+//! - `GET /now-playing` — what's on, with elapsed/total, pause state and volume
+//! - `POST /pause` — pause/resume
+//! - `POST /skip` — next track
+//! - `GET /queue` — what's left to play
+//! - `POST /queue` — `{"url": "..."}` or `{"query": "..."}` (first search result)
+//!
+//! Browsers can send requests to localhost from any page, so requests from
+//! a page (with an `Origin`), POSTs that aren't `application/json` and,
+//! without a `serve_token`, Host names other than localhost or an IP are
+//! turned away.
+
+use crate::commands::ctl::CtlCommand;
+use crate::core::events::{self, Event};
+use crate::core::mpv_ipc::{self, MpvIpc};
+use crate::core::{player, youtube};
+use crate::storage::history::History;
+use crate::storage::import::video_id_from_url;
+use crate::storage::queue::{dequeue, enqueue, load_queue};
+use crate::types::{PlayOptions, Video};
+use crate::ui::now_playing::Status;
+use crate::utils::paths::get_control_socket_path;
+use crate::utils::shutdown;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+/// Largest request body we'll read
+const MAX_BODY: usize = 16 * 1024;
+
+/// Longest request or header line we'll read
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most header lines in a request
+const MAX_HEADERS: usize = 64;
+
+/// How long a client gets to send its request (head and body)
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the API can read without asking mpv
+#[derive(Default)]
+struct Shared {
+    status: Status,
+    /// mpv's playlist, in the same order
+    playlist: Vec<Video>,
+    /// Index of the track that's on, if any
+    current: Option<usize>,
+}
+
+/// Work handed from a request to the mpv loop
+enum Request {
+    Command(Vec<&'static str>),
+    Enqueue(Video),
+}
+
+#[derive(Serialize)]
+struct NowPlaying<'a> {
+    video: Option<&'a Video>,
+    #[serde(flatten)]
+    status: &'a Status,
+}
+
+/// Play the queue and take commands (over HTTP too if `http` is given)
+/// until mpv exits. With a `token`, HTTP requests need `Authorization: Bearer <token>`.
+pub async fn run(
+    http: Option<SocketAddr>,
+    token: Option<String>,
+    history: &mut History,
+    opts: &PlayOptions,
+) -> anyhow::Result<()> {
+    let listener = match http {
+        Some(addr) => Some(TcpListener::bind(addr).await?),
+        None => None,
+    };
+    let control_path = get_control_socket_path();
+    let control = bind_control(&control_path).await?;
+    let _player = shutdown::player_running();
+    let socket = mpv_ipc::socket_path();
+    let mut child = player::spawn_mpv_idle(opts, &socket).await?;
+    let mut ipc = tokio::select! {
+        ipc = MpvIpc::connect(&socket) => ipc?,
+        _ = child.wait() => anyhow::bail!("mpv exited before it could be controlled"),
+    };
+    ipc.observe(1, "playlist-pos").await?;
+    for (id, property) in Status::PROPERTIES.iter().enumerate() {
+        ipc.observe(id as u64 + 2, property).await?;
+    }
+
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    for video in load_queue().await? {
+        append(&mut ipc, &shared, video).await?;
+    }
+
+    println!("{} {}", "Control with `yt-chill ctl`, listening on".dimmed(), control_path);
+    if let Some(addr) = http {
+        println!("{} http://{}", "Listening on".dimmed(), addr);
+        if !addr.ip().is_loopback() && token.is_none() {
+            println!("{}", "Anyone who can reach this address can control playback (set serve_token).".yellow());
+        }
+    }
+
+    let token = Arc::new(token);
+    let (requests, mut request_rx) = mpsc::channel(16);
+    // Queued in this session but not yet recorded in history (waiting for a title)
+    let mut untitled: Option<usize> = None;
+    loop {
+        tokio::select! {
+            conn = accept_http(listener.as_ref()) => {
+                let Ok(stream) = conn else { continue };
+                let (shared, requests, token) = (Arc::clone(&shared), requests.clone(), Arc::clone(&token));
+                tokio::spawn(async move {
+                    let _ = handle_http(stream, &shared, &requests, token.as_deref()).await;
+                });
+            }
+            conn = control.accept() => {
+                let Ok((stream, _)) = conn else { continue };
+                let (shared, requests) = (Arc::clone(&shared), requests.clone());
+                tokio::spawn(async move {
+                    let _ = handle_ctl(stream, &shared, &requests).await;
+                });
+            }
+            Some(request) = request_rx.recv() => match request {
+                Request::Command(args) => ipc.command(&args).await?,
+                Request::Enqueue(mut video) => {
+                    if video.title.is_empty()
+                        && let Some(entry) = history.get_all().iter().find(|e| e.video.id == video.id)
+                    {
+                        video = entry.video.clone();
+                    }
+                    enqueue(std::slice::from_ref(&video)).await?;
+                    append(&mut ipc, &shared, video).await?;
+                }
+            },
+            event = ipc.next_event() => {
+                let Some(event) = event else { break };
+                if let Some(pos) = mpv_ipc::property_change(&event, "playlist-pos") {
+                    let played = {
+                        let mut shared = shared.lock().unwrap();
+                        let previous = shared.current.and_then(|i| shared.playlist.get(i)).cloned();
+                        shared.current = pos.as_u64().map(|p| p as usize).filter(|&p| p < shared.playlist.len());
+                        untitled = shared.current.filter(|&i| shared.playlist[i].title.is_empty());
+                        (previous, shared.current.map(|i| shared.playlist[i].clone()))
+                    };
+                    if let Some(previous) = played.0 {
+                        dequeue(&previous.id).await?;
+                        events::emit(Event::TrackFinished { video: &previous, quit: false }).await;
+                    }
+                    if let Some(video) = played.1 {
+                        events::emit(Event::TrackStarted { video: &video }).await;
+                        if !video.title.is_empty() {
+                            history.add(&video).await?;
+                        }
+                    }
+                } else {
+                    let (titled, paused) = {
+                        let mut shared = shared.lock().unwrap();
+                        let paused = shared.status.update(&event).map(|p| (p, shared.status.title().to_string()));
+                        // Videos added by URL get their title from mpv, once
+                        // it's more than the URL
+                        let title = mpv_ipc::property_change(&event, "media-title").and_then(Value::as_str);
+                        let titled = match (untitled, title) {
+                            (Some(i), Some(title)) if Some(i) == shared.current && !title.contains("://") => {
+                                shared.playlist[i].title = title.to_string();
+                                untitled = None;
+                                Some(shared.playlist[i].clone())
+                            }
+                            _ => None,
+                        };
+                        (titled, paused)
+                    };
+                    if let Some((paused, title)) = paused {
+                        events::emit(events::pause_event(paused, &title)).await;
+                    }
+                    if let Some(video) = titled {
+                        history.add(&video).await?;
+                    }
+                }
+            }
+            _ = child.wait() => break,
+        }
+    }
+
+    let _ = std::fs::remove_file(&socket);
+    let _ = std::fs::remove_file(&control_path);
+    Ok(())
+}
+
+/// Listen for `yt-chill ctl`, unless another daemon already is
+async fn bind_control(path: &str) -> anyhow::Result<UnixListener> {
+    if UnixStream::connect(path).await.is_ok() {
+        anyhow::bail!("yt-chill is already running as a daemon (control it with `yt-chill ctl`)");
+    }
+    // Left behind by a daemon that didn't shut down cleanly
+    let _ = std::fs::remove_file(path);
+    Ok(UnixListener::bind(path)?)
+}
+
+/// Next HTTP connection, or never without `--serve`
+async fn accept_http(listener: Option<&TcpListener>) -> std::io::Result<TcpStream> {
+    match listener {
+        Some(listener) => listener.accept().await.map(|(stream, _)| stream),
+        None => std::future::pending().await,
+    }
+}
+
+/// Add a video to mpv's playlist, starting playback if mpv is idle
+async fn append(ipc: &mut MpvIpc, shared: &Mutex<Shared>, video: Video) -> anyhow::Result<()> {
+    let url = player::build_video_url(&video.id);
+    shared.lock().unwrap().playlist.push(video);
+    ipc.command(&["loadfile", &url, "append-play"]).await?;
+    Ok(())
+}
+
+/// Answer one `yt-chill ctl` request: a line of JSON each way
+async fn handle_ctl(stream: UnixStream, shared: &Mutex<Shared>, requests: &mpsc::Sender<Request>) -> anyhow::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(read).read_line(&mut line).await?;
+    let reply = match serde_json::from_str(&line) {
+        Ok(command) => answer(command, shared, requests).await.1,
+        Err(e) => json!({ "error": format!("bad request: {}", e) }),
+    };
+    write.write_all(format!("{}\n", reply).as_bytes()).await?;
+    Ok(())
+}
+
+/// Answer one HTTP request
+async fn handle_http(
+    stream: TcpStream,
+    shared: &Mutex<Shared>,
+    requests: &mpsc::Sender<Request>,
+    token: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let command = match read_request(&mut reader, READ_TIMEOUT).await? {
+        Ok((head, headers, body)) => {
+            let request = parse_request_line(&head);
+            match request.map(|(method, _)| check_request(method, &headers, token)) {
+                Some(Err(error)) => Err(error),
+                _ => route(request, &body),
+            }
+        }
+        Err(error) => Err(error),
+    };
+    let (status, reply) = match command {
+        Ok(command) => answer(command, shared, requests).await,
+        Err(error) => error,
+    };
+
+    let body = reply.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    reader.get_mut().write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// A failed request: status and JSON reply
+type Rejection = (u16, Value);
+
+fn reject(status: u16, error: &str) -> Rejection {
+    (status, json!({ "error": error }))
+}
+
+/// The request line, headers and body, or a rejection if any of them is too
+/// long or the client doesn't send them all within `limit`
+async fn read_request<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    limit: Duration,
+) -> std::io::Result<Result<(String, Vec<(String, String)>, Vec<u8>), Rejection>> {
+    let read = async {
+        let (head, headers) = match read_head(reader).await? {
+            Ok(head) => head,
+            Err(error) => return Ok(Err(error)),
+        };
+        let content_length = header(&headers, "content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+        if content_length > MAX_BODY {
+            return Ok(Err(reject(413, "request body too large")));
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await?;
+        Ok(Ok((head, headers, body)))
+    };
+    match tokio::time::timeout(limit, read).await {
+        Ok(request) => request,
+        Err(_) => Ok(Err(reject(408, "timed out reading the request"))),
+    }
+}
+
+/// The request line and headers (names lowercased), or a rejection if
+/// either is too long
+async fn read_head<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> std::io::Result<Result<(String, Vec<(String, String)>), Rejection>> {
+    let Some(head) = read_limited_line(reader).await? else {
+        return Ok(Err(reject(431, "request line too long")));
+    };
+    let mut headers = Vec::new();
+    loop {
+        let Some(line) = read_limited_line(reader).await? else {
+            return Ok(Err(reject(431, "header line too long")));
+        };
+        if line.trim_end().is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Ok(Err(reject(431, "too many headers")));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    Ok(Ok((head, headers)))
+}
+
+/// One line of at most [`MAX_LINE`] bytes (None if it's longer)
+async fn read_limited_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let read = (&mut *reader).take(MAX_LINE as u64 + 1).read_until(b'\n', &mut line).await?;
+    if read > MAX_LINE {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+/// The value of header `name` (lowercase)
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// Turn away requests a web page could have made, and ones without the token
+fn check_request(method: &str, headers: &[(String, String)], token: Option<&str>) -> Result<(), Rejection> {
+    if header(headers, "origin").is_some() {
+        return Err(reject(403, "requests from web pages aren't allowed"));
+    }
+    match token {
+        Some(token) => {
+            let given = header(headers, "authorization").and_then(|v| v.strip_prefix("Bearer "));
+            if given.map(str::trim) != Some(token) {
+                return Err(reject(401, "missing or wrong token (Authorization: Bearer <serve_token>)"));
+            }
+        }
+        // A page on another site could point its own name at 127.0.0.1
+        None if !header(headers, "host").is_some_and(is_local_host) => {
+            return Err(reject(403, "use localhost or an IP address, or set serve_token"));
+        }
+        None => {}
+    }
+    let json = header(headers, "content-type")
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("application/json"));
+    if method == "POST" && !json {
+        return Err(reject(415, "send Content-Type: application/json"));
+    }
+    Ok(())
+}
+
+/// "localhost" or an IP address, with or without a port
+fn is_local_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(""),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost") || name.parse::<std::net::IpAddr>().is_ok()
+}
+
+/// The command for a request line and body
+fn route(request: Option<(&str, &str)>, body: &[u8]) -> Result<CtlCommand, Rejection> {
+    match request {
+        Some(("GET", "/now-playing")) => Ok(CtlCommand::Status),
+        Some(("POST", "/pause")) => Ok(CtlCommand::Pause),
+        Some(("POST", "/skip")) => Ok(CtlCommand::Next),
+        Some(("GET", "/queue")) => Ok(CtlCommand::Queue),
+        Some(("POST", "/queue")) => match queue_body(body) {
+            Some(query) => Ok(CtlCommand::Add { query: vec![query] }),
+            None => Err(reject(400, "expected a JSON body with \"url\" or \"query\"")),
+        },
+        Some(_) => Err(reject(404, "not found")),
+        None => Err(reject(400, "bad request")),
+    }
+}
+
+/// Carry out a request from either front end
+async fn answer(command: CtlCommand, shared: &Mutex<Shared>, requests: &mpsc::Sender<Request>) -> (u16, Value) {
+    match command {
+        CtlCommand::Status => {
+            let shared = shared.lock().unwrap();
+            let video = shared.current.and_then(|i| shared.playlist.get(i));
+            (200, json!(NowPlaying { video, status: &shared.status }))
+        }
+        CtlCommand::Pause => send_to_mpv(requests, vec!["cycle", "pause"]).await,
+        CtlCommand::Next => send_to_mpv(requests, vec!["playlist-next", "force"]).await,
+        CtlCommand::Queue => {
+            let shared = shared.lock().unwrap();
+            let start = shared.current.unwrap_or(shared.playlist.len());
+            (200, json!(shared.playlist[start..]))
+        }
+        CtlCommand::Add { query } => match resolve(&query.join(" ")).await {
+            Ok(video) => {
+                let reply = json!({ "queued": video });
+                let _ = requests.send(Request::Enqueue(video)).await;
+                (200, reply)
+            }
+            Err(e) => (400, json!({ "error": e.to_string() })),
+        },
+    }
+}
+
+/// Pass a command to mpv
+async fn send_to_mpv(requests: &mpsc::Sender<Request>, args: Vec<&'static str>) -> (u16, Value) {
+    match requests.send(Request::Command(args)).await {
+        Ok(()) => (200, json!({ "ok": true })),
+        Err(_) => (503, json!({ "error": "mpv has stopped" })),
+    }
+}
+
+/// The URL or search terms in a `POST /queue` body
+fn queue_body(body: &[u8]) -> Option<String> {
+    let body: Value = serde_json::from_slice(body).ok()?;
+    body.get("url")
+        .or_else(|| body.get("query"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// The video a URL points to, or the first search result
+async fn resolve(target: &str) -> anyhow::Result<Video> {
+    if target.contains("youtube.com/") || target.contains("youtu.be/") {
+        let id = video_id_from_url(target).ok_or_else(|| anyhow::anyhow!("not a YouTube video URL: {}", target))?;
+        // The title is filled in from history or by mpv
+        return Ok(Video {
+            id,
+            ..Default::default()
+        });
+    }
+    youtube::search_videos(target, 1)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no results for \"{}\"", target))
+}
+
+/// Method and path (without a query string) from "GET /path HTTP/1.1"
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next()?.starts_with("HTTP/").then_some(())?;
+    let path = target.split('?').next().unwrap_or(target).trim_end_matches('/');
+    Some((method, if path.is_empty() { "/" } else { path }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        431 => "Request Header Fields Too Large",
+        _ => "Service Unavailable",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(parse_request_line("GET /now-playing HTTP/1.1\r\n"), Some(("GET", "/now-playing")));
+        assert_eq!(parse_request_line("POST /queue/?x=1 HTTP/1.1"), Some(("POST", "/queue")));
+        assert_eq!(parse_request_line("GET / HTTP/1.0"), Some(("GET", "/")));
+        assert_eq!(parse_request_line("garbage"), None);
+    }
+
+    #[test]
+    fn test_queue_body() {
+        assert_eq!(queue_body(br#"{"url": "https://youtu.be/abc"}"#).as_deref(), Some("https://youtu.be/abc"));
+        assert_eq!(queue_body(br#"{"query": "lofi"}"#).as_deref(), Some("lofi"));
+        assert_eq!(queue_body(b"{}"), None);
+        assert_eq!(queue_body(b"lofi"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_request_rejects_large_bodies() {
+        let mut request: &[u8] = b"POST /queue HTTP/1.1\r\nContent-Length: 99999\r\n\r\n{\"query\": \"lofi\"}";
+        let result = read_request(&mut request, READ_TIMEOUT).await.unwrap();
+        assert_eq!(result.unwrap_err().0, 413);
+
+        let mut request: &[u8] = b"POST /queue HTTP/1.1\r\nContent-Length: 17\r\n\r\n{\"query\": \"lofi\"}";
+        let (_, _, body) = read_request(&mut request, READ_TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(queue_body(&body).as_deref(), Some("lofi"));
+    }
+
+    #[tokio::test]
+    async fn test_read_request_times_out_on_a_short_body() {
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(b"POST /queue HTTP/1.1\r\nContent-Length: 100\r\n\r\n{").await.unwrap();
+
+        // The client stays connected but never sends the rest
+        let mut reader = BufReader::new(server);
+        let result = read_request(&mut reader, Duration::from_millis(50)).await.unwrap();
+        assert_eq!(result.unwrap_err().0, 408);
+        drop(client);
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_check_request() {
+        let json = headers(&[("host", "127.0.0.1:8080"), ("content-type", "application/json; charset=utf-8")]);
+        assert!(check_request("POST", &json, None).is_ok());
+        assert!(check_request("GET", &headers(&[("host", "localhost:8080")]), None).is_ok());
+        assert!(check_request("GET", &headers(&[("host", "[::1]:8080")]), None).is_ok());
+
+        // What a web page can send with fetch(..., { mode: "no-cors" })
+        let page = headers(&[("host", "127.0.0.1:8080"), ("origin", "https://evil.example"), ("content-type", "text/plain")]);
+        assert_eq!(check_request("POST", &page, None).unwrap_err().0, 403);
+        let plain = headers(&[("host", "127.0.0.1:8080"), ("content-type", "text/plain")]);
+        assert_eq!(check_request("POST", &plain, None).unwrap_err().0, 415);
+        // DNS rebinding: the page's own name pointed at 127.0.0.1
+        assert_eq!(check_request("GET", &headers(&[("host", "evil.example:8080")]), None).unwrap_err().0, 403);
+
+        let mut authed = headers(&[("host", "pi.local:8080"), ("authorization", "Bearer s3cret")]);
+        assert!(check_request("GET", &authed, Some("s3cret")).is_ok());
+        assert_eq!(check_request("GET", &authed, Some("other")).unwrap_err().0, 401);
+        authed.pop();
+        assert_eq!(check_request("GET", &authed, Some("s3cret")).unwrap_err().0, 401);
+    }
+
+    #[tokio::test]
+    async fn test_read_head_limits_lines() {
+        let request = b"GET /queue HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let (head, headers) = read_head(&mut &request[..]).await.unwrap().unwrap();
+        assert_eq!(parse_request_line(&head), Some(("GET", "/queue")));
+        assert_eq!(header(&headers, "host"), Some("localhost"));
+
+        let long = format!("GET /queue HTTP/1.1\r\nX-Junk: {}\r\n\r\n", "a".repeat(MAX_LINE));
+        assert_eq!(read_head(&mut long.as_bytes()).await.unwrap().unwrap_err().0, 431);
+    }
+}
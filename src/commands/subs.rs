@@ -0,0 +1,208 @@
+//! `yt-chill subs ...`
+
+use crate::commands::write_output;
+use crate::core::{feed, youtube};
+use crate::storage::import::parse_subscriptions;
+use crate::storage::opml::{parse_opml, to_opml};
+use crate::storage::subscriptions::{
+    find_subscription, known_channel_id, load_subscriptions, merge_subscriptions,
+    remove_subscriptions, rename_subscription, save_subscriptions, update_subscription,
+};
+use crate::types::{MenuItem, Subscription};
+use crate::ui::selector::Selector;
+use clap::Subcommand;
+use colored::Colorize;
+use std::path::PathBuf;
+
+#[derive(Subcommand, Debug)]
+pub enum SubsCommand {
+    /// List subscriptions
+    List,
+
+    /// Unsubscribe (no argument = pick any number from a list)
+    Remove {
+        /// Channel handle or name
+        channel: Option<String>,
+    },
+
+    /// Change a subscription's display name
+    Rename {
+        /// Channel handle or current name
+        channel: String,
+
+        /// New display name
+        name: String,
+    },
+
+    /// Change per-channel feed settings
+    Set {
+        /// Channel handle or name
+        channel: String,
+
+        /// Videos to pull into the feed from this channel
+        #[arg(short, long)]
+        limit: Option<usize>,
+
+        /// Leave the channel out of the feed (true/false)
+        #[arg(long)]
+        muted: Option<bool>,
+
+        /// Include the channel in new-video notifications (true/false)
+        #[arg(long)]
+        notify: Option<bool>,
+    },
+
+    /// Export subscriptions as OPML for RSS readers and other YouTube frontends
+    Export {
+        /// File to write (default: stdout)
+        path: Option<PathBuf>,
+    },
+
+    /// Import subscriptions from OPML, NewPipe JSON or FreeTube profiles
+    Import {
+        /// File to import
+        file: PathBuf,
+    },
+}
+
+/// Run a subscriptions subcommand
+pub async fn run(action: SubsCommand, selector: &Selector) -> anyhow::Result<()> {
+    let subs = load_subscriptions().await?;
+
+    match action {
+        SubsCommand::List => {
+            if subs.is_empty() {
+                println!("{}", "No subscriptions yet. Use --subscribe to add channels.".yellow());
+            }
+            for sub in &subs {
+                let mut flags = Vec::new();
+                if let Some(limit) = sub.limit {
+                    flags.push(format!("limit {}", limit));
+                }
+                if sub.muted {
+                    flags.push("muted".to_string());
+                }
+                if !sub.notify {
+                    flags.push("no notifications".to_string());
+                }
+                let flags = if flags.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", flags.join(", "))
+                };
+                println!("{} {}{}", sub.name, format!("({})", sub.handle).cyan(), flags.dimmed());
+            }
+        }
+        SubsCommand::Remove { channel } => {
+            let targets = match channel {
+                Some(key) => vec![lookup(&subs, &key)?.clone()],
+                None if subs.is_empty() => {
+                    println!("{}", "No subscriptions yet. Use --subscribe to add channels.".yellow());
+                    return Ok(());
+                }
+                None => pick(&subs, selector).await?,
+            };
+
+            if !targets.is_empty() {
+                let handles: Vec<String> = targets.iter().map(|s| s.handle.clone()).collect();
+                remove_subscriptions(&handles).await?;
+                for sub in &targets {
+                    println!("{} Unsubscribed from {}", "✓".green(), sub.name);
+                }
+            }
+        }
+        SubsCommand::Rename { channel, name } => {
+            let sub = lookup(&subs, &channel)?;
+            rename_subscription(&sub.handle, &name).await?;
+            println!("{} Renamed {} to {}", "✓".green(), sub.name, name);
+        }
+        SubsCommand::Set { channel, limit, muted, notify } => {
+            let sub = lookup(&subs, &channel)?;
+            if limit.is_none() && muted.is_none() && notify.is_none() {
+                anyhow::bail!("Nothing to change (use --limit, --muted or --notify)");
+            }
+            update_subscription(&sub.handle, |s| {
+                if let Some(limit) = limit {
+                    s.limit = Some(limit);
+                }
+                if let Some(muted) = muted {
+                    s.muted = muted;
+                }
+                if let Some(notify) = notify {
+                    s.notify = notify;
+                }
+            })
+            .await?;
+            println!("{} Updated {}", "✓".green(), sub.name);
+        }
+        SubsCommand::Export { path } => export(subs, path).await?,
+        SubsCommand::Import { file } => {
+            let content = tokio::fs::read_to_string(&file).await?;
+            let imported = if content.trim_start().starts_with('<') {
+                parse_opml(&content)
+            } else {
+                parse_subscriptions(&content)?
+            };
+            if imported.is_empty() {
+                anyhow::bail!("No YouTube channel feeds found in {}", file.display());
+            }
+            let found = imported.len();
+            let stats = merge_subscriptions(imported).await?;
+            println!(
+                "{} Imported {} channels: {} new, {} updated",
+                "✓".green(),
+                found,
+                stats.added,
+                stats.updated
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Export as OPML, looking up channel IDs we don't know yet
+async fn export(mut subs: Vec<Subscription>, path: Option<PathBuf>) -> anyhow::Result<()> {
+    let mut resolved_any = false;
+    for sub in subs.iter_mut().filter(|s| known_channel_id(s).is_none()) {
+        match youtube::resolve_channel_id(&sub.handle).await {
+            Ok(id) => {
+                sub.channel_id = Some(id);
+                resolved_any = true;
+            }
+            Err(e) => eprintln!("{} Skipping {}: {}", "Warning:".yellow(), sub.name, e),
+        }
+    }
+
+    // Remember resolved IDs so the next export is offline
+    if resolved_any {
+        save_subscriptions(&subs).await?;
+    }
+
+    write_output(&to_opml(&subs), path).await
+}
+
+/// Find a subscription or explain what's available
+fn lookup<'a>(subs: &'a [Subscription], key: &str) -> anyhow::Result<&'a Subscription> {
+    find_subscription(subs, key)
+        .ok_or_else(|| anyhow::anyhow!("No subscription matches \"{}\" (see `yt-chill subs list`)", key))
+}
+
+/// Choose subscriptions to remove with the selector, showing when each
+/// channel last uploaded so quiet ones stand out
+async fn pick(subs: &[Subscription], selector: &Selector) -> anyhow::Result<Vec<Subscription>> {
+    println!("{}", "Checking latest uploads...".dimmed());
+    // Recently loaded feeds are reused
+    let loaded = feed::load_feed(subs, false).await?;
+    let items: Vec<MenuItem<Subscription>> = loaded
+        .channels
+        .into_iter()
+        .map(|(sub, videos)| {
+            let mut label = format!("{} ({})", sub.name, sub.handle.cyan());
+            if let Some(video) = videos.first().filter(|v| !v.published.is_empty()) {
+                label.push_str(&format!("  {}", format!("last upload {}", video.published).dimmed()));
+            }
+            MenuItem::new(label, sub)
+        })
+        .collect();
+    Ok(selector.select_many(&items, "Unsubscribe from"))
+}
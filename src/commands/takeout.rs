@@ -0,0 +1,82 @@
+//! `yt-chill takeout <DIR>`: seed subscriptions and history from Google Takeout
+
+use crate::storage::history::History;
+use crate::storage::import::{parse_history, parse_subscriptions};
+use crate::storage::subscriptions::merge_subscriptions;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+const SUBSCRIPTIONS_FILE: &str = "subscriptions.csv";
+const WATCH_HISTORY_FILE: &str = "watch-history.json";
+
+/// Import everything we understand from an extracted Takeout archive (or a single file)
+pub async fn run(path: &Path, history: &mut History) -> anyhow::Result<()> {
+    let files = if path.is_dir() {
+        find_files(path)
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut imported_any = false;
+    for file in files {
+        let name = file.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let content = tokio::fs::read_to_string(&file).await?;
+
+        if name == SUBSCRIPTIONS_FILE {
+            let subs = parse_subscriptions(&content)?;
+            let found = subs.len();
+            let stats = merge_subscriptions(subs).await?;
+            println!(
+                "{} Subscriptions: {} found, {} new",
+                "✓".green(),
+                found,
+                stats.added
+            );
+            imported_any = true;
+        } else if name == WATCH_HISTORY_FILE {
+            let entries = parse_history(&content)?;
+            let found = entries.len();
+            let stats = history.merge(entries).await?;
+            println!(
+                "{} History: {} videos found, {} new, {} updated",
+                "✓".green(),
+                found,
+                stats.added,
+                stats.updated
+            );
+            imported_any = true;
+        }
+    }
+
+    if !imported_any {
+        anyhow::bail!(
+            "No {} or {} found in {} (export YouTube data as JSON from takeout.google.com)",
+            SUBSCRIPTIONS_FILE,
+            WATCH_HISTORY_FILE,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Recursively find the Takeout files we can import
+fn find_files(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return found;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            found.extend(find_files(&path));
+        } else if path
+            .file_name()
+            .is_some_and(|n| n == SUBSCRIPTIONS_FILE || n == WATCH_HISTORY_FILE)
+        {
+            found.push(path);
+        }
+    }
+
+    found
+}
@@ -0,0 +1,120 @@
+//! `yt-chill watch`: poll subscriptions and notify about new uploads
+
+use crate::core::blocklist::Blocklist;
+use crate::core::{feed, player, youtube};
+use crate::storage::history::History;
+use crate::storage::last_seen::{load_last_seen, new_uploads, save_last_seen};
+use crate::storage::subscriptions::{load_subscriptions, save_channel_ids};
+use crate::types::{Config, PlayOptions, Video};
+use crate::utils::notify;
+use crate::utils::time::{format_duration, parse_duration};
+use colored::Colorize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Videos fetched per channel on each check
+const CHECK_LIMIT: usize = 10;
+
+/// Parse `--interval`
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    parse_duration(s)
+        .filter(|d| !d.is_zero())
+        .ok_or_else(|| format!("invalid interval '{}' (try 15m, 1h)", s))
+}
+
+/// Check every `interval` until interrupted (or once, with `once`)
+pub async fn run(
+    interval: Duration,
+    once: bool,
+    config: &Config,
+    history: &mut History,
+    options: &PlayOptions,
+) -> anyhow::Result<()> {
+    let blocklist = Blocklist::from_config(config);
+
+    // Clicked notifications send their video back here to be played
+    let (play_tx, mut play_rx) = mpsc::unbounded_channel::<Video>();
+
+    if !once {
+        println!(
+            "{} (every {}, Ctrl+C to stop)",
+            "Watching subscriptions for new uploads".dimmed(),
+            format_duration(interval)
+        );
+    }
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let found = check(&blocklist, config.notify && !once, &play_tx).await?;
+                if once {
+                    if found == 0 {
+                        println!("{}", "No new uploads.".dimmed());
+                    }
+                    return Ok(());
+                }
+            }
+            Some(video) = play_rx.recv() => {
+                println!("{} {}", "Playing:".dimmed(), video.title);
+                player::play_video(&video, options).await?;
+                history.add(&video).await?;
+            }
+        }
+    }
+}
+
+/// Fetch every notifying subscription once, announcing anything new.
+/// Returns the number of new uploads.
+async fn check(
+    blocklist: &Blocklist,
+    desktop: bool,
+    play_tx: &mpsc::UnboundedSender<Video>,
+) -> anyhow::Result<usize> {
+    let subs = load_subscriptions().await?;
+    let mut last_seen = load_last_seen().await?;
+    let mut found = 0;
+    let mut looked_up = Vec::new();
+
+    for sub in subs.iter().filter(|s| s.notify) {
+        let videos = match feed::channel_id(sub, &mut looked_up).await {
+            Ok(id) => youtube::fetch_channel_videos(&id, CHECK_LIMIT).await,
+            Err(e) => Err(e),
+        };
+        let videos = match videos {
+            Ok(videos) => videos,
+            // Offline or rate limited: the other channels would fail too
+            Err(e) if e.is_retryable() => {
+                eprintln!("{} {}", "Skipping this check:".yellow(), e);
+                break;
+            }
+            Err(_) => continue,
+        };
+
+        for video in new_uploads(&videos, last_seen.get(&sub.handle).map(String::as_str)) {
+            if blocklist.blocks(video) {
+                continue;
+            }
+            found += 1;
+            println!("🆕 {} {}", format!("{}:", sub.name).cyan(), video.title);
+
+            if desktop {
+                let (title, body) = (format!("New from {}", sub.name), video.title.clone());
+                let (video, play_tx) = (video.clone(), play_tx.clone());
+                tokio::spawn(async move {
+                    if notify::send_with_play_action(&title, &body).await {
+                        let _ = play_tx.send(video);
+                    }
+                });
+            }
+        }
+
+        if let Some(newest) = videos.first() {
+            last_seen.insert(sub.handle.clone(), newest.id.clone());
+        }
+    }
+
+    save_last_seen(&last_seen).await?;
+    save_channel_ids(&looked_up).await?;
+    Ok(found)
+}
@@ -0,0 +1,147 @@
+//! Channel, keyword and length filters applied to search and feed results
+
+use crate::types::{Config, Video};
+use crate::utils::time::{parse_clock, parse_duration};
+use std::time::Duration;
+
+/// Blocked channel names and title keywords, lowercased for matching
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist {
+    channels: Vec<String>,
+    keywords: Vec<String>,
+    /// Hide live streams too (`--no-live`)
+    live: bool,
+    /// Shortest and longest videos shown, in seconds
+    min_secs: Option<u64>,
+    max_secs: Option<u64>,
+}
+
+impl Blocklist {
+    /// Build from `blocked_channels` / `blocked_keywords` and
+    /// `min_duration` / `max_duration` in the config
+    pub fn from_config(config: &Config) -> Self {
+        let lower = |items: &[String]| -> Vec<String> {
+            items
+                .iter()
+                .map(|i| i.trim().to_lowercase())
+                .filter(|i| !i.is_empty())
+                .collect()
+        };
+        Self {
+            channels: lower(&config.blocked_channels),
+            keywords: lower(&config.blocked_keywords),
+            live: false,
+            min_secs: parse_duration(&config.min_duration).map(|d| d.as_secs()),
+            max_secs: parse_duration(&config.max_duration).map(|d| d.as_secs()),
+        }
+    }
+
+    /// Override the config's length bounds (`--min-duration` / `--max-duration`)
+    pub fn durations(mut self, min: Option<Duration>, max: Option<Duration>) -> Self {
+        if let Some(min) = min {
+            self.min_secs = Some(min.as_secs());
+        }
+        if let Some(max) = max {
+            self.max_secs = Some(max.as_secs());
+        }
+        self
+    }
+
+    /// Also hide live streams
+    pub fn hide_live(mut self, hide: bool) -> Self {
+        self.live = hide;
+        self
+    }
+
+    /// Whether a video should be hidden
+    pub fn blocks(&self, video: &Video) -> bool {
+        if self.live && video.is_live() {
+            return true;
+        }
+        // Videos of unknown length (live streams, premieres) are kept
+        let secs = video.duration_secs.or_else(|| parse_clock(&video.duration));
+        if let Some(secs) = secs
+            && (self.min_secs.is_some_and(|min| secs < min) || self.max_secs.is_some_and(|max| secs > max))
+        {
+            return true;
+        }
+        let author = video.author.to_lowercase();
+        if self.channels.contains(&author) {
+            return true;
+        }
+        let title = video.title.to_lowercase();
+        self.keywords.iter().any(|k| title.contains(k.as_str()))
+    }
+
+    /// Drop blocked videos, returning how many were removed
+    pub fn filter(&self, videos: &mut Vec<Video>) -> usize {
+        let before = videos.len();
+        videos.retain(|v| !self.blocks(v));
+        before - videos.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(title: &str, author: &str) -> Video {
+        Video {
+            title: title.into(),
+            author: author.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_blocks_channels_and_keywords() {
+        let config = Config {
+            blocked_channels: vec!["Loud Reacts".into()],
+            blocked_keywords: vec!["REACTION".into()],
+            ..Default::default()
+        };
+        let mut videos = vec![
+            video("lofi hip hop radio", "Lofi Girl"),
+            video("My reaction to lofi", "Someone"),
+            video("jazz", "loud reacts"),
+        ];
+
+        assert_eq!(Blocklist::from_config(&config).filter(&mut videos), 2);
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].author, "Lofi Girl");
+    }
+
+    #[test]
+    fn test_hides_live_streams() {
+        let mut live = video("lofi hip hop radio", "Lofi Girl");
+        live.duration = "LIVE".into();
+        let mut videos = vec![live, video("jazz", "Someone")];
+
+        let blocklist = Blocklist::from_config(&Config::default());
+        assert_eq!(blocklist.clone().filter(&mut videos), 0);
+        assert_eq!(blocklist.hide_live(true).filter(&mut videos), 1);
+        assert_eq!(videos[0].title, "jazz");
+    }
+
+    #[test]
+    fn test_duration_bounds() {
+        let mut short = video("lofi in 3 minutes", "Someone");
+        short.duration = "3:00".into();
+        let mut mix = video("1 hour lofi mix", "Lofi Girl");
+        mix.duration_secs = Some(3600);
+        let mut loop_ = video("10 hours of rain", "Rain");
+        loop_.duration = "10:00:00".into();
+        let mut live = video("lofi hip hop radio", "Lofi Girl");
+        live.duration = "LIVE".into();
+
+        let config = Config {
+            min_duration: "1h".into(),
+            ..Default::default()
+        };
+        let mut videos = vec![short, mix, loop_, live];
+        let blocklist = Blocklist::from_config(&config).durations(None, Some(Duration::from_secs(3 * 3600)));
+        assert_eq!(blocklist.filter(&mut videos), 2);
+        assert_eq!(videos[0].title, "1 hour lofi mix");
+        assert_eq!(videos[1].title, "lofi hip hop radio");
+    }
+}
@@ -1,69 +1,1128 @@
 //! Downloader module - yt-dlp integration
 
 use crate::error::{Result, YtChillError};
-use crate::types::DownloadOptions;
-use indicatif::{ProgressBar, ProgressStyle};
-use tokio::process::Command;
+use crate::core::events::{self, Event};
+use crate::core::player::{build_video_url, capped_video_format};
+use crate::core::{youtube, ytdlp};
+use crate::storage::downloads::{clear_pending, find_download, mark_pending, record_download};
+use crate::types::{
+    Chapter, DownloadOptions, DownloadRecord, LiveStatus, OrganizeBy, PendingDownload, Video, VideoFormat, VideoMetadata,
+};
+use crate::ui::format::{fit, fit_start, terminal_width};
+use crate::utils::notify;
+use crate::utils::paths::{ensure_dir, expand_tilde, free_space, get_partial_downloads_dir};
+use crate::utils::time::format_clock;
+use colored::Colorize;
+use futures_util::{StreamExt, stream};
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tracing::debug;
 
-/// Download audio/video using yt-dlp
-pub async fn download(url: &str, options: &DownloadOptions) -> Result<()> {
-    if !is_command_available("yt-dlp").await {
-        return Err(YtChillError::MissingDependency("yt-dlp".into()));
+/// Marker for our progress lines in yt-dlp's stdout
+const PROGRESS_PREFIX: &str = "ytc-progress";
+
+/// yt-dlp progress template: downloaded, total (or estimate), speed, ETA
+const PROGRESS_TEMPLATE: &str = "download:ytc-progress %(progress.downloaded_bytes)s \
+%(progress.total_bytes,progress.total_bytes_estimate)s %(progress.speed)s %(progress.eta)s";
+
+/// Marker for the line with the finished file's length
+const DURATION_PREFIX: &str = "ytc-duration";
+
+/// Default yt-dlp output template (the ID lets us find the file again later)
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "%(title)s [%(id)s].%(ext)s";
+
+/// Check an output template is safe to use: relative to the download dir,
+/// not escaping it, and keeping yt-dlp's extension
+pub fn check_output_template(template: &str) -> std::result::Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("is empty".into());
+    }
+    if template.starts_with('/') || template.starts_with('~') {
+        return Err("must be relative to download_dir".into());
+    }
+    if template.split(['/', '\\']).any(|part| part == "..") {
+        return Err("must not contain `..`".into());
+    }
+    if !template.ends_with(".%(ext)s") {
+        return Err("must end with `.%(ext)s`".into());
     }
+    Ok(())
+}
 
-    let mut args = Vec::new();
+/// Prefix the output template with a per-channel or per-artist folder
+/// (yt-dlp creates the folders and sanitizes the names)
+fn organized_template(template: &str, organize: OrganizeBy) -> String {
+    match organize {
+        OrganizeBy::Off => template.to_string(),
+        OrganizeBy::Channel => format!("%(uploader)s/{}", template),
+        OrganizeBy::Artist => format!("%(artist,uploader)s/{}", template),
+    }
+}
+
+/// Whether `rate` is a yt-dlp rate like "500K", "1.5M" or "2000000"
+pub fn is_valid_rate(rate: &str) -> bool {
+    let number = rate.strip_suffix(['K', 'k', 'M', 'm', 'G', 'g']).unwrap_or(rate);
+    !number.is_empty() && number.parse::<f64>().is_ok_and(|n| n > 0.0)
+}
+
+/// Whether `quality` is a yt-dlp audio quality: 0-10 or a bitrate like "192K"
+pub fn is_valid_audio_quality(quality: &str) -> bool {
+    quality.parse::<u8>().map_or_else(|_| is_valid_rate(quality), |q| q <= 10)
+}
+
+/// One progress update from yt-dlp
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Progress {
+    downloaded: u64,
+    total: Option<u64>,
+    /// Bytes per second
+    speed: Option<f64>,
+    /// Seconds remaining
+    eta: Option<u64>,
+}
+
+/// Parse a line printed via PROGRESS_TEMPLATE (missing fields are "NA")
+fn parse_progress(line: &str) -> Option<Progress> {
+    let mut fields = line.trim().strip_prefix(PROGRESS_PREFIX)?.split_whitespace();
+    let mut next = || fields.next().and_then(|f| f.parse::<f64>().ok());
+
+    let downloaded = next()? as u64;
+    let total = next().map(|t| t as u64);
+    let speed = next();
+    let eta = next().map(|e| e as u64);
+
+    Some(Progress { downloaded, total, speed, eta })
+}
+
+/// Download audio/video using yt-dlp, skipping videos already in the archive
+pub async fn download(video: &Video, options: &DownloadOptions) -> Result<()> {
+    if let Some(existing) = archived(video, options).await? {
+        println!(
+            "{} Already downloaded at {} {}",
+            "✓".green(),
+            existing.path,
+            "(use --force to download again)".dimmed()
+        );
+        return Ok(());
+    }
+
+    ytdlp::ensure_available().await?;
+
+    let bar = progress_bar(None);
+    bar.set_message("Checking disk space...");
+    if let Err(e) = preflight(&[video], options).await {
+        bar.finish_and_clear();
+        return Err(e);
+    }
+    let result = run_ytdlp(video, options, &bar).await;
+    if options.notify {
+        match &result {
+            Ok(_) => notify::send("Download finished", &video.title).await,
+            Err(e) => notify::send("Download failed", &format!("{}: {}", video.title, e)).await,
+        }
+    }
+    let file = result?;
+    println!("{} Download complete!", "✓".green());
+    print_downloads(std::slice::from_ref(&file));
+    Ok(())
+}
+
+/// A finished download
+#[derive(Debug, Clone)]
+pub struct DownloadedFile {
+    pub video: Video,
+    /// Where yt-dlp put it
+    pub path: Option<String>,
+    /// Bytes on disk
+    pub size: Option<u64>,
+    pub duration_secs: Option<u64>,
+    /// Whether tags and cover art went in (None = not asked for)
+    pub embedded: Option<bool>,
+}
+
+/// A table of finished downloads: file, size, length and tags
+pub fn print_downloads(files: &[DownloadedFile]) {
+    const SIZE_WIDTH: usize = 10;
+    const LENGTH_WIDTH: usize = 8;
+    const TAGS_WIDTH: usize = 4;
+    if files.is_empty() {
+        return;
+    }
+
+    let names: Vec<&str> = files
+        .iter()
+        .map(|f| f.path.as_deref().unwrap_or(&f.video.title))
+        .collect();
+    let fixed = 2 + SIZE_WIDTH + LENGTH_WIDTH + TAGS_WIDTH + 6;
+    let room = terminal_width().unwrap_or(100).saturating_sub(fixed).max(20);
+    let width = names.iter().map(|n| n.chars().count()).max().unwrap_or(0).clamp(4, room);
+
+    println!(
+        "  {}",
+        format!("{}  {:>SIZE_WIDTH$}  {:>LENGTH_WIDTH$}  {}", fit("File", width), "Size", "Length", "Tags").dimmed()
+    );
+    for (file, name) in files.iter().zip(names) {
+        let size = file.size.map(|s| HumanBytes(s).to_string()).unwrap_or_else(|| "?".into());
+        let length = file.duration_secs.map(format_clock).unwrap_or_else(|| "?".into());
+        let tags = match file.embedded {
+            Some(true) => "✓".green(),
+            Some(false) => "✗".red(),
+            None => "-".dimmed(),
+        };
+        println!(
+            "  {}  {:>SIZE_WIDTH$}  {:>LENGTH_WIDTH$}  {}",
+            fit_start(name, width),
+            size,
+            length,
+            tags
+        );
+    }
+}
 
-    // Audio-only by default (download as MP3), unless --video flag is passed
+/// What [`download_many`] did
+#[derive(Debug, Default)]
+pub struct DownloadSummary {
+    pub downloaded: Vec<DownloadedFile>,
+    /// Already in the download archive
+    pub skipped: usize,
+    pub failed: Vec<(Video, YtChillError)>,
+}
+
+/// Download several videos, `jobs` at a time. Videos already in the archive
+/// are weeded out before anything starts, so the overall bar only counts
+/// real downloads.
+pub async fn download_many(videos: &[Video], options: &DownloadOptions, jobs: usize) -> Result<DownloadSummary> {
+    let mut summary = DownloadSummary::default();
+    let mut todo = Vec::new();
+    for video in videos {
+        if todo.iter().any(|v: &&Video| v.id == video.id) {
+            continue;
+        }
+        if archived(video, options).await?.is_some() {
+            summary.skipped += 1;
+        } else {
+            todo.push(video);
+        }
+    }
+
+    if summary.skipped > 0 {
+        println!(
+            "{}",
+            format!("Skipping {} already downloaded (use --force to download again)", summary.skipped).dimmed()
+        );
+    }
+    if todo.is_empty() {
+        return Ok(summary);
+    }
+    ytdlp::ensure_available().await?;
+
+    if todo.len() > 1 {
+        println!("{}", "Checking disk space...".dimmed());
+    }
+    preflight(&todo, options).await?;
+
+    let multi = MultiProgress::new();
+    let overall = multi.add(ProgressBar::new(todo.len() as u64));
+    overall.set_style(
+        ProgressStyle::default_bar()
+            .template("{prefix:.bold} [{bar:30.cyan/dim}] {pos}/{len} videos")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    overall.set_prefix("Total");
+
+    let multi = &multi;
+    let mut results = stream::iter(todo)
+        .map(|video| {
+            let bar = multi.insert_before(&overall, progress_bar(Some(&video.title)));
+            async move {
+                let result = run_ytdlp(video, options, &bar).await;
+                multi.remove(&bar);
+                (video, result)
+            }
+        })
+        .buffer_unordered(jobs.max(1));
+
+    // Above the bars, or plain lines when there's no terminal to draw them on
+    let report = |line: String| {
+        if multi.is_hidden() {
+            println!("{}", line);
+        } else {
+            let _ = multi.println(line);
+        }
+    };
+    while let Some((video, result)) = results.next().await {
+        overall.inc(1);
+        match result {
+            Ok(file) => {
+                report(format!("{} {}", "✓".green(), video.title));
+                summary.downloaded.push(file);
+            }
+            Err(e) => {
+                report(format!("{} {}: {}", "✗".red(), video.title, e));
+                summary.failed.push((video.clone(), e));
+            }
+        }
+    }
+    overall.finish_and_clear();
+
+    if options.notify {
+        let (title, body) = notification(&summary);
+        notify::send(&title, &body).await;
+    }
+    Ok(summary)
+}
+
+/// Title and body of the notification after [`download_many`]
+fn notification(summary: &DownloadSummary) -> (String, String) {
+    let title = if summary.failed.is_empty() { "Downloads finished" } else { "Downloads finished with errors" };
+    let mut body = format!("{} downloaded", summary.downloaded.len());
+    if !summary.failed.is_empty() {
+        body.push_str(&format!(", {} failed", summary.failed.len()));
+    }
+    (title.to_string(), body)
+}
+
+/// Make sure the downloads fit before starting any, rather than have yt-dlp
+/// fail halfway with a full disk. Sizes yt-dlp can't tell are left out.
+async fn preflight(videos: &[&Video], options: &DownloadOptions) -> Result<()> {
+    let sizes = estimate_sizes(videos, options).await;
+    let needed: u64 = videos.iter().filter_map(|v| sizes.get(&v.id)).sum();
+    debug!("Downloads need about {} ({} of {} sizes known)", HumanBytes(needed), sizes.len(), videos.len());
+    // .part files go to the temp dir first, which can be on another disk
+    let output_dir = expand_tilde(&options.output_dir);
+    check_space(needed, &[&output_dir, &get_partial_downloads_dir()])
+}
+
+/// Fail if any of `dirs` has less than `needed` bytes free, plus a margin
+/// since estimates are rough and audio conversion can grow files
+fn check_space(needed: u64, dirs: &[&str]) -> Result<()> {
+    let needed = needed.saturating_add(needed / 10);
+    for dir in dirs {
+        if let Some(free) = free_space(dir)
+            && free < needed
+        {
+            return Err(YtChillError::DiskFull(format!(
+                "{} has {} free, but the download needs about {}",
+                dir,
+                HumanBytes(free),
+                HumanBytes(needed)
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Where the size estimate leaves each video's info for the download to
+/// load, so YouTube's pages are only fetched and parsed once per video
+fn info_json_path(id: &str) -> PathBuf {
+    info_dir().join(format!("{}.info.json", id))
+}
+
+fn info_dir() -> PathBuf {
+    Path::new(&get_partial_downloads_dir()).join("info")
+}
+
+/// How many videos' metadata the size estimate fetches at once
+const METADATA_JOBS: usize = 4;
+
+/// Expected download size by video ID, from each video's metadata (see
+/// [`fetch_metadata`]) and the formats the download will pick. Videos whose
+/// size can't be told are left out. A fresh extraction is kept (see
+/// [`info_json_path`]) for [`run_ytdlp`].
+async fn estimate_sizes(videos: &[&Video], options: &DownloadOptions) -> HashMap<String, u64> {
+    // A leftover info file's stream URLs may have expired
+    for video in videos {
+        let _ = tokio::fs::remove_file(info_json_path(&video.id)).await;
+    }
+    let access = access_args(options);
+    stream::iter(videos)
+        .map(|video| {
+            let access = &access;
+            async move {
+                let info = info_json_path(&video.id);
+                match extract_metadata(&build_video_url(&video.id), access, Some(&info)).await {
+                    Ok(meta) => planned_size(&meta.formats, options).map(|size| (video.id.clone(), size)),
+                    Err(e) => {
+                        debug!("Couldn't estimate the size of {}: {}", video.id, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(METADATA_JOBS)
+        .filter_map(std::future::ready)
+        .collect()
+        .await
+}
+
+/// The last (best) of `formats` that `keep` accepts; yt-dlp lists them worst first
+fn best(formats: &[VideoFormat], keep: impl Fn(&VideoFormat) -> bool) -> Option<&VideoFormat> {
+    formats.iter().rev().find(|f| keep(f))
+}
+
+/// Size of the formats the download will pick from `formats`, following
+/// yt-dlp's defaults for the choice in [`selection_args`]. None if a picked
+/// format's size is unknown, or `--format` is more than plain format codes.
+fn planned_size(formats: &[VideoFormat], options: &DownloadOptions) -> Option<u64> {
+    let audio_only = |f: &VideoFormat| f.has_audio && !f.has_video;
+    let picked: Vec<&VideoFormat> = match options.format {
+        // e.g. "251" or "137+140"; selectors like "bestaudio[ext=m4a]" are left to yt-dlp
+        Some(ref format) => format
+            .split('+')
+            .map(|code| formats.iter().find(|f| f.code == code))
+            .collect::<Option<_>>()?,
+        // -x: bestaudio/best
+        None if !options.video => vec![best(formats, audio_only).or_else(|| best(formats, |f| f.has_audio))?],
+        // bestvideo+bestaudio/best, under the max_resolution cap
+        None => {
+            let cap = options.max_height.unwrap_or(u32::MAX);
+            let fits = |f: &VideoFormat| f.height.is_some_and(|height| height <= cap);
+            match (best(formats, |f| f.has_video && !f.has_audio && fits(f)), best(formats, audio_only)) {
+                (Some(video), Some(audio)) => vec![video, audio],
+                _ => vec![best(formats, |f| f.has_video && f.has_audio && fits(f))?],
+            }
+        }
+    };
+    picked.iter().map(|f| f.filesize).sum()
+}
+
+/// The format choice and the settings that decide which formats YouTube
+/// offers
+fn selection_args(options: &DownloadOptions) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+
+    // Audio-only by default, unless --video flag is passed
     if !options.video {
-        args.extend(["-x", "--audio-format", "mp3"]);
+        args.extend(["-x".into(), "--audio-format".into(), options.audio_format.as_str().into()]);
+        if let Some(ref quality) = options.audio_quality {
+            args.extend(["--audio-quality".into(), quality.clone()]);
+        }
     } else {
-        args.extend(["--remux-video", "mp4"]);
+        args.extend(["--remux-video".into(), "mp4".into()]);
     }
 
-    if let Some(ref format) = options.format {
-        args.extend(["--format", format]);
+    // --remux-video above handles merged streams, so the cap can use them
+    let capped = options
+        .max_height
+        .filter(|_| options.video && options.format.is_none())
+        .map(|height| capped_video_format(height, false));
+    if let Some(format) = options.format.clone().or(capped) {
+        args.extend(["--format".into(), format]);
     }
 
-    // Output template
-    let output_template = format!("{}/%(title)s [%(id)s].%(ext)s", options.output_dir);
-    args.extend(["-o", &output_template]);
-    args.push(url);
-
-    // Show progress spinner
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
+    args.extend(access_args(options));
+    args
+}
+
+/// Cookies and proxy: what YouTube needs to hand over the video at all
+fn access_args(options: &DownloadOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(ref browser) = options.cookies_from_browser {
+        args.extend(["--cookies-from-browser".into(), browser.clone()]);
+    }
+
+    if let Some(ref proxy) = options.proxy {
+        args.extend(["--proxy".into(), proxy.clone()]);
+    }
+    args
+}
+
+/// The archive's record of this video, unless `force` asks to download it again
+async fn archived(video: &Video, options: &DownloadOptions) -> Result<Option<DownloadRecord>> {
+    if options.force {
+        return Ok(None);
+    }
+    find_download(&video.id, options.video).await
+}
+
+/// A download progress bar, with the title in front when several run at once
+fn progress_bar(title: Option<&str>) -> ProgressBar {
+    let template = match title {
+        Some(_) => "{spinner:.green} {prefix} [{bar:20.green/dim}] {bytes}/{total_bytes} {msg}",
+        None => "{spinner:.green} [{bar:30.green/dim}] {bytes}/{total_bytes} {msg}",
+    };
+    let bar = ProgressBar::new(0);
+    bar.set_style(ProgressStyle::default_bar().template(template).unwrap().progress_chars("=> "));
+    if let Some(title) = title {
+        bar.set_prefix(fit(title, 30));
+    }
+    bar.set_message("Starting...");
+    bar.enable_steady_tick(std::time::Duration::from_millis(100));
+    bar
+}
+
+/// Run yt-dlp for one video, showing progress on `bar`, and record it in the
+/// archive
+async fn run_ytdlp(video: &Video, options: &DownloadOptions, bar: &ProgressBar) -> Result<DownloadedFile> {
+    let url = build_video_url(&video.id);
+
+    let selection = selection_args(options);
+    let mut args: Vec<&str> = selection.iter().map(String::as_str).collect();
+
+    // Tags and cover art. The channel is the artist unless the title says otherwise.
+    let title_metadata = options
+        .title_pattern
+        .as_ref()
+        .map(|pattern| format!("title:{}", pattern));
+    if options.embed_metadata {
+        args.extend(["--embed-metadata", "--embed-thumbnail", "--convert-thumbnails", "jpg"]);
+    }
+    // Artist is also needed to pick the folder when organizing by artist
+    if options.embed_metadata || options.organize == OrganizeBy::Artist {
+        args.extend(["--parse-metadata", "uploader:%(artist)s"]);
+        if let Some(ref parse) = title_metadata {
+            args.extend(["--parse-metadata", parse]);
+        }
+    }
+
+    if let Some(ref rate) = options.limit_rate {
+        args.extend(["--limit-rate", rate]);
+    }
+
+    // e.g. aria2c for multi-connection fragment downloads
+    let downloader_args = options
+        .external_downloader
+        .as_ref()
+        .zip(options.external_downloader_args.as_ref())
+        .map(|(name, extra)| format!("{}:{}", name, extra));
+    if let Some(ref name) = options.external_downloader {
+        args.extend(["--downloader", name]);
+    }
+    if let Some(ref extra) = downloader_args {
+        args.extend(["--downloader-args", extra]);
+    }
+
+    // Download and convert in our own staging dir, so .part files never land in
+    // the download dir and `download --resume` can pick them up. The finished
+    // file is moved over below.
+    let staging_dir = get_partial_downloads_dir();
+    ensure_dir(&staging_dir).await?;
+    args.extend(["--continue", "-P", &staging_dir]);
+
+    // Output template (relative to the output dir)
+    let output_template = organized_template(
+        options.output_template.as_deref().unwrap_or(DEFAULT_OUTPUT_TEMPLATE),
+        options.organize,
     );
-    spinner.set_message("Downloading...");
-    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+    args.extend(["-o", &output_template]);
+    // The info the size estimate fetched, if it's there, instead of extracting again
+    let info_json = info_json_path(&video.id);
+    let info_json_arg = info_json.to_string_lossy().into_owned();
+    if info_json.exists() {
+        args.extend(["--load-info-json", &info_json_arg]);
+    } else {
+        args.push(&url);
+    }
+
+    args.extend(["--newline", "--progress", "--quiet", "--progress-template", PROGRESS_TEMPLATE]);
+    // Report the length, then where the file ended up (for the download archive)
+    let duration_print = format!("after_move:{} %(duration)s", DURATION_PREFIX);
+    args.extend(["--print", &duration_print, "--print", "after_move:filepath"]);
+
+    mark_pending(PendingDownload {
+        video: video.clone(),
+        include_video: options.video,
+        output_dir: options.output_dir.clone(),
+        started: chrono::Utc::now().timestamp(),
+    })
+    .await?;
 
-    let status = Command::new("yt-dlp")
+    debug!("Running yt-dlp {}", args.join(" "));
+    let mut child = ytdlp::command()
         .args(&args)
-        .status()
-        .await
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| YtChillError::Spawn(format!("Failed to start yt-dlp: {}", e)))?;
 
-    spinner.finish_and_clear();
+    // Kept to tell refusals (age, region, rate limits) apart if it fails
+    let stderr = child.stderr.take().map(|stderr| {
+        tokio::spawn(async move {
+            let mut text = String::new();
+            let _ = BufReader::new(stderr).read_to_string(&mut text).await;
+            text
+        })
+    });
+
+    let mut final_path = None;
+    let mut duration_secs = video.duration_secs;
+    if let Some(stdout) = child.stdout.take() {
+        let mut lines = BufReader::new(stdout).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(duration) = line.trim().strip_prefix(DURATION_PREFIX) {
+                duration_secs = duration.trim().parse::<f64>().ok().map(|d| d as u64).or(duration_secs);
+                continue;
+            }
+            let Some(progress) = parse_progress(&line) else {
+                if !line.trim().is_empty() {
+                    final_path = Some(line.trim().to_string());
+                }
+                continue;
+            };
+            // Video downloads fetch video and audio streams one after another
+            if progress.downloaded < bar.position() {
+                bar.reset();
+            }
+            if let Some(total) = progress.total {
+                bar.set_length(total);
+            }
+            bar.set_position(progress.downloaded);
+            bar.set_message(progress_message(&progress));
+        }
+    }
+
+    bar.set_message("Converting...");
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("yt-dlp failed: {}", e)))?;
+    let _ = tokio::fs::remove_file(&info_json).await;
+
+    bar.finish_and_clear();
 
+    let stderr = match stderr {
+        Some(task) => task.await.unwrap_or_default(),
+        None => String::new(),
+    };
     if !status.success() {
-        return Err(YtChillError::Spawn(format!(
-            "yt-dlp exited with code: {:?}",
-            status.code()
-        )));
+        debug!(%status, "yt-dlp failed: {}", stderr.trim());
+        if stderr.trim().is_empty() {
+            return Err(YtChillError::Spawn(format!(
+                "yt-dlp exited with code: {:?}",
+                status.code()
+            )));
+        }
+        return Err(YtChillError::from_ytdlp(&stderr));
     }
 
-    println!("✓ Download complete!");
-    Ok(())
+    let final_path = match final_path {
+        Some(staged) => {
+            let output_dir = expand_tilde(&options.output_dir);
+            let path = move_into_place(Path::new(&staged), Path::new(&staging_dir), Path::new(&output_dir)).await?;
+            Some(path.to_string_lossy().into_owned())
+        }
+        None => None,
+    };
+    clear_pending(&video.id, options.video).await?;
+
+    events::emit(Event::DownloadDone { video, path: final_path.as_deref() }).await;
+    if let Some(ref path) = final_path {
+        record_download(DownloadRecord {
+            id: video.id.clone(),
+            title: video.title.clone(),
+            video: options.video,
+            path: path.clone(),
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+        .await?;
+    }
+
+    let size = match final_path {
+        Some(ref path) => tokio::fs::metadata(path).await.ok().map(|m| m.len()),
+        None => None,
+    };
+    Ok(DownloadedFile {
+        video: video.clone(),
+        path: final_path,
+        size,
+        duration_secs,
+        embedded: options.embed_metadata.then(|| !embedding_failed(&stderr)),
+    })
 }
 
-/// Check if a command is available in PATH
-async fn is_command_available(cmd: &str) -> bool {
-    Command::new("which")
-        .arg(cmd)
+/// Move a finished file from the staging dir to the same place under
+/// `output_dir` (channel folders and all). A rename on the same disk; across
+/// disks it's copied under a hidden name first and renamed into place, so a
+/// half-copied file never shows up in the download dir.
+async fn move_into_place(staged: &Path, staging_dir: &Path, output_dir: &Path) -> Result<PathBuf> {
+    let relative = match staged.strip_prefix(staging_dir) {
+        Ok(relative) => relative,
+        // Not one of ours (an output template that escaped?): leave it be
+        Err(_) => return Ok(staged.to_path_buf()),
+    };
+    let target = output_dir.join(relative);
+    let target_dir = target.parent().unwrap_or(output_dir);
+    tokio::fs::create_dir_all(target_dir).await?;
+
+    if tokio::fs::rename(staged, &target).await.is_err() {
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let copying = target_dir.join(format!(".{}.yt-chill-tmp", name));
+        if let Err(e) = tokio::fs::copy(staged, &copying).await {
+            let _ = tokio::fs::remove_file(&copying).await;
+            return Err(e.into());
+        }
+        tokio::fs::rename(&copying, &target).await?;
+        tokio::fs::remove_file(staged).await?;
+    }
+    debug!("Moved {} to {}", staged.display(), target.display());
+
+    // Drop the channel folder in the staging dir if that was its last file
+    if let Some(parent) = staged.parent().filter(|p| *p != staging_dir) {
+        let _ = tokio::fs::remove_dir(parent).await;
+    }
+    Ok(target)
+}
+
+/// Whether yt-dlp warned that tags or cover art couldn't be embedded (it
+/// carries on without them)
+fn embedding_failed(stderr: &str) -> bool {
+    const STEPS: &[&str] = &["[EmbedThumbnail]", "[Metadata]", "[ThumbnailsConvertor]", "[FFmpegMetadata]"];
+    stderr
+        .lines()
+        .filter(|line| line.starts_with("WARNING:") || line.starts_with("ERROR:"))
+        .any(|line| STEPS.iter().any(|step| line.contains(step)) || line.to_lowercase().contains("thumbnail"))
+}
+
+/// Speed and ETA as reported by yt-dlp, e.g. "2.1 MiB/s, ETA 0:45"
+fn progress_message(progress: &Progress) -> String {
+    let speed = progress
+        .speed
+        .map(|s| format!("{}/s", HumanBytes(s as u64)))
+        .unwrap_or_default();
+    match progress.eta {
+        Some(eta) => format!("{}, ETA {}", speed, format_clock(eta)),
+        None => speed,
+    }
+}
+
+/// Fetch formats, chapters and the rest of a video's details with `yt-dlp -J`,
+/// cached for `video_ttl_secs` unless the video is live or about to be.
+/// Shared by the details view (`yt-chill info`, [`crate::YtChill::metadata`])
+/// and the download size check.
+pub async fn fetch_metadata(url: &str) -> Result<VideoMetadata> {
+    extract_metadata(url, &[], None).await
+}
+
+/// [`fetch_metadata`] with extra yt-dlp arguments (cookies, proxy). When
+/// yt-dlp has to be asked, its raw info is also written to `keep_info`.
+async fn extract_metadata(url: &str, extra: &[String], keep_info: Option<&Path>) -> Result<VideoMetadata> {
+    use crate::storage::cache::{CacheKind, get_cache_key, get_cached, set_cache};
+
+    let cache_key = get_cache_key(&format!("metadata:{}", url));
+    if let Some(cached) = get_cached::<VideoMetadata>(&cache_key).await {
+        debug!(key = %cache_key, "Cache hit");
+        return Ok(cached);
+    }
+
+    ytdlp::ensure_available().await?;
+
+    debug!("Running yt-dlp -J {}", url);
+    let output = ytdlp::command()
+        .args(["-J", "--no-playlist", "--no-warnings"])
+        .args(extra)
+        .arg(url)
+        .output()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start yt-dlp: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(YtChillError::from_ytdlp(&String::from_utf8_lossy(&output.stderr)));
+    }
+    let meta = parse_metadata(&output.stdout)?;
+    if let Some(path) = keep_info
+        && let Some(dir) = path.parent()
+    {
+        ensure_dir(&dir.to_string_lossy()).await?;
+        tokio::fs::write(path, &output.stdout).await?;
+    }
+    // A live stream's length and formats are still changing
+    if matches!(meta.live_status, LiveStatus::NotLive | LiveStatus::WasLive) {
+        let _ = set_cache(&cache_key, CacheKind::Video, &meta).await;
+    }
+    Ok(meta)
+}
+
+/// The videos in a playlist, without fetching each one's details
+pub async fn fetch_playlist(url: &str) -> Result<Vec<Video>> {
+    let entries = fetch_flat(url, &[]).await?;
+    Ok(entries.into_iter().map(|(video, _)| video).collect())
+}
+
+/// A channel's uploads, newest first: at most `limit`, and none uploaded
+/// before the `since` unix timestamp. Upload times in flat listings are
+/// YouTube's "3 weeks ago", so `since` is only accurate to about that.
+pub async fn fetch_channel_uploads(channel: &str, limit: Option<usize>, since: Option<i64>) -> Result<Vec<Video>> {
+    let end = limit.map(|n| n.to_string());
+    let mut extra = Vec::new();
+    if let Some(ref end) = end {
+        extra.extend(["--playlist-end", end.as_str()]);
+    }
+    if since.is_some() {
+        extra.extend(["--extractor-args", "youtubetab:approximate_date"]);
+    }
+
+    let entries = fetch_flat(&youtube::channel_uploads_url(channel), &extra).await?;
+    Ok(entries
+        .into_iter()
+        // Uploads are newest first, so everything after the first old one is older still
+        .take_while(|(_, timestamp)| since.zip(*timestamp).is_none_or(|(since, t)| t >= since))
+        .map(|(video, _)| video)
+        .collect())
+}
+
+/// Run `yt-dlp -J --flat-playlist` on a playlist or channel tab
+async fn fetch_flat(url: &str, extra: &[&str]) -> Result<Vec<(Video, Option<i64>)>> {
+    ytdlp::ensure_available().await?;
+
+    debug!("Running yt-dlp -J --flat-playlist {} {}", extra.join(" "), url);
+    let output = ytdlp::command()
+        .args(["-J", "--flat-playlist", "--no-warnings"])
+        .args(extra)
+        .arg(url)
         .output()
         .await
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start yt-dlp: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(YtChillError::from_ytdlp(&String::from_utf8_lossy(&output.stderr)));
+    }
+    parse_playlist(&output.stdout)
+}
+
+/// `yt-dlp -J --flat-playlist` output
+#[derive(Debug, Deserialize)]
+struct RawPlaylist {
+    #[serde(default)]
+    entries: Vec<RawPlaylistEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawPlaylistEntry {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    uploader: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    view_count: Option<u64>,
+    /// Unix time, approximate for channel tabs
+    #[serde(default)]
+    timestamp: Option<i64>,
+}
+
+/// Turn `yt-dlp -J --flat-playlist` output into videos and their upload times
+fn parse_playlist(json: &[u8]) -> Result<Vec<(Video, Option<i64>)>> {
+    let raw: RawPlaylist = serde_json::from_slice(json)
+        .map_err(|e| YtChillError::YouTubeParse(format!("Unexpected yt-dlp output: {}", e)))?;
+
+    Ok(raw
+        .entries
+        .into_iter()
+        .map(|e| {
+            let duration_secs = e.duration.map(|d| d.round() as u64);
+            let video = Video {
+                thumbnail: format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", e.id),
+                id: e.id,
+                title: e.title,
+                author: e.channel.or(e.uploader).unwrap_or_default(),
+                duration: duration_secs.map(format_clock).unwrap_or_default(),
+                views: e.view_count.map(|n| format!("{} views", n)).unwrap_or_default(),
+                duration_secs,
+                view_count: e.view_count,
+                ..Default::default()
+            };
+            (video, e.timestamp)
+        })
+        .collect())
+}
+
+/// The parts of yt-dlp's JSON dump we use
+#[derive(Debug, Deserialize)]
+struct RawMetadata {
+    id: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    uploader: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    channel_id: Option<String>,
+    /// YYYYMMDD
+    #[serde(default)]
+    upload_date: Option<String>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    view_count: Option<u64>,
+    #[serde(default)]
+    live_status: Option<LiveStatus>,
+    #[serde(default)]
+    chapters: Option<Vec<RawChapter>>,
+    #[serde(default)]
+    formats: Option<Vec<RawFormat>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawChapter {
+    start_time: f64,
+    end_time: f64,
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFormat {
+    format_id: String,
+    #[serde(default)]
+    ext: String,
+    #[serde(default)]
+    height: Option<u32>,
+    #[serde(default)]
+    fps: Option<f64>,
+    #[serde(default)]
+    tbr: Option<f64>,
+    #[serde(default)]
+    filesize: Option<u64>,
+    #[serde(default)]
+    filesize_approx: Option<u64>,
+    #[serde(default)]
+    vcodec: Option<String>,
+    #[serde(default)]
+    acodec: Option<String>,
+}
+
+/// Turn `yt-dlp -J` output into [`VideoMetadata`]
+fn parse_metadata(json: &[u8]) -> Result<VideoMetadata> {
+    let raw: RawMetadata = serde_json::from_slice(json)
+        .map_err(|e| YtChillError::YouTubeParse(format!("Unexpected yt-dlp output: {}", e)))?;
+
+    // "none" means the stream has no audio (or video); missing means unknown
+    let has = |codec: &Option<String>| codec.as_deref().is_some_and(|c| c != "none");
+    let formats = raw
+        .formats
+        .unwrap_or_default()
+        .into_iter()
+        // Storyboards and other image-only formats
+        .filter(|f| has(&f.vcodec) || has(&f.acodec))
+        .map(|f| VideoFormat {
+            resolution: match f.height {
+                Some(height) if has(&f.vcodec) => format!("{}p", height),
+                _ => "audio only".into(),
+            },
+            height: f.height.filter(|_| has(&f.vcodec)),
+            fps: f.fps.map(|fps| fps.round() as u32),
+            bitrate: f.tbr,
+            filesize: f.filesize.or(f.filesize_approx),
+            has_audio: has(&f.acodec),
+            has_video: has(&f.vcodec),
+            code: f.format_id,
+            ext: f.ext,
+        })
+        .collect();
+
+    Ok(VideoMetadata {
+        id: raw.id,
+        title: raw.title,
+        uploader: raw.uploader.or(raw.channel).unwrap_or_default(),
+        channel_id: raw.channel_id,
+        upload_date: raw
+            .upload_date
+            .and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y%m%d").ok()),
+        duration: raw.duration.map(|d| d.round() as u64),
+        view_count: raw.view_count,
+        live_status: raw.live_status.unwrap_or_default(),
+        chapters: raw
+            .chapters
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| Chapter { title: c.title, start: c.start_time, end: c.end_time })
+            .collect(),
+        formats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_move_into_place() {
+        let root = std::env::temp_dir().join(format!("yt-chill-move-{}", std::process::id()));
+        let staging = root.join("partial");
+        let output = root.join("Downloads");
+        std::fs::create_dir_all(staging.join("Lofi Girl")).unwrap();
+        let staged = staging.join("Lofi Girl/Rain [abc].mp3");
+        std::fs::write(&staged, "song").unwrap();
+
+        let moved = move_into_place(&staged, &staging, &output).await.unwrap();
+        assert_eq!(moved, output.join("Lofi Girl/Rain [abc].mp3"));
+        assert_eq!(std::fs::read_to_string(&moved).unwrap(), "song");
+        assert!(!staged.exists());
+        assert!(!staging.join("Lofi Girl").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_planned_size() {
+        let format = |code: &str, height: Option<u32>, audio: bool, filesize: Option<u64>| VideoFormat {
+            code: code.into(),
+            height,
+            has_audio: audio,
+            has_video: height.is_some(),
+            filesize,
+            ..Default::default()
+        };
+        // Worst first, as yt-dlp lists them
+        let formats = [
+            format("140", None, true, Some(3_000_000)),
+            format("251", None, true, Some(4_000_000)),
+            format("18", Some(360), true, Some(20_000_000)),
+            format("136", Some(720), false, Some(40_000_000)),
+            format("137", Some(1080), false, None),
+        ];
+
+        let audio = DownloadOptions::default();
+        assert_eq!(planned_size(&formats, &audio), Some(4_000_000));
+        let capped = DownloadOptions { video: true, max_height: Some(720), ..Default::default() };
+        assert_eq!(planned_size(&formats, &capped), Some(44_000_000));
+        // The best video's size is unknown
+        let video = DownloadOptions { video: true, ..Default::default() };
+        assert_eq!(planned_size(&formats, &video), None);
+        let codes = DownloadOptions { format: Some("18".into()), ..Default::default() };
+        assert_eq!(planned_size(&formats, &codes), Some(20_000_000));
+        let selector = DownloadOptions { format: Some("bestaudio[ext=m4a]".into()), ..Default::default() };
+        assert_eq!(planned_size(&formats, &selector), None);
+    }
+
+    #[test]
+    fn test_check_space() {
+        let dir = std::env::temp_dir();
+        let dir = dir.to_string_lossy();
+        assert!(check_space(0, &[&dir]).is_ok());
+        // Folders that don't exist yet are checked on the nearest parent
+        let missing = format!("{}/yt-chill-missing/a/b", dir);
+        assert!(matches!(check_space(u64::MAX / 2, &[&missing]), Err(YtChillError::DiskFull(_))));
+    }
+
+    #[test]
+    fn test_embedding_failed() {
+        assert!(!embedding_failed(""));
+        assert!(!embedding_failed("WARNING: [youtube] Falling back to generic n function search\n"));
+        assert!(embedding_failed("WARNING: [EmbedThumbnail] Unable to embed thumbnail in opus\n"));
+        assert!(embedding_failed("WARNING: Skipping embedding the thumbnail because the file is missing.\n"));
+    }
+
+    #[test]
+    fn test_notification() {
+        let video = Video {
+            id: "abc123".into(),
+            title: "Lofi".into(),
+            ..Default::default()
+        };
+        let mut summary = DownloadSummary::default();
+        assert_eq!(notification(&summary).0, "Downloads finished");
+        summary.failed.push((video, YtChillError::Spawn("boom".into())));
+        assert_eq!(
+            notification(&summary),
+            ("Downloads finished with errors".to_string(), "0 downloaded, 1 failed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        let progress = parse_progress("ytc-progress 1048576 4194304 524288.5 6").unwrap();
+        assert_eq!(progress.downloaded, 1_048_576);
+        assert_eq!(progress.total, Some(4_194_304));
+        assert_eq!(progress.eta, Some(6));
+
+        let unknown = parse_progress("ytc-progress 1024 NA NA NA").unwrap();
+        assert_eq!(unknown.total, None);
+        assert_eq!(unknown.speed, None);
+
+        assert!(parse_progress("[download] Destination: foo.webm").is_none());
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let json = br#"{
+            "id": "abc", "title": "Lofi", "uploader": "Lofi Girl", "channel_id": "UCSJ4gkVC6NrvII8umztf0Ow",
+            "upload_date": "20240302", "duration": 224.6, "view_count": 1234, "live_status": "was_live",
+            "chapters": [{"start_time": 0.0, "end_time": 60.0, "title": "Intro"}],
+            "formats": [
+                {"format_id": "sb0", "ext": "mhtml", "vcodec": "none", "acodec": "none"},
+                {"format_id": "251", "ext": "webm", "vcodec": "none", "acodec": "opus", "tbr": 130.5, "filesize": 3500000},
+                {"format_id": "137", "ext": "mp4", "height": 1080, "fps": 29.97, "vcodec": "avc1", "acodec": "none", "filesize_approx": 90000000}
+            ]
+        }"#;
+        let meta = parse_metadata(json).unwrap();
+        assert_eq!(meta.upload_date, chrono::NaiveDate::from_ymd_opt(2024, 3, 2));
+        assert_eq!(meta.duration, Some(225));
+        assert_eq!(meta.live_status, LiveStatus::WasLive);
+        assert_eq!(meta.chapters[0].title, "Intro");
+
+        let codes: Vec<_> = meta.formats.iter().map(|f| f.code.as_str()).collect();
+        assert_eq!(codes, ["251", "137"]);
+        assert_eq!(meta.formats[0].resolution, "audio only");
+        assert!(meta.formats[0].has_audio && !meta.formats[0].has_video);
+        assert_eq!(meta.formats[1].resolution, "1080p");
+        assert_eq!(meta.formats[1].fps, Some(30));
+        assert_eq!(meta.formats[1].filesize, Some(90_000_000));
+
+        // Only the ID is required
+        assert_eq!(parse_metadata(br#"{"id": "x"}"#).unwrap().live_status, LiveStatus::NotLive);
+        assert!(parse_metadata(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_playlist() {
+        let json = br#"{
+            "id": "PL123",
+            "title": "Chill mix",
+            "entries": [
+                {"id": "abc", "title": "Rain", "channel": "Lofi Girl", "duration": 225.0, "view_count": 1200, "timestamp": 1700000000},
+                {"id": "def", "title": "Snow", "uploader": "Someone"}
+            ]
+        }"#;
+        let entries = parse_playlist(json).unwrap();
+        assert_eq!(entries[0].1, Some(1700000000));
+        assert_eq!(entries[1].1, None);
+        let videos: Vec<Video> = entries.into_iter().map(|(video, _)| video).collect();
+        assert_eq!(videos.len(), 2);
+        assert_eq!(videos[0].id, "abc");
+        assert_eq!(videos[0].author, "Lofi Girl");
+        assert_eq!(videos[0].duration, "3:45");
+        assert_eq!(videos[0].duration_secs, Some(225));
+        assert_eq!(videos[1].author, "Someone");
+        assert_eq!(videos[1].duration, "");
+        assert!(parse_playlist(b"not json").is_err());
+    }
+
+    #[test]
+    fn test_is_valid_rate() {
+        assert!(is_valid_rate("500K"));
+        assert!(is_valid_rate("1.5M"));
+        assert!(is_valid_rate("2000000"));
+        assert!(!is_valid_rate("fast"));
+        assert!(!is_valid_rate("K"));
+        assert!(!is_valid_rate("0"));
+
+        assert!(is_valid_audio_quality("0"));
+        assert!(is_valid_audio_quality("192K"));
+        assert!(!is_valid_audio_quality("11"));
+    }
+
+    #[test]
+    fn test_check_output_template() {
+        assert!(check_output_template(DEFAULT_OUTPUT_TEMPLATE).is_ok());
+        assert!(check_output_template("%(uploader)s/%(title)s.%(ext)s").is_ok());
+        assert!(check_output_template("/tmp/%(title)s.%(ext)s").is_err());
+        assert!(check_output_template("../%(title)s.%(ext)s").is_err());
+        assert!(check_output_template("%(title)s.mp3").is_err());
+
+        assert_eq!(
+            organized_template("%(title)s.%(ext)s", OrganizeBy::Channel),
+            "%(uploader)s/%(title)s.%(ext)s"
+        );
+    }
 }
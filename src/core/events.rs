@@ -0,0 +1,107 @@
+//! JSON events (track started, paused, download done, ...) for scripts,
+//! overlays and home automation, sent where the `[events]` config says
+
+use crate::types::{EventsConfig, Video};
+use serde::Serialize;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+static SETTINGS: OnceLock<EventsConfig> = OnceLock::new();
+
+/// How long to wait for whatever listens on the events socket
+#[cfg(unix)]
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Something scripts may want to react to
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event<'a> {
+    TrackStarted { video: &'a Video },
+    /// `quit` when playback was stopped rather than reaching the end
+    TrackFinished { video: &'a Video, quit: bool },
+    Paused { title: &'a str },
+    Resumed { title: &'a str },
+    DownloadDone { video: &'a Video, path: Option<&'a str> },
+}
+
+/// Set where events go for this run (first call wins)
+pub fn configure(config: EventsConfig) {
+    SETTINGS.get_or_init(|| config);
+}
+
+/// The pause state changing to `paused`
+pub fn pause_event(paused: bool, title: &str) -> Event<'_> {
+    if paused {
+        Event::Paused { title }
+    } else {
+        Event::Resumed { title }
+    }
+}
+
+/// One line of JSON, stamped with the Unix time
+pub fn to_json(event: &Event) -> String {
+    let mut value = serde_json::to_value(event).unwrap_or_default();
+    value["time"] = chrono::Utc::now().timestamp().into();
+    value.to_string()
+}
+
+/// Send an event to the configured command and socket. Failures are
+/// ignored: nobody listening mustn't get in the way of playback.
+pub async fn emit(event: Event<'_>) {
+    let Some(settings) = SETTINGS.get() else {
+        return;
+    };
+    if settings.command.is_empty() && settings.socket.is_empty() {
+        return;
+    }
+    let line = to_json(&event);
+
+    if !settings.command.is_empty() {
+        let _ = run_command(&settings.command, &line).await;
+    }
+    #[cfg(unix)]
+    if !settings.socket.is_empty() {
+        let _ = tokio::time::timeout(SOCKET_TIMEOUT, send_to_socket(&settings.socket, &line)).await;
+    }
+}
+
+/// Start the command with the event on stdin and in $YT_CHILL_EVENT,
+/// without waiting for it to finish
+async fn run_command(command: &str, line: &str) -> std::io::Result<()> {
+    tracing::trace!("Running events command: {}", command);
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .env("YT_CHILL_EVENT", line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(format!("{}\n", line).as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Write the event as a line to a Unix socket someone is listening on
+#[cfg(unix)]
+async fn send_to_socket(path: &str, line: &str) -> std::io::Result<()> {
+    let mut stream = tokio::net::UnixStream::connect(crate::utils::paths::expand_tilde(path)).await?;
+    stream.write_all(format!("{}\n", line).as_bytes()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_to_json() {
+        let event: Value = serde_json::from_str(&to_json(&pause_event(true, "Lofi"))).unwrap();
+        assert_eq!(event["event"], "paused");
+        assert_eq!(event["title"], "Lofi");
+        assert!(event["time"].is_i64());
+    }
+}
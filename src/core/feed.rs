@@ -0,0 +1,151 @@
+//! The subscription feed: recent uploads from each channel, reusing each
+//! channel's last fetch until it's older than `feed_ttl_secs`
+
+use crate::core::youtube;
+use crate::error::Result;
+use crate::storage::cache::{self, CacheKind};
+use crate::storage::feed_cache::{load_feed_cache, save_feed_cache};
+use crate::storage::subscriptions::{known_channel_id, save_channel_ids};
+use crate::types::{ChannelFeed, Subscription, Video};
+use chrono::Utc;
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// Videos pulled into the feed per channel unless the subscription says otherwise
+pub const DEFAULT_CHANNEL_LIMIT: usize = 5;
+
+/// Most videos per channel in data saver mode, whatever the subscription says
+pub const DATA_SAVER_CHANNEL_LIMIT: usize = 2;
+
+static DATA_SAVER: OnceLock<bool> = OnceLock::new();
+
+/// Fetch fewer videos per channel (first call wins)
+pub fn set_data_saver(on: bool) {
+    let _ = DATA_SAVER.set(on);
+}
+
+/// Videos to show for a subscription
+fn channel_limit(sub: &Subscription) -> usize {
+    let limit = sub.limit.unwrap_or(DEFAULT_CHANNEL_LIMIT);
+    if DATA_SAVER.get().copied().unwrap_or(false) {
+        limit.min(DATA_SAVER_CHANNEL_LIMIT)
+    } else {
+        limit
+    }
+}
+
+/// Uploads for each subscription, in the order given
+#[derive(Debug, Default)]
+pub struct Feed {
+    pub channels: Vec<(Subscription, Vec<Video>)>,
+    /// Channels fetched from YouTube this time
+    pub fetched: usize,
+    /// Channels that couldn't be fetched (any older results are still shown)
+    pub failed: usize,
+}
+
+impl Feed {
+    /// Every channel's videos, one list
+    pub fn videos(&self) -> Vec<Video> {
+        self.channels.iter().flat_map(|(_, videos)| videos.iter().cloned()).collect()
+    }
+}
+
+/// A subscription's UC... channel ID: stored, in its handle, or looked up on
+/// its channel page (added to `looked_up` so it can be saved)
+pub async fn channel_id(sub: &Subscription, looked_up: &mut Vec<(String, String)>) -> Result<String> {
+    if let Some(id) = known_channel_id(sub) {
+        return Ok(id);
+    }
+    let id = youtube::resolve_channel_id(&sub.handle).await?;
+    looked_up.push((sub.handle.clone(), id.clone()));
+    Ok(id)
+}
+
+/// Load the feed for `subs`. Channels fetched within the feed TTL come from
+/// the cache unless `refresh` (or `--no-cache`) asks for everything again.
+/// Once YouTube can't be reached, the remaining channels use whatever was
+/// cached instead of each waiting out their retries.
+pub async fn load_feed(subs: &[Subscription], refresh: bool) -> Result<Feed> {
+    let mut store = load_feed_cache().await;
+    let ttl = cache::ttl_secs(CacheKind::Feed);
+    let refresh = refresh || cache::bypassed();
+    let mut feed = Feed::default();
+    let mut online = true;
+    let mut looked_up = Vec::new();
+
+    for sub in subs {
+        let limit = channel_limit(sub);
+        let now = Utc::now().timestamp();
+        let cached = store.get(&sub.handle);
+
+        if !refresh && cached.is_some_and(|c| c.is_fresh(limit, ttl, now)) {
+            debug!(channel = %sub.handle, "Feed cache hit");
+        } else if online {
+            let videos = match channel_id(sub, &mut looked_up).await {
+                Ok(id) => youtube::fetch_channel_videos(&id, limit).await,
+                Err(e) => Err(e),
+            };
+            match videos {
+                Ok(videos) => {
+                    feed.fetched += 1;
+                    store.insert(sub.handle.clone(), ChannelFeed { last_fetched: now, limit, videos });
+                }
+                Err(e) => {
+                    debug!(channel = %sub.handle, error = %e, "Couldn't fetch channel");
+                    feed.failed += 1;
+                    online = !e.is_retryable();
+                }
+            }
+        } else {
+            feed.failed += 1;
+        }
+
+        let videos = store
+            .get(&sub.handle)
+            .map(|c| c.videos.iter().take(limit).cloned().collect())
+            .unwrap_or_default();
+        feed.channels.push((sub.clone(), videos));
+    }
+
+    if feed.fetched > 0 && ttl > 0 {
+        save_feed_cache(&store).await?;
+    }
+    save_channel_ids(&looked_up).await?;
+    Ok(feed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_channel_id_uses_imported_ids() {
+        let mut looked_up = Vec::new();
+        // As the OPML, NewPipe/FreeTube and Takeout importers store them
+        let imported = Subscription {
+            handle: "/channel/UCSJ4gkVC6NrvII8umztf0Ow".into(),
+            ..Default::default()
+        };
+        assert_eq!(channel_id(&imported, &mut looked_up).await.unwrap(), "UCSJ4gkVC6NrvII8umztf0Ow");
+        let named = Subscription {
+            handle: "@LofiGirl".into(),
+            channel_id: Some("UCSJ4gkVC6NrvII8umztf0Ow".into()),
+            ..Default::default()
+        };
+        assert_eq!(channel_id(&named, &mut looked_up).await.unwrap(), "UCSJ4gkVC6NrvII8umztf0Ow");
+        assert!(looked_up.is_empty());
+    }
+
+    #[test]
+    fn test_channel_feed_freshness() {
+        let feed = ChannelFeed { last_fetched: 1_000, limit: 5, videos: Vec::new() };
+        assert!(feed.is_fresh(5, 900, 1_899));
+        assert!(feed.is_fresh(3, 900, 1_000));
+        assert!(!feed.is_fresh(5, 900, 1_900));
+        // A higher limit needs more videos than were fetched
+        assert!(!feed.is_fresh(10, 900, 1_000));
+        // A TTL of 0 turns the cache off
+        assert!(!feed.is_fresh(5, 0, 1_000));
+    }
+}
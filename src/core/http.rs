@@ -0,0 +1,153 @@
+//! HTTP layer for YouTube requests, behind a trait so other backends (or
+//! recorded responses in tests) can stand in for reqwest
+
+use crate::error::{Result, YtChillError};
+use crate::types::NetworkConfig;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// What [`HttpBackend::send`] returns
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A GET, or a POST when there's a JSON body
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub json: Option<Value>,
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: Vec::new(),
+            json: None,
+        }
+    }
+
+    pub fn post_json(url: &str, body: Value) -> Self {
+        Self {
+            json: Some(body),
+            ..Self::get(url)
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// "GET" or "POST", for logging
+    pub fn method(&self) -> &'static str {
+        if self.json.is_some() { "POST" } else { "GET" }
+    }
+}
+
+/// A response, read to the end
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: StatusCode,
+    /// Where we ended up after redirects
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// First header called `name` (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// Sends requests for [`crate::core::youtube`]
+pub trait HttpBackend: Send + Sync {
+    /// Send a request and read the whole response. Failures worth retrying
+    /// (timeouts, refused connections) should come back as
+    /// [`YtChillError::Network`]; other statuses are handled by the caller.
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>>;
+}
+
+/// Idle connections kept open per host between requests
+const POOL_IDLE_PER_HOST: usize = 8;
+
+/// How long an idle connection is kept for the next request
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// The default backend. One is built per process and shared, so feed
+/// refreshes and thumbnail fetches reuse pooled keep-alive connections
+/// (and TLS sessions) instead of reconnecting for every request.
+#[derive(Debug, Clone)]
+pub struct ReqwestBackend {
+    client: reqwest::Client,
+}
+
+impl ReqwestBackend {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    /// A client with the configured timeouts and proxy. Without a proxy,
+    /// reqwest still honors HTTP(S)_PROXY from the environment.
+    pub fn from_settings(network: &NetworkConfig, proxy: Option<&str>) -> Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(network.connect_timeout_secs))
+            .timeout(Duration::from_secs(network.timeout_secs))
+            .pool_max_idle_per_host(POOL_IDLE_PER_HOST)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(POOL_IDLE_TIMEOUT)
+            // YouTube speaks HTTP/2 over TLS; requests share one connection
+            .http2_adaptive_window(true);
+        if let Some(proxy) = proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| YtChillError::Network(format!("Invalid proxy {}: {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        Ok(Self::new(builder.build()?))
+    }
+}
+
+impl HttpBackend for ReqwestBackend {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>> {
+        Box::pin(async move {
+            let mut builder = match request.json {
+                Some(ref body) => self.client.post(&request.url).json(body),
+                None => self.client.get(&request.url),
+            };
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+
+            let response = builder.send().await.map_err(transient)?;
+            let status = response.status();
+            let url = response.url().to_string();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(n, v)| Some((n.to_string(), v.to_str().ok()?.to_string())))
+                .collect();
+            let body = response.bytes().await.map_err(transient)?.to_vec();
+            Ok(HttpResponse { status, url, headers, body })
+        })
+    }
+}
+
+/// Report timeouts and failed connections as retryable network errors
+fn transient(e: reqwest::Error) -> YtChillError {
+    if e.is_timeout() || e.is_connect() {
+        YtChillError::Network(e.to_string())
+    } else {
+        e.into()
+    }
+}
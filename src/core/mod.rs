@@ -1,5 +1,18 @@
-//! Core functionality: YouTube, player, downloader
+//! Core functionality: YouTube, player, downloader, events
 
+pub mod blocklist;
 pub mod downloader;
+pub mod events;
+pub mod feed;
+pub mod http;
+pub mod mpd;
+#[cfg(unix)]
+pub mod mpv_ipc;
+#[cfg(unix)]
+pub mod now_playing_file;
 pub mod player;
+pub mod rate_limit;
+pub mod thumbnail;
+pub mod timebox;
 pub mod youtube;
+pub mod ytdlp;
@@ -0,0 +1,248 @@
+//! Minimal MPD client: hand streams to a running MPD server's queue
+
+use crate::error::{Result, YtChillError};
+use crate::types::PlaybackOutcome;
+use crate::utils::paths::expand_tilde;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Connection to an MPD server
+pub struct MpdClient {
+    reader: BufReader<Box<dyn AsyncRead + Unpin + Send>>,
+    writer: Box<dyn AsyncWrite + Unpin + Send>,
+}
+
+/// Whether `host` is a unix socket path rather than a host name
+pub fn is_socket_path(host: &str) -> bool {
+    host.starts_with('/') || host.starts_with('~')
+}
+
+/// Split `[password@]host[:port]` or `[password@]/path/to/socket` into its parts
+pub fn parse_address(address: &str) -> (Option<String>, String) {
+    let (password, host) = match address.rsplit_once('@') {
+        Some((password, host)) => (Some(password.to_string()), host),
+        None => (None, address),
+    };
+    let host = if is_socket_path(host) {
+        expand_tilde(host)
+    } else if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:6600", host)
+    };
+    (password, host)
+}
+
+/// Quote a command argument per the MPD protocol
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl MpdClient {
+    /// Connect to `[password@]host[:port]` or `[password@]/path/to/socket`
+    pub async fn connect(address: &str) -> Result<Self> {
+        let (password, host) = parse_address(address);
+        let unreachable = |e: std::io::Error| YtChillError::Network(format!("Can't reach MPD at {}: {}", host, e));
+        let mut client = if is_socket_path(&host) {
+            #[cfg(unix)]
+            {
+                let (read, writer) = tokio::net::UnixStream::connect(&host).await.map_err(unreachable)?.into_split();
+                Self::new(read, writer)
+            }
+            #[cfg(not(unix))]
+            return Err(unreachable(std::io::ErrorKind::Unsupported.into()));
+        } else {
+            let (read, writer) = TcpStream::connect(&host).await.map_err(unreachable)?.into_split();
+            Self::new(read, writer)
+        };
+
+        let mut greeting = String::new();
+        client.reader.read_line(&mut greeting).await?;
+        if !greeting.starts_with("OK MPD") {
+            return Err(YtChillError::Network(format!("{} is not an MPD server", host)));
+        }
+
+        if let Some(password) = password {
+            client.command(&format!("password {}", quote(&password))).await?;
+        }
+        Ok(client)
+    }
+
+    fn new(read: impl AsyncRead + Unpin + Send + 'static, writer: impl AsyncWrite + Unpin + Send + 'static) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(read)),
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Send a command and collect its `key: value` response lines
+    async fn command(&mut self, command: &str) -> Result<Vec<(String, String)>> {
+        self.writer.write_all(format!("{}\n", command).as_bytes()).await?;
+
+        let mut pairs = Vec::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).await? == 0 {
+                return Err(YtChillError::Network("MPD closed the connection".into()));
+            }
+            let line = line.trim_end();
+            if line == "OK" {
+                return Ok(pairs);
+            }
+            if let Some(error) = line.strip_prefix("ACK ") {
+                return Err(YtChillError::Network(format!("MPD: {}", error)));
+            }
+            if let Some((key, value)) = line.split_once(": ") {
+                pairs.push((key.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    /// Add a URL to the queue, starting playback if MPD is idle. Returns the song ID.
+    pub async fn enqueue(&mut self, url: &str) -> Result<String> {
+        let added = self.command(&format!("addid {}", quote(url))).await?;
+        let id = added
+            .into_iter()
+            .find(|(k, _)| k == "Id")
+            .map(|(_, v)| v)
+            .ok_or_else(|| YtChillError::Network("MPD didn't return a song ID".into()))?;
+
+        let status = self.command("status").await?;
+        let stopped = status.iter().any(|(k, v)| k == "state" && v != "play");
+        if stopped {
+            self.command(&format!("playid {}", id)).await?;
+        }
+        Ok(id)
+    }
+
+    /// Wait for song `id` to play through: Finished once MPD moves past it,
+    /// Quit if it's stopped while playing or removed before it starts
+    pub async fn wait_until_played(&mut self, id: &str) -> Result<PlaybackOutcome> {
+        let mut started = false;
+        loop {
+            let status = self.command("status").await?;
+            let get = |key: &str| status.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+            let stopped = get("state").is_none_or(|state| state == "stop");
+
+            if get("songid") == Some(id) {
+                if stopped && started {
+                    return Ok(PlaybackOutcome::Quit);
+                }
+                started |= !stopped;
+            } else if started {
+                return Ok(PlaybackOutcome::Finished);
+            } else if self.command(&format!("playlistid {}", id)).await.is_err() {
+                // Taken off the queue before it played
+                return Ok(PlaybackOutcome::Quit);
+            }
+
+            // Blocks until something changes
+            self.command("idle player playlist").await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("localhost"), (None, "localhost:6600".to_string()));
+        assert_eq!(
+            parse_address("secret@10.0.0.2:6601"),
+            (Some("secret".to_string()), "10.0.0.2:6601".to_string())
+        );
+        assert_eq!(parse_address("/run/mpd/socket"), (None, "/run/mpd/socket".to_string()));
+        assert_eq!(
+            parse_address("secret@/run/mpd/socket"),
+            (Some("secret".to_string()), "/run/mpd/socket".to_string())
+        );
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_starts_idle_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"OK MPD 0.23.5\n").await.unwrap();
+
+            let mut commands = Vec::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let reply: &[u8] = if line.starts_with("addid") {
+                    b"Id: 7\nOK\n"
+                } else if line == "status" {
+                    b"volume: 50\nstate: stop\nOK\n"
+                } else {
+                    b"OK\n"
+                };
+                commands.push(line);
+                write.write_all(reply).await.unwrap();
+            }
+            commands
+        });
+
+        let mut client = MpdClient::connect(&address).await.unwrap();
+        assert_eq!(client.enqueue("https://stream/a").await.unwrap(), "7");
+        drop(client);
+
+        let commands = server.await.unwrap();
+        assert_eq!(commands, vec!["addid \"https://stream/a\"", "status", "playid 7"]);
+    }
+
+    /// A server that answers each command with the next scripted reply
+    async fn scripted_server(replies: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"OK MPD 0.23.5\n").await.unwrap();
+            for reply in replies {
+                if lines.next_line().await.ok().flatten().is_none() {
+                    break;
+                }
+                write.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_played() {
+        // Queued behind song 6, played, then MPD moves on to song 8
+        let address = scripted_server(vec![
+            "state: play\nsongid: 6\nOK\n",
+            "Id: 7\nOK\n",
+            "changed: player\nOK\n",
+            "state: play\nsongid: 7\nOK\n",
+            "changed: player\nOK\n",
+            "state: play\nsongid: 8\nOK\n",
+        ])
+        .await;
+        let mut client = MpdClient::connect(&address).await.unwrap();
+        assert_eq!(client.wait_until_played("7").await.unwrap(), PlaybackOutcome::Finished);
+
+        // Stopped while playing
+        let address = scripted_server(vec![
+            "state: play\nsongid: 7\nOK\n",
+            "changed: player\nOK\n",
+            "state: stop\nsongid: 7\nOK\n",
+        ])
+        .await;
+        let mut client = MpdClient::connect(&address).await.unwrap();
+        assert_eq!(client.wait_until_played("7").await.unwrap(), PlaybackOutcome::Quit);
+
+        // Removed from the queue before it started
+        let address = scripted_server(vec!["state: play\nsongid: 6\nOK\n", "ACK [50@0] {playlistid} No such song\n"]).await;
+        let mut client = MpdClient::connect(&address).await.unwrap();
+        assert_eq!(client.wait_until_played("7").await.unwrap(), PlaybackOutcome::Quit);
+    }
+}
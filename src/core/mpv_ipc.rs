@@ -0,0 +1,95 @@
+//! Minimal client for mpv's JSON IPC (`--input-ipc-server`)
+
+use crate::error::{Result, YtChillError};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+/// How long to wait for mpv to open its socket
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connection to a running mpv
+pub struct MpvIpc {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+/// A fresh socket path for an mpv started by this process
+pub fn socket_path() -> PathBuf {
+    std::env::temp_dir().join(format!("yt-chill-mpv-{}.sock", std::process::id()))
+}
+
+impl MpvIpc {
+    /// Connect to mpv's socket, waiting for mpv to create it
+    pub async fn connect(path: &Path) -> Result<Self> {
+        let deadline = tokio::time::Instant::now() + CONNECT_TIMEOUT;
+        let stream = loop {
+            match UnixStream::connect(path).await {
+                Ok(stream) => break stream,
+                Err(e) if tokio::time::Instant::now() >= deadline => {
+                    return Err(YtChillError::Spawn(format!("Can't talk to mpv: {}", e)));
+                }
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        };
+        let (read, writer) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(read).lines(),
+            writer,
+        })
+    }
+
+    /// Send a command, e.g. `["loadfile", url, "append"]`. Replies arrive as events.
+    pub async fn command(&mut self, args: &[&str]) -> Result<()> {
+        self.send(json!({ "command": args })).await
+    }
+
+    /// Press a key as if typed into mpv, so its own bindings apply
+    pub async fn keypress(&mut self, key: &str) -> Result<()> {
+        self.command(&["keypress", key]).await
+    }
+
+    /// Get `property-change` events for a property, tagged with `id`
+    pub async fn observe(&mut self, id: u64, property: &str) -> Result<()> {
+        self.send(json!({ "command": ["observe_property", id, property] })).await
+    }
+
+    async fn send(&mut self, message: Value) -> Result<()> {
+        self.writer.write_all(format!("{}\n", message).as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Next event or reply from mpv (None once mpv has gone away)
+    pub async fn next_event(&mut self) -> Option<Value> {
+        loop {
+            let line = self.lines.next_line().await.ok()??;
+            if let Ok(event) = serde_json::from_str(&line) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// The new value if `event` is a change of the observed property `name`
+pub fn property_change<'a>(event: &'a Value, name: &str) -> Option<&'a Value> {
+    if event.get("event")?.as_str()? != "property-change" || event.get("name")?.as_str()? != name {
+        return None;
+    }
+    event.get("data")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_change() {
+        let event = json!({"event": "property-change", "id": 1, "name": "playlist-pos", "data": 2});
+        assert_eq!(property_change(&event, "playlist-pos"), Some(&json!(2)));
+        assert_eq!(property_change(&event, "pause"), None);
+        assert_eq!(property_change(&json!({"event": "idle"}), "playlist-pos"), None);
+    }
+}
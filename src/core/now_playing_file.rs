@@ -0,0 +1,167 @@
+//! Now-playing text file for OBS (and other overlay) text sources: the
+//! title, artist and elapsed time of what mpv is playing, followed over
+//! mpv's JSON IPC and rewritten every few seconds
+
+use crate::core::mpv_ipc::MpvIpc;
+use crate::utils::paths::expand_tilde;
+use crate::utils::time::format_clock;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::debug;
+
+/// How often the file is rewritten (only when the text changed)
+const UPDATE_INTERVAL: Duration = Duration::from_secs(2);
+
+static PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Keep the file at this path up to date while mpv plays (first call wins)
+pub fn set_path(path: Option<String>) {
+    let _ = PATH.set(path.map(|p| PathBuf::from(expand_tilde(&p))));
+}
+
+/// The configured file, if any
+pub fn path() -> Option<&'static Path> {
+    PATH.get().and_then(|p| p.as_deref())
+}
+
+/// What goes in the file
+#[derive(Debug, Default)]
+struct Track {
+    title: Option<String>,
+    /// From the file's tags, when it has them
+    artist: Option<String>,
+    elapsed: Option<f64>,
+    duration: Option<f64>,
+    playlist_pos: Option<usize>,
+}
+
+impl Track {
+    const PROPERTIES: &[&str] = &["media-title", "metadata/by-key/artist", "time-pos", "duration", "playlist-pos"];
+
+    fn apply(&mut self, event: &Value) {
+        if event.get("event").and_then(Value::as_str) != Some("property-change") {
+            return;
+        }
+        let Some(name) = event.get("name").and_then(Value::as_str) else {
+            return;
+        };
+        let data = event.get("data").unwrap_or(&Value::Null);
+        match name {
+            "media-title" => self.title = data.as_str().map(str::to_string),
+            "metadata/by-key/artist" => self.artist = data.as_str().map(str::to_string),
+            "time-pos" => self.elapsed = data.as_f64(),
+            "duration" => self.duration = data.as_f64(),
+            "playlist-pos" => self.playlist_pos = data.as_u64().map(|p| p as usize),
+            _ => {}
+        }
+    }
+
+    /// Title, artist and "elapsed / total" on their own lines, so an overlay
+    /// can lay them out. `channel` stands in for a missing artist tag
+    /// (YouTube streams don't have one). None until mpv has a title.
+    fn render(&self, channel: Option<&str>) -> Option<String> {
+        let title = self.title.as_deref().filter(|t| !t.is_empty())?;
+        let artist = self.artist.as_deref().or(channel).unwrap_or("");
+        let mut time = format_clock(self.elapsed.unwrap_or(0.0) as u64);
+        if let Some(duration) = self.duration {
+            time.push_str(&format!(" / {}", format_clock(duration as u64)));
+        }
+        Some(format!("{}\n{}\n{}\n", title, artist, time))
+    }
+}
+
+/// Follow the mpv listening on `socket` until it exits, keeping the
+/// configured file up to date, then empty it so the overlay goes blank.
+/// `channels` follows mpv's playlist, as far as it's known.
+pub async fn follow(socket: PathBuf, channels: Vec<String>) {
+    let Some(path) = path() else {
+        return;
+    };
+    if let Ok(mut ipc) = MpvIpc::connect(&socket).await {
+        let mut observed = true;
+        for (id, property) in Track::PROPERTIES.iter().enumerate() {
+            observed &= ipc.observe(id as u64 + 1, property).await.is_ok();
+        }
+        if observed {
+            update(&mut ipc, path, &channels).await;
+        }
+    }
+    clear();
+}
+
+async fn update(ipc: &mut MpvIpc, path: &Path, channels: &[String]) {
+    let mut track = Track::default();
+    let mut written = String::new();
+    let mut tick = tokio::time::interval(UPDATE_INTERVAL);
+    loop {
+        tokio::select! {
+            event = ipc.next_event() => match event {
+                Some(event) => track.apply(&event),
+                None => return,
+            },
+            _ = tick.tick() => {
+                let channel = track
+                    .playlist_pos
+                    .and_then(|pos| channels.get(pos))
+                    .map(String::as_str)
+                    .filter(|c| !c.is_empty());
+                if let Some(text) = track.render(channel)
+                    && text != written
+                {
+                    if let Err(e) = write(path, &text) {
+                        debug!("Can't write {}: {}", path.display(), e);
+                    }
+                    written = text;
+                }
+            }
+        }
+    }
+}
+
+/// Empty the file (nothing is playing)
+pub fn clear() {
+    if let Some(path) = path() {
+        let _ = write(path, "");
+    }
+}
+
+/// Replace the file in one go, so a text source never reads half of it
+fn write(path: &Path, text: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let staged = path.with_file_name(format!(".{}.yt-chill-tmp", name));
+    std::fs::write(&staged, text)?;
+    std::fs::rename(&staged, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(name: &str, data: Value) -> Value {
+        json!({ "event": "property-change", "id": 1, "name": name, "data": data })
+    }
+
+    #[test]
+    fn test_render() {
+        let mut track = Track::default();
+        assert_eq!(track.render(Some("Lofi Girl")), None);
+
+        track.apply(&change("media-title", json!("lofi hip hop radio")));
+        track.apply(&change("time-pos", json!(83.4)));
+        assert_eq!(track.render(Some("Lofi Girl")).unwrap(), "lofi hip hop radio\nLofi Girl\n1:23\n");
+
+        track.apply(&change("duration", json!(225.0)));
+        track.apply(&change("metadata/by-key/artist", json!("Chillhop")));
+        assert_eq!(track.render(Some("Lofi Girl")).unwrap(), "lofi hip hop radio\nChillhop\n1:23 / 3:45\n");
+
+        // No tag and no known channel leaves the line empty, so the layout holds
+        track.apply(&change("metadata/by-key/artist", Value::Null));
+        assert_eq!(track.render(None).unwrap(), "lofi hip hop radio\n\n1:23 / 3:45\n");
+    }
+}
@@ -1,54 +1,514 @@
 //! Player module - mpv and syncplay integration
 
+use crate::core::events::{self, Event};
+use crate::core::mpd::{self, MpdClient};
+#[cfg(unix)]
+use crate::core::now_playing_file;
+use crate::core::ytdlp;
 use crate::error::{Result, YtChillError};
-use crate::types::PlayOptions;
+use crate::types::{PlayOptions, PlaybackOutcome, PlayerType, Video};
+use crate::utils::paths::get_watch_later_dir;
+use crate::utils::shutdown;
+use colored::Colorize;
+use std::collections::HashMap;
 use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
 use std::time::Duration;
 use tokio::process::Command;
 use tokio::time::sleep;
+use tracing::debug;
+
+/// yt-dlp format for the best video no taller than `max_height`, or the
+/// smallest one when nothing fits. `muxed` picks single-file streams, for
+/// players (and downloads) that can't combine separate audio and video.
+pub fn capped_video_format(max_height: u32, muxed: bool) -> String {
+    if muxed {
+        format!("best[height<={0}]/worst", max_height)
+    } else {
+        format!("bestvideo[height<={0}]+bestaudio/best[height<={0}]/worst", max_height)
+    }
+}
+
+/// yt-dlp format for `data_saver`: about 64 kbps audio, 360p video,
+/// falling back to the smallest stream when those aren't offered
+pub fn data_saver_format(video: bool, player: &PlayerType) -> &'static str {
+    match player {
+        // VLC gets a single URL, so video needs a muxed stream
+        PlayerType::Vlc if video => "best[height<=360]/worst",
+        _ if video => "bestvideo[height<=360]+bestaudio[abr<=64]/best[height<=360]/worst",
+        _ => "bestaudio[abr<=64]/worstaudio/worst",
+    }
+}
 
 /// Build YouTube URL from video ID
 pub fn build_video_url(video_id: &str) -> String {
     format!("https://www.youtube.com/watch?v={}", video_id)
 }
 
-/// Play audio/video using mpv with buffering indicator
-pub async fn play(url: &str, options: &PlayOptions) -> Result<()> {
-    // Check if mpv is available
-    if !is_command_available("mpv").await {
-        return Err(YtChillError::MissingDependency("mpv".into()));
+/// Play a YouTube video
+pub async fn play_video(video: &Video, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    let opts = PlayOptions {
+        title: Some(video.title.clone()),
+        channel: Some(video.author.clone()),
+        id: Some(video.id.clone()),
+        ..options.clone()
+    };
+    events::emit(Event::TrackStarted { video }).await;
+    let outcome = play(&build_video_url(&video.id), &opts).await?;
+    let quit = outcome == PlaybackOutcome::Quit;
+    events::emit(Event::TrackFinished { video, quit }).await;
+    Ok(outcome)
+}
+
+/// Run the user's pre-play hook. Failures are reported but don't stop playback.
+async fn run_pre_play_hook(hook: &str, url: &str, options: &PlayOptions) {
+    let id = options.id.as_deref().unwrap_or("");
+    debug!("Running pre_play_hook: {}", hook);
+    let result = Command::new("sh")
+        .kill_on_drop(true)
+        .args(["-c", hook])
+        .env("YT_CHILL_URL", url)
+        .env("YT_CHILL_TITLE", options.title.as_deref().unwrap_or(""))
+        .env("YT_CHILL_ID", id)
+        .stdin(Stdio::null())
+        .status()
+        .await;
+
+    let problem = match result {
+        Ok(status) if status.success() => return,
+        Ok(status) => format!("exited with {}", status),
+        Err(e) => e.to_string(),
+    };
+    eprintln!("{} pre_play_hook {}", "Warning:".yellow(), problem);
+}
+
+/// Play with the configured player
+pub async fn play(url: &str, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    // MPD plays in its own process, so Ctrl+C is ours to handle
+    let _player = (options.player != PlayerType::Mpd).then(shutdown::player_running);
+    if let Some(ref hook) = options.pre_play_hook {
+        run_pre_play_hook(hook, url, options).await;
+    }
+
+    match options.player {
+        PlayerType::Mpv => play_mpv(url, options).await,
+        PlayerType::Syncplay => play_with_syncplay(url).await.map(|_| PlaybackOutcome::Finished),
+        PlayerType::Vlc => play_vlc(url, options).await,
+        PlayerType::Mpd => queue_in_mpd(url, options).await,
+        PlayerType::Custom(ref template) => play_custom(template, url, options).await,
     }
+}
+
+/// Stream URLs by page URL and format
+type StreamMap = HashMap<(String, String), Vec<String>>;
+
+/// Streams resolved ahead of time by [`prefetch`]
+static PREFETCHED: LazyLock<Mutex<StreamMap>> = LazyLock::new(|| Mutex::new(HashMap::new()));
 
-    let mut args = vec!["--really-quiet"];
+/// yt-dlp format the configured player gets streams in, or None for players
+/// that look streams up themselves (syncplay, custom commands)
+fn stream_format(options: &PlayOptions) -> Option<String> {
+    if let Some(format) = requested_format(options) {
+        return Some(format);
+    }
+    let format = match options.player {
+        PlayerType::Mpv if options.video => "bestvideo+bestaudio/best",
+        // VLC gets a single URL, so video needs a muxed stream
+        PlayerType::Vlc if options.video => "best",
+        PlayerType::Mpv | PlayerType::Vlc | PlayerType::Mpd => "bestaudio/best",
+        PlayerType::Syncplay | PlayerType::Custom(_) => return None,
+    };
+    Some(format.into())
+}
+
+/// The format asked for, or the `max_height` cap in video mode (mpv and VLC)
+fn requested_format(options: &PlayOptions) -> Option<String> {
+    if options.format.is_some() {
+        return options.format.clone();
+    }
+    let height = options.max_height.filter(|_| options.video)?;
+    match options.player {
+        PlayerType::Mpv => Some(capped_video_format(height, false)),
+        PlayerType::Vlc => Some(capped_video_format(height, true)),
+        _ => None,
+    }
+}
+
+/// Resolve a video's stream in the background while something else plays,
+/// so it starts straight away when its turn comes
+pub fn prefetch(video: &Video, options: &PlayOptions) {
+    let Some(format) = stream_format(options) else {
+        return;
+    };
+    // mpv remembers resume positions by URL, so it needs the YouTube one
+    if options.resume && options.player == PlayerType::Mpv {
+        return;
+    }
+
+    let url = build_video_url(&video.id);
+    let options = options.clone();
+    tokio::spawn(async move {
+        if let Ok(streams) = resolve_stream_urls(&url, &format, &options).await
+            && let Ok(mut prefetched) = PREFETCHED.lock()
+        {
+            prefetched.insert((url, format), streams);
+        }
+    });
+}
+
+/// Take a stream [`prefetch`] resolved for this URL and format, if it's ready
+fn take_prefetched(url: &str, format: &str) -> Option<Vec<String>> {
+    PREFETCHED.lock().ok()?.remove(&(url.to_string(), format.to_string()))
+}
+
+/// Resolve a YouTube URL to direct stream URL(s) with `yt-dlp -g`, for
+/// players that can't do it themselves. Local files pass through unchanged.
+pub async fn resolve_stream_urls(url: &str, format: &str, options: &PlayOptions) -> Result<Vec<String>> {
+    if !url.starts_with("http") {
+        return Ok(vec![url.to_string()]);
+    }
+    if let Some(streams) = take_prefetched(url, format) {
+        return Ok(streams);
+    }
+    ytdlp::ensure_available().await?;
+
+    let mut args = vec!["-g", "-f", format];
+    if let Some(ref browser) = options.cookies_from_browser {
+        args.extend(["--cookies-from-browser", browser]);
+    }
+    if let Some(ref proxy) = options.proxy {
+        args.extend(["--proxy", proxy]);
+    }
+    args.push(url);
+
+    debug!("Running yt-dlp {}", args.join(" "));
+    let output = ytdlp::command()
+        .args(&args)
+        .output()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start yt-dlp: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        debug!(status = %output.status, "yt-dlp failed: {}", stderr.trim());
+        return Err(YtChillError::from_ytdlp(&stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Play with VLC, handing it the stream yt-dlp picked
+async fn play_vlc(url: &str, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    // cvlc is VLC without a GUI; prefer it for audio-only playback
+    let binary = if !options.video && is_command_available("cvlc").await {
+        "cvlc"
+    } else if is_command_available("vlc").await {
+        "vlc"
+    } else {
+        return Err(YtChillError::MissingDependency("vlc".into()));
+    };
+
+    // VLC's own YouTube support breaks often, so let yt-dlp choose the stream
+    let format = stream_format(options).unwrap_or_default();
+    let streams = resolve_stream_urls(url, &format, options).await?;
+    let Some(stream) = streams.first() else {
+        return Err(YtChillError::Spawn("yt-dlp returned no stream URL".into()));
+    };
+
+    let mut args: Vec<String> = vec!["--play-and-exit".into(), "--quiet".into()];
+    if !options.video {
+        args.extend(["--no-video".into(), "--intf".into(), "dummy".into()]);
+    }
+    if let Some(ref title) = options.title {
+        args.push(format!("--meta-title={}", title));
+    }
+    if let Some(end) = options.end {
+        args.push(format!("--stop-time={}", end.as_secs()));
+    }
+    args.push(stream.clone());
+
+    println!("🎵 Vibing in VLC... (Ctrl+C to stop)");
+    debug!("Running {} {}", binary, args.join(" "));
+    let status = Command::new(binary)
+        .kill_on_drop(true)
+        .args(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start {}: {}", binary, e)))?;
+
+    vlc_outcome(status.code())
+}
+
+/// Hand the audio stream to MPD, then wait while MPD plays it, so the
+/// queue and `--for` move on when the track does
+async fn queue_in_mpd(url: &str, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    let address = options.mpd_host.clone().unwrap_or_else(|| {
+        let host = std::env::var("MPD_HOST").unwrap_or_else(|_| "localhost".into());
+        let socket = host.rsplit('@').next().is_some_and(mpd::is_socket_path);
+        match std::env::var("MPD_PORT") {
+            Ok(port) if !host.contains(':') && !socket => format!("{}:{}", host, port),
+            _ => host,
+        }
+    });
+
+    // Direct googlevideo URLs expire after a few hours, so queue soon-to-play items
+    let format = stream_format(options).unwrap_or_default();
+    let streams = resolve_stream_urls(url, &format, options).await?;
+    let Some(stream) = streams.first() else {
+        return Err(YtChillError::Spawn("yt-dlp returned no stream URL".into()));
+    };
+
+    let mut client = MpdClient::connect(&address).await?;
+    let id = client.enqueue(stream).await?;
+    println!("🎵 Queued in MPD ({}), waiting for it to play", address);
+    client.wait_until_played(&id).await
+}
+
+/// Interpret VLC's exit: 0 is a normal finish or quit from the interface,
+/// no code means it was interrupted (Ctrl+C), anything else is an error
+fn vlc_outcome(code: Option<i32>) -> Result<PlaybackOutcome> {
+    match code {
+        Some(0) => Ok(PlaybackOutcome::Finished),
+        None => Ok(PlaybackOutcome::Quit),
+        Some(code) => Err(YtChillError::Spawn(format!("vlc exited with code: {}", code))),
+    }
+}
+
+/// Quote a value for `sh -c`
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Fill `{url}` and `{title}` into a custom player template (the URL is
+/// appended if the template doesn't mention it)
+fn expand_player_template(template: &str, url: &str, title: &str) -> String {
+    // One pass over the template, so placeholders inside the values stay literal
+    let mut command = String::with_capacity(template.len() + url.len() + title.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("{url}") {
+            command.push_str(&shell_quote(url));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{title}") {
+            command.push_str(&shell_quote(title));
+            rest = after;
+        } else {
+            command.push('{');
+            rest = &rest[1..];
+        }
+    }
+    command.push_str(rest);
+    if !template.contains("{url}") {
+        command.push(' ');
+        command.push_str(&shell_quote(url));
+    }
+    command
+}
+
+/// Play with a user-supplied command template, e.g. "celluloid {url}"
+async fn play_custom(template: &str, url: &str, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    let command = expand_player_template(template, url, options.title.as_deref().unwrap_or(""));
+
+    debug!("Running player: {}", command);
+    let status = Command::new("sh")
+        .kill_on_drop(true)
+        .args(["-c", &command])
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .status()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start player: {}", e)))?;
+
+    if !status.success() {
+        return Err(YtChillError::Spawn(format!(
+            "player exited with code: {:?}",
+            status.code()
+        )));
+    }
+
+    Ok(PlaybackOutcome::Finished)
+}
+
+/// Browsers yt-dlp can read cookies from
+const COOKIE_BROWSERS: &[&str] = &[
+    "brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale",
+];
+
+/// Whether `spec` is a yt-dlp `--cookies-from-browser` value, e.g. "firefox"
+/// or "chrome:Profile 1"
+pub fn is_valid_cookie_browser(spec: &str) -> bool {
+    let browser = spec.split([':', '+']).next().unwrap_or("").to_lowercase();
+    COOKIE_BROWSERS.contains(&browser.as_str())
+}
+
+/// mpv arguments for the play options (everything but the URLs)
+fn mpv_args(options: &PlayOptions) -> Vec<String> {
+    let mut args: Vec<String> = if options.chapters {
+        // Keep mpv's status line (chapter and progress) but hide its log
+        vec![
+            "--msg-level=all=error".into(),
+            "--term-status-msg=${?chapter:[${chapter}] }${time-pos} / ${duration} (${speed}x)".into(),
+        ]
+    } else {
+        vec!["--really-quiet".into()]
+    };
 
     // Audio-only by default, unless --video flag is passed
     if !options.video {
-        args.push("--no-video");
+        args.push("--no-video".into());
     }
 
-    if let Some(ref format) = options.format {
-        args.push("--ytdl-format");
+    if let Some(format) = requested_format(options) {
+        args.push("--ytdl-format".into());
         args.push(format);
     }
 
-    args.push(url);
+    // mpv runs yt-dlp itself, so it needs to know which one
+    if let Some(path) = ytdlp::custom_path() {
+        args.push(format!("--script-opts-append=ytdl_hook-ytdl_path={}", path));
+    }
 
-    // Show snarky buffering message
-    print!("⏳ Convincing YouTube to share... 🙄");
-    use std::io::Write;
-    std::io::stdout().flush().ok();
+    // Signed-in cookies for age-restricted and members-only videos
+    if let Some(ref browser) = options.cookies_from_browser {
+        args.push(format!("--ytdl-raw-options-append=cookies-from-browser={}", browser));
+    }
+
+    // yt-dlp resolves the stream through the proxy; mpv can only fetch through HTTP ones
+    if let Some(ref proxy) = options.proxy {
+        args.push(format!("--ytdl-raw-options-append=proxy={}", proxy));
+        if proxy.starts_with("http") {
+            args.push(format!("--http-proxy={}", proxy));
+        }
+    }
 
-    // Spawn a background task to show "now playing" after typical buffer time
-    let playing_msg_handle = tokio::spawn(async {
-        sleep(Duration::from_secs(6)).await;
-        // Clear the line and show playing message
-        print!("\r\x1b[K");  // Clear current line
+    if let Some(speed) = options.speed {
+        args.push(format!("--speed={}", speed));
+    }
+
+    if options.resume {
+        args.push("--save-position-on-quit".into());
+        args.push(format!("--watch-later-dir={}", get_watch_later_dir()));
+    }
+
+    // Start at A and jump back there on reaching B
+    if let Some((a, b)) = options.ab_loop {
+        args.push(format!("--start={}", a));
+        args.push(format!("--ab-loop-a={}", a));
+        args.push(format!("--ab-loop-b={}", b));
+    }
+
+    // Live streams start at the live edge unless asked otherwise
+    if options.live_from_start {
+        args.push("--ytdl-raw-options-append=live-from-start=".into());
+    }
+
+    // Stop early (time-boxed sessions), optionally fading out first
+    if let Some(end) = options.end {
+        args.push(format!("--end={}", end.as_secs()));
+
+        if let Some(fade) = options.fade_out {
+            let start = end.saturating_sub(fade).as_secs();
+            args.push(format!(
+                "--af=lavfi=[afade=t=out:st={}:d={}]",
+                start,
+                fade.as_secs()
+            ));
+        }
+    }
+
+    // User extras last so they can override ours
+    args.extend(options.mpv_args.iter().cloned());
+    args
+}
+
+/// Play audio/video using mpv with buffering indicator
+async fn play_mpv(url: &str, options: &PlayOptions) -> Result<PlaybackOutcome> {
+    // Check if mpv is available
+    if !is_command_available("mpv").await {
+        return Err(YtChillError::MissingDependency("mpv".into()));
+    }
+
+    let mut args = mpv_args(options);
+    // A stream prefetched while the previous track played skips mpv's own lookup
+    let prefetched = stream_format(options).and_then(|format| take_prefetched(url, &format));
+    match prefetched.as_deref() {
+        Some([stream]) => args.push(stream.clone()),
+        Some([video, audio]) => {
+            args.push(format!("--audio-file={}", audio));
+            args.push(video.clone());
+        }
+        _ => args.push(url.to_string()),
+    }
+    if prefetched.is_some()
+        && let Some(ref title) = options.title
+    {
+        args.push(format!("--force-media-title={}", title));
+    }
+
+    // A live now-playing line replaces mpv's own status output
+    #[cfg(unix)]
+    if crate::ui::now_playing::enabled() {
+        let socket = crate::core::mpv_ipc::socket_path();
+        args.splice(0..0, ["--terminal=no".to_string(), format!("--input-ipc-server={}", socket.display())]);
+        debug!("Running mpv {}", args.join(" "));
+        let mut child = Command::new("mpv")
+            .kill_on_drop(true)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| YtChillError::Spawn(format!("Failed to start mpv: {}", e)))?;
+        let line = tokio::spawn(crate::ui::now_playing::show(socket.clone()));
+        let export = tokio::spawn(now_playing_file::follow(socket.clone(), options.channel.iter().cloned().collect()));
+        let outcome = wait_mpv(&mut child).await;
+        let _ = line.await;
+        stop_export(export).await;
+        let _ = std::fs::remove_file(&socket);
+        return outcome;
+    }
+
+    // Without the line, mpv only needs a socket for the now-playing file
+    #[cfg(unix)]
+    let export = now_playing_file::path().map(|_| {
+        let socket = crate::core::mpv_ipc::socket_path();
+        args.insert(0, format!("--input-ipc-server={}", socket.display()));
+        let channels = options.channel.iter().cloned().collect();
+        (tokio::spawn(now_playing_file::follow(socket.clone(), channels)), socket)
+    });
+
+    use std::io::Write;
+    let playing_msg_handle = if prefetched.is_some() {
         println!("🎵 Vibing... Sit back and chill. (space=pause, q=quit)");
+        None
+    } else {
+        // Show snarky buffering message
+        print!("⏳ Convincing YouTube to share... 🙄");
         std::io::stdout().flush().ok();
-    });
+
+        // Spawn a background task to show "now playing" after typical buffer time
+        Some(tokio::spawn(async {
+            sleep(Duration::from_secs(6)).await;
+            // Clear the line and show playing message
+            print!("\r\x1b[K");  // Clear current line
+            println!("🎵 Vibing... Sit back and chill. (space=pause, q=quit)");
+            std::io::stdout().flush().ok();
+        }))
+    };
 
     // Spawn mpv with inherited stdio so keyboard controls work
+    debug!("Running mpv {}", args.join(" "));
     let status = Command::new("mpv")
+        .kill_on_drop(true)
         .args(&args)
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
@@ -58,8 +518,34 @@ pub async fn play(url: &str, options: &PlayOptions) -> Result<()> {
         .map_err(|e| YtChillError::Spawn(format!("Failed to start mpv: {}", e)))?;
 
     // Cancel the message task if mpv exits quickly (e.g., error or early quit)
-    playing_msg_handle.abort();
+    if let Some(handle) = playing_msg_handle {
+        handle.abort();
+    }
+
+    // Clear the buffering/playing line
+    print!("\r\x1b[K");
+
+    #[cfg(unix)]
+    if let Some((export, socket)) = export {
+        stop_export(export).await;
+        let _ = std::fs::remove_file(&socket);
+    }
+
+    mpv_outcome(status)
+}
+
+/// Stop following mpv for the now-playing file once it has exited. The
+/// follower may still be waiting for a socket mpv never opened.
+#[cfg(unix)]
+async fn stop_export(export: tokio::task::JoinHandle<()>) {
+    export.abort();
+    let _ = export.await;
+    now_playing_file::clear();
+}
 
+/// How mpv's exit status maps to a playback outcome
+fn mpv_outcome(status: std::process::ExitStatus) -> Result<PlaybackOutcome> {
+    debug!(%status, "mpv exited");
     if !status.success() {
         // Don't treat user quit (q key) as an error
         if status.code() != Some(4) {
@@ -68,22 +554,109 @@ pub async fn play(url: &str, options: &PlayOptions) -> Result<()> {
                 status.code()
             )));
         }
+        return Ok(PlaybackOutcome::Quit);
     }
 
-    // Clear line and show goodbye
-    print!("\r\x1b[K");
-    println!("👋 Thanks for chilling.");
+    Ok(PlaybackOutcome::Finished)
+}
 
-    Ok(())
+/// Start one mpv for a whole playlist, so tracks follow each other without
+/// a gap or re-buffering. mpv listens for JSON IPC on `socket` (`loadfile
+/// ... append` adds to the playlist); wait for it with [`wait_mpv`]. In a
+/// terminal, show [`crate::ui::now_playing::show`] alongside.
+pub async fn spawn_mpv_playlist(
+    videos: &[Video],
+    options: &PlayOptions,
+    socket: &std::path::Path,
+) -> Result<tokio::process::Child> {
+    if !is_command_available("mpv").await {
+        return Err(YtChillError::MissingDependency("mpv".into()));
+    }
+
+    let mut args = mpv_args(options);
+    args.extend([
+        "--gapless-audio=yes".into(),
+        "--prefetch-playlist=yes".into(),
+        format!("--input-ipc-server={}", socket.display()),
+    ]);
+    args.extend(videos.iter().map(|v| build_video_url(&v.id)));
+    #[cfg(unix)]
+    if now_playing_file::path().is_some() {
+        let channels = videos.iter().map(|v| v.author.clone()).collect();
+        tokio::spawn(now_playing_file::follow(socket.to_path_buf(), channels));
+    }
+
+    // The caller shows the now-playing line, which relays keys to mpv
+    #[cfg(unix)]
+    let line = crate::ui::now_playing::enabled();
+    #[cfg(not(unix))]
+    let line = false;
+    if line {
+        args.insert(0, "--terminal=no".into());
+    }
+    let stdio = || if line { Stdio::null() } else { Stdio::inherit() };
+    debug!("Running mpv {}", args.join(" "));
+
+    Command::new("mpv")
+        .kill_on_drop(true)
+        .args(&args)
+        .stdin(stdio())
+        .stdout(stdio())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start mpv: {}", e)))
+}
+
+/// Start mpv with an empty playlist that waits for `loadfile` commands on
+/// the JSON IPC `socket` (used by `--serve`)
+pub async fn spawn_mpv_idle(options: &PlayOptions, socket: &std::path::Path) -> Result<tokio::process::Child> {
+    if !is_command_available("mpv").await {
+        return Err(YtChillError::MissingDependency("mpv".into()));
+    }
+
+    let mut args = mpv_args(options);
+    args.extend([
+        "--idle=yes".into(),
+        "--terminal=no".into(),
+        "--gapless-audio=yes".into(),
+        "--prefetch-playlist=yes".into(),
+        format!("--input-ipc-server={}", socket.display()),
+    ]);
+    // Tracks are added as it goes, so channel names aren't known up front
+    #[cfg(unix)]
+    if now_playing_file::path().is_some() {
+        tokio::spawn(now_playing_file::follow(socket.to_path_buf(), Vec::new()));
+    }
+    debug!("Running mpv {}", args.join(" "));
+
+    Command::new("mpv")
+        .kill_on_drop(true)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start mpv: {}", e)))
+}
+
+/// Wait for mpv started by [`spawn_mpv_playlist`] to exit
+pub async fn wait_mpv(child: &mut tokio::process::Child) -> Result<PlaybackOutcome> {
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("mpv failed: {}", e)))?;
+    mpv_outcome(status)
 }
 
 /// Play with syncplay
 pub async fn play_with_syncplay(url: &str) -> Result<()> {
+    let _player = shutdown::player_running();
     if !is_command_available("syncplay").await {
         return Err(YtChillError::MissingDependency("syncplay".into()));
     }
 
     let status = Command::new("syncplay")
+        .kill_on_drop(true)
         .arg(url)
         .status()
         .await
@@ -99,6 +672,54 @@ pub async fn play_with_syncplay(url: &str) -> Result<()> {
     Ok(())
 }
 
+/// A hard-to-guess room name like "yt-chill-3f9a1c"
+pub fn new_room_name() -> String {
+    use sha2::{Digest, Sha256};
+    let seed = format!(
+        "{}-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id()
+    );
+    let hash = Sha256::digest(seed.as_bytes());
+    let suffix: String = hash.iter().take(3).map(|b| format!("{:02x}", b)).collect();
+    format!("yt-chill-{}", suffix)
+}
+
+/// What friends run to join a room
+pub fn syncplay_invite(server: &str, room: &str) -> String {
+    format!("syncplay --host {} --room {}", server, room)
+}
+
+/// Host a syncplay room with `urls` as its shared playlist
+pub async fn host_syncplay(urls: &[String], server: &str, room: &str) -> Result<()> {
+    let _player = shutdown::player_running();
+    if !is_command_available("syncplay").await {
+        return Err(YtChillError::MissingDependency("syncplay".into()));
+    }
+
+    // Syncplay loads a shared playlist from a file with one entry per line
+    let playlist = std::env::temp_dir().join(format!("{}.txt", room));
+    tokio::fs::write(&playlist, urls.join("\n")).await?;
+
+    let status = Command::new("syncplay")
+        .kill_on_drop(true)
+        .args(["--host", server, "--room", room, "--load-playlist-from-file"])
+        .arg(&playlist)
+        .status()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start syncplay: {}", e)))?;
+
+    tokio::fs::remove_file(&playlist).await.ok();
+
+    if !status.success() {
+        return Err(YtChillError::Spawn(format!(
+            "syncplay exited with code: {:?}",
+            status.code()
+        )));
+    }
+    Ok(())
+}
+
 /// Check if a command is available in PATH
 async fn is_command_available(cmd: &str) -> bool {
     Command::new("which")
@@ -108,3 +729,75 @@ async fn is_command_available(cmd: &str) -> bool {
         .map(|o| o.status.success())
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_room_name() {
+        let room = new_room_name();
+        assert!(room.starts_with("yt-chill-"));
+        assert_eq!(room.len(), "yt-chill-".len() + 6);
+        assert_eq!(
+            syncplay_invite("syncplay.pl:8999", &room),
+            format!("syncplay --host syncplay.pl:8999 --room {}", room)
+        );
+    }
+
+    #[test]
+    fn test_cookie_browser() {
+        assert!(is_valid_cookie_browser("firefox"));
+        assert!(is_valid_cookie_browser("chrome:Profile 1"));
+        assert!(!is_valid_cookie_browser("netscape"));
+    }
+
+    #[test]
+    fn test_stream_format() {
+        let audio = PlayOptions::default();
+        assert_eq!(stream_format(&audio).as_deref(), Some("bestaudio/best"));
+
+        let vlc_video = PlayOptions { video: true, player: PlayerType::Vlc, ..Default::default() };
+        assert_eq!(stream_format(&vlc_video).as_deref(), Some("best"));
+
+        let custom = PlayOptions { player: PlayerType::Custom("celluloid {url}".into()), ..Default::default() };
+        assert_eq!(stream_format(&custom), None);
+
+        let saver = PlayOptions { format: Some(data_saver_format(false, &PlayerType::Mpv).into()), ..Default::default() };
+        assert_eq!(stream_format(&saver).as_deref(), Some("bestaudio[abr<=64]/worstaudio/worst"));
+        assert!(!data_saver_format(true, &PlayerType::Vlc).contains('+'));
+
+        let capped = PlayOptions { video: true, max_height: Some(720), ..Default::default() };
+        assert_eq!(
+            stream_format(&capped).as_deref(),
+            Some("bestvideo[height<=720]+bestaudio/best[height<=720]/worst")
+        );
+        let capped_audio = PlayOptions { max_height: Some(720), ..Default::default() };
+        assert_eq!(stream_format(&capped_audio).as_deref(), Some("bestaudio/best"));
+    }
+
+    #[test]
+    fn test_vlc_outcome() {
+        assert_eq!(vlc_outcome(Some(0)).unwrap(), PlaybackOutcome::Finished);
+        assert_eq!(vlc_outcome(None).unwrap(), PlaybackOutcome::Quit);
+        assert!(vlc_outcome(Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_expand_player_template() {
+        assert_eq!(
+            expand_player_template("vlc --play-and-exit {url}", "https://y/v", "Rock 'n' Roll"),
+            "vlc --play-and-exit 'https://y/v'"
+        );
+        assert_eq!(
+            expand_player_template("iina --mpv-title={title}", "u", "it's"),
+            "iina --mpv-title='it'\\''s' 'u'"
+        );
+        // Placeholders and quotes in the values don't get expanded again
+        assert_eq!(
+            expand_player_template("player {url} --title {title}", "/music/{title}'; rm -rf ~ #.mp3", "{url}'x"),
+            "player '/music/{title}'\\''; rm -rf ~ #.mp3' --title '{url}'\\''x'"
+        );
+        assert_eq!(expand_player_template("echo {{url}}", "u", ""), "echo {'u'}");
+    }
+}
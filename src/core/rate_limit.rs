@@ -0,0 +1,68 @@
+//! Token-bucket rate limiting for YouTube requests
+
+use std::time::{Duration, Instant};
+
+/// Requests allowed back to back before the per-minute pace kicks in
+const MAX_BURST: f64 = 5.0;
+
+/// Token bucket that refills continuously at `per_minute` tokens a minute
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    /// Bucket allowing `per_minute` requests a minute, starting full
+    pub fn new(per_minute: u32, now: Instant) -> Self {
+        let capacity = MAX_BURST.min(per_minute as f64).max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: per_minute as f64 / 60.0,
+            last: now,
+        }
+    }
+
+    /// Reserve one token and return how long to wait before using it.
+    /// The balance may go negative, so callers queue up in order.
+    pub fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.refill_per_sec)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_paces_after_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60, start);
+
+        for _ in 0..5 {
+            assert_eq!(bucket.reserve(start), Duration::ZERO);
+        }
+        // Sixth request waits a second, seventh queues behind it
+        assert_eq!(bucket.reserve(start).as_secs(), 1);
+        assert_eq!(bucket.reserve(start).as_secs(), 2);
+
+        // After a long pause the bucket is full again, but no fuller
+        let later = start + Duration::from_secs(600);
+        for _ in 0..5 {
+            assert_eq!(bucket.reserve(later), Duration::ZERO);
+        }
+        assert!(bucket.reserve(later) > Duration::ZERO);
+    }
+}
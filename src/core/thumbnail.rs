@@ -0,0 +1,124 @@
+//! Video thumbnails for the fzf preview pane
+
+use crate::core::player::shell_quote;
+use crate::core::youtube;
+use crate::error::Result;
+use crate::utils::paths::{ensure_dir, find_executable, get_thumbnails_dir};
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio::fs;
+
+/// Thumbnails kept on disk (about 15 KB each); the least recently shown go first
+const MAX_THUMBNAILS: usize = 500;
+
+/// Whether `id` looks like a YouTube video ID (safe to use in paths and URLs)
+pub fn is_video_id(id: &str) -> bool {
+    id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Medium-size (320x180) thumbnail URL for a video
+pub fn thumbnail_url(id: &str) -> String {
+    format!("https://i.ytimg.com/vi/{}/mqdefault.jpg", id)
+}
+
+/// Download a video's thumbnail into the cache (once) and return its path
+pub async fn fetch_thumbnail(id: &str) -> Result<PathBuf> {
+    // The ID becomes a file name
+    if !is_video_id(id) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("not a video ID: {}", id)).into());
+    }
+
+    let path = PathBuf::from(get_thumbnails_dir()).join(format!("{}.jpg", id));
+    if path.exists() {
+        // Bump the modified time so eviction drops least recently shown ones first
+        let _ = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(SystemTime::now()));
+        return Ok(path);
+    }
+
+    let bytes = youtube::fetch_bytes(&thumbnail_url(id)).await?;
+    ensure_dir(&get_thumbnails_dir()).await?;
+    fs::write(&path, bytes).await?;
+    evict_thumbnails().await;
+    Ok(path)
+}
+
+/// Delete the least recently shown thumbnails past [`MAX_THUMBNAILS`]
+async fn evict_thumbnails() {
+    let Ok(mut entries) = fs::read_dir(get_thumbnails_dir()).await else {
+        return;
+    };
+    let mut files = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Ok(meta) = entry.metadata().await
+            && meta.is_file()
+        {
+            files.push((entry.path(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+        }
+    }
+    for path in pick_evictions(files, MAX_THUMBNAILS) {
+        let _ = fs::remove_file(path).await;
+    }
+}
+
+/// All but the `keep` most recently used files
+fn pick_evictions(mut files: Vec<(PathBuf, SystemTime)>, keep: usize) -> Vec<PathBuf> {
+    files.sort_by_key(|(_, used)| std::cmp::Reverse(*used));
+    files.into_iter().skip(keep).map(|(path, _)| path).collect()
+}
+
+/// Shell command that draws an image file (appended as the last argument)
+/// sized to the fzf preview pane, if this terminal can show images.
+/// kitty uses its own graphics protocol; chafa picks kitty, sixel or
+/// plain symbols depending on the terminal.
+fn image_renderer() -> Option<&'static str> {
+    let in_kitty = std::env::var_os("KITTY_WINDOW_ID").is_some();
+    if in_kitty && find_executable("kitty").is_some() {
+        return Some(
+            "kitty icat --clear --transfer-mode=memory --stdin=no \
+             --place=\"${FZF_PREVIEW_COLUMNS}x${FZF_PREVIEW_LINES}@0x0\"",
+        );
+    }
+    if find_executable("chafa").is_some() {
+        return Some("chafa --size=\"${FZF_PREVIEW_COLUMNS}x${FZF_PREVIEW_LINES}\"");
+    }
+    None
+}
+
+/// fzf `--preview` command: fetch the thumbnail for the video ID in `field`
+/// (e.g. `{3}`) through `yt-chill thumbnail`, then draw it
+pub fn preview_command(field: &str) -> Option<String> {
+    let renderer = image_renderer()?;
+    let exe = std::env::current_exe().ok()?;
+    Some(format!(
+        "f=$({} thumbnail {} 2>/dev/null) && {} \"$f\"",
+        shell_quote(&exe.to_string_lossy()),
+        field,
+        renderer
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_video_id() {
+        assert!(is_video_id("jfKfPfyJRdk"));
+        assert!(is_video_id("a-b_c123456"));
+        assert!(!is_video_id("short"));
+        assert!(!is_video_id("../../etc/p"));
+        assert!(!is_video_id("abc;rm -rf"));
+    }
+
+    #[test]
+    fn test_pick_evictions_keeps_recent() {
+        let now = SystemTime::now();
+        let file = |name: &str, age_secs: u64| (PathBuf::from(name), now - std::time::Duration::from_secs(age_secs));
+        let files = vec![file("old.jpg", 300), file("new.jpg", 1), file("mid.jpg", 60)];
+        assert_eq!(pick_evictions(files.clone(), 2), vec![PathBuf::from("old.jpg")]);
+        assert!(pick_evictions(files, 5).is_empty());
+    }
+}
@@ -0,0 +1,55 @@
+//! Time-boxed listening: play through a list until a time budget runs out
+
+use crate::core::player;
+use crate::error::Result;
+use crate::types::{PlayOptions, PlaybackOutcome, PlayedItem, Video};
+use std::time::{Duration, Instant};
+
+/// How long the final track fades out before the budget ends
+const FADE_OUT: Duration = Duration::from_secs(8);
+
+/// Don't start another track with less than this much budget left
+const MIN_REMAINING: Duration = Duration::from_secs(30);
+
+/// Play videos in order until `budget` is used up or the user quits.
+/// The last track is cut off at the budget with a gentle fade.
+pub async fn play_for(
+    videos: &[Video],
+    budget: Duration,
+    options: &PlayOptions,
+) -> Result<Vec<PlayedItem>> {
+    let started = Instant::now();
+    let mut played = Vec::new();
+
+    for (i, video) in videos.iter().enumerate() {
+        if let Some(next) = videos.get(i + 1) {
+            player::prefetch(next, options);
+        }
+
+        let remaining = budget.saturating_sub(started.elapsed());
+        if remaining < MIN_REMAINING {
+            break;
+        }
+
+        let opts = PlayOptions {
+            end: Some(remaining),
+            fade_out: Some(FADE_OUT.min(remaining)),
+            ..options.clone()
+        };
+
+        println!("▶ {}", video.title);
+        let track_started = Instant::now();
+        let outcome = player::play_video(video, &opts).await?;
+
+        played.push(PlayedItem {
+            video: video.clone(),
+            listened: track_started.elapsed(),
+        });
+
+        if outcome == PlaybackOutcome::Quit {
+            break;
+        }
+    }
+
+    Ok(played)
+}
@@ -1,15 +1,129 @@
 //! YouTube scraping and parsing
 
+use crate::core::http::{HttpBackend, HttpRequest, HttpResponse, ReqwestBackend};
+use crate::core::rate_limit::TokenBucket;
 use crate::error::{Result, YtChillError};
-use crate::types::Video;
+use crate::types::{Channel, NetworkConfig, Video};
+use crate::utils::time::format_age;
+use futures_util::{Stream, StreamExt, stream};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, trace, warn};
+
+/// First retry delay; doubles with each attempt
+const BACKOFF_BASE_MS: u64 = 500;
+
+/// Longest wait between attempts
+const BACKOFF_MAX_MS: u64 = 10_000;
 
 const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 
+/// Web client version sent with continuation requests when the page doesn't say
+const FALLBACK_CLIENT_VERSION: &str = "2.20240101.00.00";
+
+static PROXY: OnceLock<Option<String>> = OnceLock::new();
+static NETWORK: OnceLock<NetworkConfig> = OnceLock::new();
+static LIMITER: OnceLock<Option<Mutex<TokenBucket>>> = OnceLock::new();
+static BACKEND: OnceLock<Arc<dyn HttpBackend>> = OnceLock::new();
+
+/// Set once YouTube has shown us its EU consent page
+static SEND_CONSENT: AtomicBool = AtomicBool::new(false);
+
+/// Cookies that mark the consent page as answered (reject all, English)
+const CONSENT_COOKIES: &str = "CONSENT=YES+cb; SOCS=CAI";
+
+/// Route YouTube requests through a proxy for this run (first call wins).
+/// Without one, reqwest still honors HTTP(S)_PROXY from the environment.
+pub fn set_proxy(proxy: Option<String>) {
+    PROXY.get_or_init(|| proxy);
+}
+
+/// Set timeouts and retries for this run (first call wins)
+pub fn set_network(network: NetworkConfig) {
+    NETWORK.get_or_init(|| network);
+}
+
+/// Send YouTube requests through this backend instead of a reqwest client
+/// built from the proxy and network settings (first call wins)
+pub fn set_http_backend(backend: Arc<dyn HttpBackend>) {
+    BACKEND.get_or_init(|| backend);
+}
+
+/// Build the shared HTTP client now, after [`set_proxy`] and [`set_network`],
+/// rather than on the first request
+pub fn init_backend() -> Result<()> {
+    backend().map(|_| ())
+}
+
+/// Active network settings (defaults unless configured)
+fn network() -> NetworkConfig {
+    NETWORK.get().cloned().unwrap_or_default()
+}
+
+/// Wait for a slot under the `requests_per_minute` ceiling, shared by
+/// every request this process makes
+async fn throttle() {
+    let limiter = LIMITER.get_or_init(|| match network().requests_per_minute {
+        0 => None,
+        per_minute => Some(Mutex::new(TokenBucket::new(per_minute, Instant::now()))),
+    });
+    let Some(limiter) = limiter else {
+        return;
+    };
+
+    let wait = match limiter.lock() {
+        Ok(mut bucket) => bucket.reserve(Instant::now()),
+        Err(_) => Duration::ZERO,
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Whether `proxy` is a URL reqwest and yt-dlp both understand
+pub fn is_valid_proxy(proxy: &str) -> bool {
+    let Some((scheme, rest)) = proxy.split_once("://") else {
+        return false;
+    };
+    matches!(scheme, "http" | "https" | "socks4" | "socks5" | "socks5h") && !rest.is_empty()
+}
+
+/// The backend requests go through: the one set with [`set_http_backend`],
+/// else a reqwest client with the configured proxy and timeouts, built once
+fn backend() -> Result<Arc<dyn HttpBackend>> {
+    if let Some(backend) = BACKEND.get() {
+        return Ok(Arc::clone(backend));
+    }
+    let proxy = PROXY.get().and_then(|p| p.as_deref());
+    let built = ReqwestBackend::from_settings(&network(), proxy)?;
+    Ok(Arc::clone(BACKEND.get_or_init(|| Arc::new(built))))
+}
+
+/// The uploads tab of a channel, from a subscription-style handle ("@name"
+/// or "/channel/UC...") or a channel URL
+pub fn channel_uploads_url(channel: &str) -> String {
+    let channel = channel.trim().trim_end_matches('/');
+    let base = if channel.starts_with("http://") || channel.starts_with("https://") {
+        channel.to_string()
+    } else if channel.starts_with('/') {
+        format!("https://www.youtube.com{}", channel)
+    } else {
+        format!("https://www.youtube.com/@{}", channel.trim_start_matches('@'))
+    };
+    if base.ends_with("/videos") { base } else { format!("{}/videos", base) }
+}
+
 /// Build YouTube search URL
 fn build_search_url(query: &str, filter: &str) -> String {
     let encoded_query = urlencoding::encode(query);
     let sp = match filter {
         "video" => "EgIQAQ%3D%3D",
+        // Videos over 20 minutes
+        "long" => "EgQQARgC",
         "channel" => "EgIQAg%3D%3D",
         _ => "",
     };
@@ -19,40 +133,233 @@ fn build_search_url(query: &str, filter: &str) -> String {
     )
 }
 
-/// Fetch YouTube HTML with browser-like headers
-async fn fetch_youtube_html(url: &str) -> Result<String> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get(url)
-        .header("User-Agent", USER_AGENT)
-        .header("Accept-Language", "en-US,en;q=0.9")
-        .send()
+/// Delay before retry number `attempt` (1-based): exponential backoff with up
+/// to 50% random jitter, so parallel feed refreshes don't retry in lockstep
+fn backoff_delay(attempt: u32, jitter: u64) -> Duration {
+    let exp = BACKOFF_BASE_MS.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    let base = exp.min(BACKOFF_MAX_MS);
+    Duration::from_millis(base + jitter % (base / 2 + 1))
+}
+
+/// Cheap jitter source; doesn't need to be a good RNG
+fn jitter() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Whether a response status is worth retrying (rate limited or server error)
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Seconds from a `Retry-After: <secs>` header, capped to the max backoff
+fn retry_after(response: &HttpResponse) -> Option<Duration> {
+    let secs: u64 = response.header("Retry-After")?.trim().parse().ok()?;
+    Some(Duration::from_secs(secs).min(Duration::from_millis(BACKOFF_MAX_MS)))
+}
+
+/// Whether a response is YouTube's cookie consent interstitial rather than
+/// the page we asked for (served to EU IPs)
+fn is_consent_page(final_url: &str, html: &str) -> bool {
+    final_url.starts_with("https://consent.youtube.com")
+        || (html.contains("consent.youtube.com/save") && !html.contains("ytInitialData"))
+}
+
+/// Whether YouTube sent us to Google's "unusual traffic" page
+fn is_rate_limit_page(final_url: &str) -> bool {
+    final_url.contains("google.com/sorry")
+}
+
+/// Fetch YouTube HTML with browser-like headers, retrying timeouts, failed
+/// connections and 429/5xx responses with exponential backoff. If YouTube
+/// answers with its consent page, retry once with the consent cookies set.
+async fn fetch_youtube_html(http: &dyn HttpBackend, url: &str) -> Result<String> {
+    fetch_youtube(http, url, None).await
+}
+
+/// Like [`fetch_youtube_html`], POSTing `body` as JSON when there is one
+async fn fetch_youtube(http: &dyn HttpBackend, url: &str, body: Option<&Value>) -> Result<String> {
+    let max_attempts = network().max_attempts.max(1);
+
+    let mut attempt = 1;
+    loop {
+        throttle().await;
+        let consent = SEND_CONSENT.load(Ordering::Relaxed);
+        let mut request = match body {
+            Some(body) => HttpRequest::post_json(url, body.clone()),
+            None => HttpRequest::get(url),
+        };
+        request = request
+            .header("User-Agent", USER_AGENT)
+            .header("Accept-Language", "en-US,en;q=0.9");
+        if consent {
+            request = request.header("Cookie", CONSENT_COOKIES);
+        }
+        debug!(attempt, consent, "{} {}", request.method(), url);
+        let started = Instant::now();
+        let result = http.send(request).await;
+        if let Ok(ref response) = result {
+            debug!(status = %response.status, elapsed_ms = started.elapsed().as_millis() as u64, "Response for {}", url);
+        }
+
+        let mut rate_limited = false;
+        let (reason, wait) = match result {
+            Ok(response) if response.status.is_success() => {
+                let final_url = response.url.clone();
+                let html = response.text();
+                if is_rate_limit_page(&final_url) {
+                    return Err(YtChillError::RateLimited(format!("redirected to {}", final_url)));
+                }
+                if consent && is_consent_page(&final_url, &html) {
+                    return Err(YtChillError::ConsentRequired(url.to_string()));
+                }
+                if !consent && is_consent_page(&final_url, &html) {
+                    debug!("Got YouTube's consent page ({}), retrying with consent cookies", final_url);
+                    SEND_CONSENT.store(true, Ordering::Relaxed);
+                    continue;
+                }
+                return Ok(html);
+            }
+            Ok(response) if is_retryable_status(response.status) => {
+                rate_limited = response.status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                (format!("HTTP {}", response.status), retry_after(&response))
+            }
+            Ok(response) => {
+                return Err(YtChillError::Network(format!(
+                    "HTTP {}: {}",
+                    response.status,
+                    url
+                )));
+            }
+            // Timeouts and failed connections, see HttpBackend::send
+            Err(YtChillError::Network(reason)) => (reason, None),
+            Err(e) => return Err(e),
+        };
+
+        if attempt >= max_attempts && rate_limited {
+            return Err(YtChillError::RateLimited(format!("{} after {} attempt(s)", reason, attempt)));
+        }
+        if attempt >= max_attempts {
+            return Err(YtChillError::Network(format!(
+                "{} after {} attempt(s): {}",
+                reason, attempt, url
+            )));
+        }
+
+        let delay = wait.unwrap_or_else(|| backoff_delay(attempt, jitter()));
+        warn!(attempt, delay_ms = delay.as_millis() as u64, "Request failed: {}", reason);
+        eprintln!(
+            "Network hiccup ({}), retrying in {:.1}s ({}/{})...",
+            reason,
+            delay.as_secs_f32(),
+            attempt,
+            max_attempts - 1
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Download a small binary resource such as a thumbnail (no retries)
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>> {
+    debug!("GET {}", url);
+    let response = backend()?
+        .send(HttpRequest::get(url).header("User-Agent", USER_AGENT))
         .await?;
 
-    if !response.status().is_success() {
-        return Err(YtChillError::Network(format!(
-            "HTTP {}: {}",
-            response.status(),
-            url
-        )));
+    if !response.status.is_success() {
+        return Err(YtChillError::Network(format!("HTTP {}: {}", response.status, url)));
     }
+    Ok(response.body)
+}
+
+/// Time a single request to youtube.com (no retries), for `yt-chill doctor`
+pub async fn check_reachable() -> Result<Duration> {
+    let http = backend()?;
+    let start = Instant::now();
+    let response = http
+        .send(HttpRequest::get("https://www.youtube.com/").header("User-Agent", USER_AGENT))
+        .await?;
 
-    Ok(response.text().await?)
+    if !response.status.is_success() {
+        return Err(YtChillError::Network(format!("HTTP {}", response.status)));
+    }
+    Ok(start.elapsed())
 }
 
-/// Extract ytInitialData JSON from YouTube HTML
-fn extract_yt_initial_data(html: &str) -> Result<serde_json::Value> {
-    let re = regex::Regex::new(r"var ytInitialData = (.+?);</script>")
-        .expect("Invalid regex");
+/// The parts of ytInitialData we read. Everything else in the page's data
+/// (several MB of menus, sidebars and tracking) is skipped by the parser
+/// without being built into values.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitialData {
+    contents: Option<InitialContents>,
+}
 
-    let captures = re.captures(html).ok_or_else(|| {
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitialContents {
+    two_column_search_results_renderer: Option<TwoColumnResults>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TwoColumnResults {
+    primary_contents: Option<PrimaryContents>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PrimaryContents {
+    section_list_renderer: Option<SectionList>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SectionList {
+    contents: Vec<Value>,
+}
+
+/// Result sections (item lists and the continuation) from the ytInitialData
+/// in a results page. Only the JSON is parsed, straight from the page, and
+/// only the sections are kept.
+fn extract_result_sections(html: &str) -> Result<Vec<Value>> {
+    const MARKER: &str = "var ytInitialData = ";
+    let start = html.find(MARKER).ok_or_else(|| {
+        warn!(html_bytes = html.len(), "No ytInitialData in the page");
         YtChillError::YouTubeParse("Failed to find ytInitialData".into())
     })?;
 
-    let json_str = captures.get(1).unwrap().as_str();
-    serde_json::from_str(json_str).map_err(|e| {
+    // Reads one JSON value and leaves the rest of the page alone
+    let json = &html[start + MARKER.len()..];
+    let data = InitialData::deserialize(&mut serde_json::Deserializer::from_str(json)).map_err(|e| {
+        warn!(json_bytes = json.len(), "ytInitialData isn't valid JSON: {}", e);
         YtChillError::YouTubeParse(format!("Failed to parse ytInitialData: {}", e))
-    })
+    })?;
+
+    let sections = data
+        .contents
+        .and_then(|c| c.two_column_search_results_renderer)
+        .and_then(|r| r.primary_contents)
+        .and_then(|p| p.section_list_renderer)
+        .map(|s| s.contents);
+    Ok(sections.unwrap_or_else(|| {
+        warn!("No result list in ytInitialData; YouTube may have changed its layout");
+        Vec::new()
+    }))
+}
+
+/// Items in the first result section
+fn first_section_items(sections: &[Value]) -> &[Value] {
+    sections
+        .first()
+        .and_then(|c| c.get("itemSectionRenderer"))
+        .and_then(|i| i.get("contents"))
+        .and_then(|c| c.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
 }
 
 /// Decode HTML entities in a string
@@ -60,185 +367,385 @@ fn decode_html_entities(s: &str) -> String {
     html_escape::decode_html_entities(s).to_string()
 }
 
-/// Parse video results from ytInitialData
-fn parse_search_results(data: &serde_json::Value, limit: usize) -> Vec<Video> {
-    let items = data
-        .get("contents")
-        .and_then(|c| c.get("twoColumnSearchResultsRenderer"))
-        .and_then(|r| r.get("primaryContents"))
-        .and_then(|p| p.get("sectionListRenderer"))
-        .and_then(|s| s.get("contents"))
-        .and_then(|c| c.get(0))
-        .and_then(|c| c.get("itemSectionRenderer"))
-        .and_then(|i| i.get("contents"))
-        .and_then(|c| c.as_array());
-
-    let Some(items) = items else {
-        return Vec::new();
+/// A video from one item of a result list; None for channels, shelves, ads...
+fn parse_video_item(item: &Value) -> Option<Video> {
+    let Some(v) = item.get("videoRenderer") else {
+        trace!("Skipping non-video item: {:?}", item.as_object().and_then(|o| o.keys().next()));
+        return None;
     };
 
-    items
-        .iter()
-        .filter_map(|item| {
-            let v = item.get("videoRenderer")?;
-
-            let id = v.get("videoId")?.as_str()?.to_string();
-            let title = v
-                .get("title")
-                .and_then(|t| t.get("runs"))
-                .and_then(|r| r.get(0))
-                .and_then(|r| r.get("text"))
-                .and_then(|t| t.as_str())
-                .map(decode_html_entities)
-                .unwrap_or_default();
+    let id = v.get("videoId")?.as_str()?.to_string();
+    let title = v
+        .get("title")
+        .and_then(|t| t.get("runs"))
+        .and_then(|r| r.get(0))
+        .and_then(|r| r.get("text"))
+        .and_then(|t| t.as_str())
+        .map(decode_html_entities)
+        .unwrap_or_default();
 
-            let author = v
-                .get("longBylineText")
-                .and_then(|t| t.get("runs"))
-                .and_then(|r| r.get(0))
-                .and_then(|r| r.get("text"))
-                .and_then(|t| t.as_str())
-                .unwrap_or("")
-                .to_string();
+    let author = v
+        .get("longBylineText")
+        .and_then(|t| t.get("runs"))
+        .and_then(|r| r.get(0))
+        .and_then(|r| r.get("text"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("")
+        .to_string();
 
-            let duration = v
-                .get("lengthText")
-                .and_then(|t| t.get("simpleText"))
-                .and_then(|t| t.as_str())
-                .unwrap_or("LIVE")
-                .to_string();
+    let duration = v
+        .get("lengthText")
+        .and_then(|t| t.get("simpleText"))
+        .and_then(|t| t.as_str())
+        .unwrap_or_else(|| {
+            trace!("No length for {}, treating it as live", id);
+            "LIVE"
+        })
+        .to_string();
 
-            let views = v
-                .get("viewCountText")
-                .and_then(|t| t.get("simpleText"))
-                .and_then(|t| t.as_str())
-                .unwrap_or("")
-                .to_string();
+    let views = v
+        .get("viewCountText")
+        .and_then(|t| t.get("simpleText"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("")
+        .to_string();
 
-            let published = v
-                .get("publishedTimeText")
-                .and_then(|t| t.get("simpleText"))
-                .and_then(|t| t.as_str())
-                .unwrap_or("")
-                .to_string();
+    let published = v
+        .get("publishedTimeText")
+        .and_then(|t| t.get("simpleText"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("")
+        .to_string();
 
-            let thumbnail = v
-                .get("thumbnail")
-                .and_then(|t| t.get("thumbnails"))
-                .and_then(|t| t.as_array())
-                .and_then(|t| t.last())
-                .and_then(|t| t.get("url"))
-                .and_then(|t| t.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            Some(Video {
-                id,
-                title,
-                author,
-                duration,
-                views,
-                published,
-                thumbnail,
-            })
-        })
-        .take(limit)
-        .collect()
+    let thumbnail = v
+        .get("thumbnail")
+        .and_then(|t| t.get("thumbnails"))
+        .and_then(|t| t.as_array())
+        .and_then(|t| t.last())
+        .and_then(|t| t.get("url"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    Some(Video {
+        id,
+        title,
+        author,
+        duration,
+        views,
+        published,
+        thumbnail,
+        ..Default::default()
+    }
+    .with_stats())
 }
 
 /// Search YouTube for videos (with caching)
 pub async fn search_videos(query: &str, limit: usize) -> Result<Vec<Video>> {
-    use crate::storage::cache::{get_cache_key, get_cached, set_cache};
+    search_with_filter(query, limit, "video").await
+}
 
-    // Generate cache key from query + limit
-    let cache_key = get_cache_key(&format!("video:{}:{}", query, limit));
+/// Search for videos longer than 20 minutes (podcasts, mixes, lectures)
+pub async fn search_long_videos(query: &str, limit: usize) -> Result<Vec<Video>> {
+    search_with_filter(query, limit, "long").await
+}
+
+async fn search_with_filter(query: &str, limit: usize, filter: &str) -> Result<Vec<Video>> {
+    use crate::storage::cache::{CacheKind, get_cache_key, get_cached, set_cache};
+
+    // Generate cache key from filter + query + limit
+    let cache_key = get_cache_key(&format!("{}:{}:{}", filter, query, limit));
 
     // Check cache first
     if let Some(cached) = get_cached::<Vec<Video>>(&cache_key).await {
+        debug!(key = %cache_key, "Cache hit");
         return Ok(cached);
     }
 
-    // Fetch from YouTube
-    let url = build_search_url(query, "video");
-    let html = fetch_youtube_html(&url).await?;
-    let data = extract_yt_initial_data(&html)?;
-    let results = parse_search_results(&data, limit);
-
-    if results.is_empty() {
-        return Err(YtChillError::NoResults);
+    // Fetch from YouTube, going past the first page if `limit` needs it
+    let mut results = Vec::new();
+    let mut found = std::pin::pin!(search_stream(None, query, filter).take(limit));
+    while let Some(video) = found.next().await {
+        match video {
+            Ok(video) => results.push(video),
+            // Keep what the first pages gave us
+            Err(e) if !results.is_empty() => {
+                warn!("Stopping at {} results: {}", results.len(), e);
+                break;
+            }
+            Err(e) => return Err(e),
+        }
     }
 
     // Cache results (ignore errors, caching is best-effort)
-    let _ = set_cache(&cache_key, &results).await;
+    let _ = set_cache(&cache_key, CacheKind::Search, &results).await;
 
     Ok(results)
 }
 
-/// Channel info for subscriptions
-#[derive(Debug, Clone)]
-pub struct ChannelInfo {
-    pub name: String,
-    pub handle: String,
+/// One page of search results and the token for the next, if there is one
+#[derive(Debug, Default)]
+struct SearchPage {
+    videos: Vec<Video>,
+    continuation: Option<String>,
 }
 
-/// Parse channel results from ytInitialData
-fn parse_channel_results(data: &serde_json::Value, limit: usize) -> Vec<ChannelInfo> {
-    let items = data
-        .get("contents")
-        .and_then(|c| c.get("twoColumnSearchResultsRenderer"))
-        .and_then(|r| r.get("primaryContents"))
-        .and_then(|p| p.get("sectionListRenderer"))
-        .and_then(|s| s.get("contents"))
-        .and_then(|c| c.get(0))
-        .and_then(|c| c.get("itemSectionRenderer"))
-        .and_then(|i| i.get("contents"))
-        .and_then(|c| c.as_array());
+/// Videos and the continuation token from a list of result sections (the
+/// first page's `sectionListRenderer` or a continuation's appended items)
+fn parse_sections(sections: &[Value]) -> SearchPage {
+    let mut page = SearchPage::default();
+    for section in sections {
+        if let Some(items) = section
+            .get("itemSectionRenderer")
+            .and_then(|i| i.get("contents"))
+            .and_then(|c| c.as_array())
+        {
+            page.videos.extend(items.iter().filter_map(parse_video_item));
+        } else if let Some(token) = section
+            .get("continuationItemRenderer")
+            .and_then(|c| c.get("continuationEndpoint"))
+            .and_then(|e| e.get("continuationCommand"))
+            .and_then(|c| c.get("token"))
+            .and_then(|t| t.as_str())
+        {
+            page.continuation = Some(token.to_string());
+        }
+    }
+    page
+}
+
+/// The parts of a continuation response we read
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ContinuationResponse {
+    on_response_received_commands: Vec<ResponseCommand>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ResponseCommand {
+    append_continuation_items_action: Option<AppendItems>,
+}
 
-    let Some(items) = items else {
-        return Vec::new();
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct AppendItems {
+    continuation_items: Vec<Value>,
+}
+
+/// Result sections appended by a continuation response
+fn continuation_sections(json: &str) -> Result<Vec<Value>> {
+    let response: ContinuationResponse = serde_json::from_str(json)
+        .map_err(|e| YtChillError::YouTubeParse(format!("Failed to parse continuation response: {}", e)))?;
+    Ok(response
+        .on_response_received_commands
+        .into_iter()
+        .filter_map(|c| c.append_continuation_items_action)
+        .flat_map(|a| a.continuation_items)
+        .collect())
+}
+
+/// API key and client version a page was served with, for asking for more results
+#[derive(Debug, Clone, PartialEq)]
+struct Innertube {
+    api_key: Option<String>,
+    client_version: String,
+}
+
+fn extract_innertube(html: &str) -> Innertube {
+    let field = |name: &str| {
+        regex::Regex::new(&format!(r#""{}":"([^"]+)""#, name))
+            .expect("Invalid regex")
+            .captures(html)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_string())
     };
+    Innertube {
+        api_key: field("INNERTUBE_API_KEY"),
+        client_version: field("INNERTUBE_CLIENT_VERSION").unwrap_or_else(|| FALLBACK_CLIENT_VERSION.to_string()),
+    }
+}
+
+/// Fetch the results after `token`
+async fn fetch_continuation(http: &dyn HttpBackend, innertube: &Innertube, token: &str) -> Result<Vec<Value>> {
+    let mut url = "https://www.youtube.com/youtubei/v1/search".to_string();
+    if let Some(ref key) = innertube.api_key {
+        url.push_str(&format!("?key={}", urlencoding::encode(key)));
+    }
+    let body = json!({
+        "context": {
+            "client": {
+                "clientName": "WEB",
+                "clientVersion": innertube.client_version,
+                "hl": "en",
+            }
+        },
+        "continuation": token,
+    });
+    continuation_sections(&fetch_youtube(http, &url, Some(&body)).await?)
+}
+
+/// Search YouTube for videos, yielding results as each page is parsed and
+/// following continuations until YouTube runs out (uncached). The next page
+/// is only fetched once the results so far have been taken.
+pub fn search_videos_stream(query: &str) -> impl Stream<Item = Result<Video>> + use<> {
+    search_stream(None, query, "video")
+}
+
+/// `http` defaults to [`backend`], looked up when the first page is fetched
+fn search_stream(http: Option<Arc<dyn HttpBackend>>, query: &str, filter: &str) -> impl Stream<Item = Result<Video>> + use<> {
+    let state = SearchState {
+        http,
+        url: build_search_url(query, filter),
+        innertube: None,
+        continuation: None,
+        pending: VecDeque::new(),
+        seen: HashSet::new(),
+        done: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        let item = state.next().await?;
+        Some((item, state))
+    })
+}
+
+/// Where a streamed search is up to
+struct SearchState {
+    http: Option<Arc<dyn HttpBackend>>,
+    url: String,
+    /// Set once the first page is in
+    innertube: Option<Innertube>,
+    continuation: Option<String>,
+    pending: VecDeque<Video>,
+    /// YouTube repeats videos across pages now and then
+    seen: HashSet<String>,
+    done: bool,
+}
+
+impl SearchState {
+    /// Fetch pages until there's a video to yield or nothing left
+    async fn next(&mut self) -> Option<Result<Video>> {
+        loop {
+            if let Some(video) = self.pending.pop_front() {
+                return Some(Ok(video));
+            }
+            if self.done {
+                return None;
+            }
+
+            let page = match self.fetch_page().await {
+                Ok(page) => page,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            let before = self.seen.len();
+            self.pending
+                .extend(page.videos.into_iter().filter(|v| self.seen.insert(v.id.clone())));
+            // A page of nothing new means YouTube is going in circles
+            self.continuation = page.continuation.filter(|_| self.seen.len() > before);
+            if self.continuation.is_none() {
+                self.done = true;
+                if self.seen.is_empty() {
+                    return Some(Err(YtChillError::NoResults));
+                }
+            }
+        }
+    }
 
-    items
+    async fn fetch_page(&mut self) -> Result<SearchPage> {
+        let http = match self.http {
+            Some(ref http) => Arc::clone(http),
+            None => Arc::clone(self.http.insert(backend()?)),
+        };
+        let Some(ref innertube) = self.innertube else {
+            let html = fetch_youtube_html(&*http, &self.url).await?;
+            self.innertube = Some(extract_innertube(&html));
+            return Ok(parse_sections(&extract_result_sections(&html)?));
+        };
+        match self.continuation {
+            Some(ref token) => {
+                debug!(results = self.seen.len(), "Fetching the next page of results");
+                Ok(parse_sections(&fetch_continuation(&*http, innertube, token).await?))
+            }
+            None => Ok(SearchPage::default()),
+        }
+    }
+}
+
+/// Text of a `{"simpleText": ...}` or `{"runs": [{"text": ...}, ...]}` field
+fn text_of(field: Option<&Value>) -> String {
+    let Some(field) = field else {
+        return String::new();
+    };
+    if let Some(text) = field.get("simpleText").and_then(|t| t.as_str()) {
+        return decode_html_entities(text);
+    }
+    field
+        .get("runs")
+        .and_then(|r| r.as_array())
+        .map(|runs| {
+            runs.iter()
+                .filter_map(|r| r.get("text").and_then(|t| t.as_str()))
+                .collect::<String>()
+        })
+        .map(|text| decode_html_entities(&text))
+        .unwrap_or_default()
+}
+
+/// Parse channel results from a results page's sections
+fn parse_channel_results(sections: &[Value], limit: usize) -> Vec<Channel> {
+    first_section_items(sections)
         .iter()
         .filter_map(|item| {
             let c = item.get("channelRenderer")?;
+            let id = c.get("channelId")?.as_str()?.to_string();
+            let name = text_of(c.get("title"));
+            if name.is_empty() {
+                return None;
+            }
 
-            let name = c
-                .get("title")
-                .and_then(|t| t.get("simpleText"))
-                .and_then(|t| t.as_str())
-                .map(decode_html_entities)
-                .unwrap_or_default();
+            // Channels with a handle show it where the subscriber count used
+            // to be, and the subscriber count where the video count was
+            let subscriber_text = text_of(c.get("subscriberCountText"));
+            let video_text = text_of(c.get("videoCountText"));
+            let (shown_handle, subscribers, video_count) = if subscriber_text.starts_with('@') {
+                (Some(subscriber_text), video_text, String::new())
+            } else {
+                (None, subscriber_text, video_text)
+            };
 
-            // Try to get handle, fall back to channel ID
             let handle = c
-                .get("subscriberCountText")
-                .and_then(|_| c.get("channelId"))
-                .and_then(|id| id.as_str())
-                .map(|id| format!("@{}", id))
-                .or_else(|| {
-                    c.get("channelId")
-                        .and_then(|id| id.as_str())
-                        .map(|s| s.to_string())
-                })
-                .unwrap_or_default();
+                .get("navigationEndpoint")
+                .and_then(|n| n.get("browseEndpoint"))
+                .and_then(|b| b.get("canonicalBaseUrl"))
+                .and_then(|u| u.as_str())
+                .map(|url| url.strip_prefix("/").filter(|h| h.starts_with('@')).unwrap_or(url).to_string())
+                .or(shown_handle)
+                .unwrap_or_else(|| format!("/channel/{}", id));
 
-            if name.is_empty() || handle.is_empty() {
-                return None;
-            }
+            let thumbnail = c
+                .get("thumbnail")
+                .and_then(|t| t.get("thumbnails"))
+                .and_then(|t| t.as_array())
+                .and_then(|t| t.last())
+                .and_then(|t| t.get("url"))
+                .and_then(|t| t.as_str())
+                .map(|url| if url.starts_with("//") { format!("https:{}", url) } else { url.to_string() })
+                .unwrap_or_default();
 
-            Some(ChannelInfo { name, handle })
+            Some(Channel { id, name, handle, thumbnail, subscribers, video_count })
         })
         .take(limit)
         .collect()
 }
 
 /// Search for channels
-pub async fn search_channels(query: &str, limit: usize) -> Result<Vec<ChannelInfo>> {
+pub async fn search_channels(query: &str, limit: usize) -> Result<Vec<Channel>> {
     let url = build_search_url(query, "channel");
-    let html = fetch_youtube_html(&url).await?;
-    let data = extract_yt_initial_data(&html)?;
-    let results = parse_channel_results(&data, limit);
+    let html = fetch_youtube_html(&*backend()?, &url).await?;
+    let results = parse_channel_results(&extract_result_sections(&html)?, limit);
 
     if results.is_empty() {
         return Err(YtChillError::NoResults);
@@ -247,36 +754,179 @@ pub async fn search_channels(query: &str, limit: usize) -> Result<Vec<ChannelInf
     Ok(results)
 }
 
-/// Fetch recent videos from a channel
-pub async fn fetch_channel_videos(channel_handle: &str, limit: usize) -> Result<Vec<Video>> {
-    use crate::storage::cache::{get_cache_key, get_cached, set_cache};
+/// Look up the UC... channel ID for a handle like "@lofigirl" or "/c/name"
+pub async fn resolve_channel_id(handle: &str) -> Result<String> {
+    let path = if handle.starts_with('/') {
+        handle.to_string()
+    } else if handle.starts_with('@') {
+        format!("/{}", handle)
+    } else {
+        format!("/@{}", handle)
+    };
+
+    let html = fetch_youtube_html(&*backend()?, &format!("https://www.youtube.com{}", path)).await?;
+    let re = regex::Regex::new(r#""(?:externalId|channelId)":"(UC[\w-]{22})""#).expect("Invalid regex");
+
+    re.captures(&html)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string())
+        .ok_or_else(|| YtChillError::YouTubeParse(format!("No channel ID found for {}", handle)))
+}
+
+/// The channel that uploaded a video, from its watch page
+pub async fn fetch_video_channel(video_id: &str) -> Result<Channel> {
+    let url = format!("https://www.youtube.com/watch?v={}", video_id);
+    let html = fetch_youtube_html(&*backend()?, &url).await?;
+    parse_watch_channel(&html)
+        .ok_or_else(|| YtChillError::YouTubeParse(format!("No channel found for video {}", video_id)))
+}
 
-    // Generate cache key
-    let cache_key = get_cache_key(&format!("channel:{}:{}", channel_handle, limit));
+/// Owner channel from a watch page's player response: ID, name and
+/// @handle (or a /channel/ handle when it has none)
+fn parse_watch_channel(html: &str) -> Option<Channel> {
+    let field = |pattern: &str| {
+        regex::Regex::new(pattern)
+            .expect("Invalid regex")
+            .captures(html)
+            .and_then(|c| c.get(1))
+            .map(|m| m.as_str().to_string())
+    };
 
-    // Check cache first
-    if let Some(cached) = get_cached::<Vec<Video>>(&cache_key).await {
-        return Ok(cached);
-    }
+    let id = field(r#""externalChannelId":"(UC[\w-]{22})""#).or_else(|| field(r#""channelId":"(UC[\w-]{22})""#))?;
+    // JSON-escaped, so unescape it as a JSON string
+    let name = field(r#""ownerChannelName":"((?:[^"\\]|\\.)*)""#)
+        .and_then(|raw| serde_json::from_str::<String>(&format!("\"{}\"", raw)).ok())
+        .unwrap_or_default();
+    let handle = field(r#""ownerProfileUrl":"https?://www\.youtube\.com/(@[^"/]+)""#)
+        .unwrap_or_else(|| format!("/channel/{}", id));
 
-    // Build channel URL - search for channel videos
-    let search_query = format!("{} ", channel_handle);
-    let url = build_search_url(&search_query, "video");
-    let html = fetch_youtube_html(&url).await?;
-    let data = extract_yt_initial_data(&html)?;
-    let results = parse_search_results(&data, limit);
+    Some(Channel { id, name, handle, ..Default::default() })
+}
 
-    // Cache results
-    if !results.is_empty() {
-        let _ = set_cache(&cache_key, &results).await;
-    }
+/// A channel's RSS feed of its latest uploads
+pub fn channel_feed_url(channel_id: &str) -> String {
+    format!("https://www.youtube.com/feeds/videos.xml?channel_id={}", channel_id)
+}
 
-    Ok(results)
+/// A channel's newest uploads, newest first, from its RSS feed (uncached;
+/// the feed keeps its own per-channel cache, see [`crate::core::feed`]).
+/// The feed has no lengths, so `duration` is empty.
+pub async fn fetch_channel_videos(channel_id: &str, limit: usize) -> Result<Vec<Video>> {
+    let xml = fetch_youtube_html(&*backend()?, &channel_feed_url(channel_id)).await?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+    Ok(parse_channel_feed(&xml, channel_id, limit, now))
+}
+
+/// Videos in a channel's RSS feed that the channel uploaded itself, newest first
+fn parse_channel_feed(xml: &str, channel_id: &str, limit: usize, now: i64) -> Vec<Video> {
+    let entry = regex::Regex::new(r"(?s)<entry>(.*?)</entry>").expect("Invalid regex");
+    let tag = |body: &str, pattern: &str| {
+        regex::Regex::new(pattern)
+            .expect("Invalid regex")
+            .captures(body)
+            .and_then(|c| c.get(1))
+            .map(|m| decode_html_entities(m.as_str().trim()))
+    };
+
+    let mut videos: Vec<(i64, Video)> = entry
+        .captures_iter(xml)
+        .filter_map(|c| {
+            let body = c.get(1)?.as_str();
+            if tag(body, r"<yt:channelId>([^<]*)</yt:channelId>")? != channel_id {
+                return None;
+            }
+            let published = tag(body, r"<published>([^<]*)</published>")
+                .and_then(|p| chrono::DateTime::parse_from_rfc3339(&p).ok())
+                .map_or(0, |p| p.timestamp());
+            let views = tag(body, r#"<media:statistics views="(\d+)""#).unwrap_or_default();
+            let video = Video {
+                id: tag(body, r"<yt:videoId>([^<]*)</yt:videoId>")?,
+                title: tag(body, r"<title>([^<]*)</title>").unwrap_or_default(),
+                author: tag(body, r"(?s)<author>\s*<name>([^<]*)</name>").unwrap_or_default(),
+                views: if views.is_empty() { views } else { format!("{} views", views) },
+                published: if published > 0 {
+                    format_age(now.saturating_sub(published).max(0) as u64)
+                } else {
+                    String::new()
+                },
+                thumbnail: tag(body, r#"<media:thumbnail url="([^"]*)""#).unwrap_or_default(),
+                ..Default::default()
+            }
+            .with_stats();
+            Some((published, video))
+        })
+        .collect();
+
+    videos.sort_by_key(|(published, _)| std::cmp::Reverse(*published));
+    videos.into_iter().map(|(_, video)| video).take(limit).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::http::BoxFuture;
+
+    /// Canned responses for URLs containing each key; 404 for anything else
+    struct Recorded(Vec<(&'static str, String)>);
+
+    impl HttpBackend for Recorded {
+        fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>> {
+            let found = self.0.iter().find(|(key, _)| request.url.contains(key));
+            let response = HttpResponse {
+                status: if found.is_some() { reqwest::StatusCode::OK } else { reqwest::StatusCode::NOT_FOUND },
+                url: request.url.clone(),
+                headers: Vec::new(),
+                body: found.map(|(_, body)| body.clone().into_bytes()).unwrap_or_default(),
+            };
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_follows_continuations() {
+        let video = |id: &str| json!({"videoRenderer": {"videoId": id, "title": {"runs": [{"text": id}]}}});
+        let first = json!({"contents": {"twoColumnSearchResultsRenderer": {"primaryContents": {"sectionListRenderer": {"contents": [
+            {"itemSectionRenderer": {"contents": [video("a"), video("b")]}},
+            {"continuationItemRenderer": {"continuationEndpoint": {"continuationCommand": {"token": "NEXT"}}}},
+        ]}}}}});
+        let next = json!({"onResponseReceivedCommands": [{"appendContinuationItemsAction": {"continuationItems": [
+            {"itemSectionRenderer": {"contents": [video("b"), video("c")]}},
+        ]}}]});
+        let http = Recorded(vec![
+            ("/results?", format!(r#"<script>ytcfg.set({{"INNERTUBE_API_KEY":"k"}});</script><script>var ytInitialData = {};</script>"#, first)),
+            ("/youtubei/v1/search?key=k", next.to_string()),
+        ]);
+
+        let videos: Vec<Video> = search_stream(Some(Arc::new(http)), "lofi", "video")
+            .map(|v| v.unwrap())
+            .collect()
+            .await;
+        assert_eq!(videos.iter().map(|v| v.id.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        // Nothing back from YouTube at all
+        let empty = search_stream(Some(Arc::new(Recorded(Vec::new()))), "lofi", "video");
+        let results: Vec<_> = empty.collect().await;
+        assert!(matches!(results.as_slice(), [Err(YtChillError::Network(_))]));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rate_limit_page() {
+        struct Sorry;
+        impl HttpBackend for Sorry {
+            fn send(&self, _: HttpRequest) -> BoxFuture<'_, Result<HttpResponse>> {
+                Box::pin(async {
+                    Ok(HttpResponse {
+                        status: reqwest::StatusCode::OK,
+                        url: "https://www.google.com/sorry/index".into(),
+                        headers: Vec::new(),
+                        body: Vec::new(),
+                    })
+                })
+            }
+        }
+        let result = fetch_youtube_html(&Sorry, "https://www.youtube.com/results?search_query=x").await;
+        assert!(matches!(result, Err(YtChillError::RateLimited(_))));
+    }
 
     #[test]
     fn test_build_search_url() {
@@ -284,5 +934,188 @@ mod tests {
         assert!(url.contains("search_query=lofi%20beats"));
         assert!(url.contains("sp=EgIQAQ"));
     }
+
+    #[test]
+    fn test_parse_channel_feed() {
+        let entry = |id: &str, channel: &str, published: &str| {
+            format!(
+                "<entry><yt:videoId>{id}</yt:videoId><yt:channelId>{channel}</yt:channelId>\
+                 <title>Rock &amp; {id}</title><author><name>Lofi Girl</name></author>\
+                 <published>{published}</published><media:group>\
+                 <media:thumbnail url=\"https://i1.ytimg.com/vi/{id}/hqdefault.jpg\" width=\"480\"/>\
+                 <media:community><media:statistics views=\"1234\"/></media:community></media:group></entry>"
+            )
+        };
+        let xml = format!(
+            "<feed>{}{}{}</feed>",
+            entry("older", "UCme", "2026-01-01T00:00:00+00:00"),
+            entry("newer", "UCme", "2026-01-03T00:00:00+00:00"),
+            entry("other", "UCsomeoneelse", "2026-01-04T00:00:00+00:00"),
+        );
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-05T00:00:00+00:00").unwrap().timestamp();
+
+        let videos = parse_channel_feed(&xml, "UCme", 10, now);
+        let ids: Vec<&str> = videos.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["newer", "older"]);
+        assert_eq!(videos[0].title, "Rock & newer");
+        assert_eq!(videos[0].author, "Lofi Girl");
+        assert_eq!(videos[0].published, "2 days ago");
+        assert_eq!(videos[0].view_count, Some(1234));
+        assert_eq!(videos[0].thumbnail, "https://i1.ytimg.com/vi/newer/hqdefault.jpg");
+        assert_eq!(parse_channel_feed(&xml, "UCme", 1, now).len(), 1);
+    }
+
+    #[test]
+    fn test_channel_uploads_url() {
+        assert_eq!(channel_uploads_url("@LofiGirl"), "https://www.youtube.com/@LofiGirl/videos");
+        assert_eq!(channel_uploads_url("LofiGirl"), "https://www.youtube.com/@LofiGirl/videos");
+        assert_eq!(channel_uploads_url("/channel/UC123"), "https://www.youtube.com/channel/UC123/videos");
+        assert_eq!(channel_uploads_url("https://www.youtube.com/@LofiGirl/"), "https://www.youtube.com/@LofiGirl/videos");
+        assert_eq!(channel_uploads_url("https://www.youtube.com/@LofiGirl/videos"), "https://www.youtube.com/@LofiGirl/videos");
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(1, 0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(2, 0), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(3, 0), Duration::from_millis(2000));
+        assert_eq!(backoff_delay(30, 0), Duration::from_millis(BACKOFF_MAX_MS));
+        // Jitter adds at most half the base delay
+        assert!(backoff_delay(2, u64::MAX) <= Duration::from_millis(1500));
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_is_consent_page() {
+        assert!(is_consent_page("https://consent.youtube.com/m?continue=x", ""));
+        assert!(is_consent_page(
+            "https://www.youtube.com/results",
+            r#"<form action="https://consent.youtube.com/save">"#
+        ));
+        assert!(!is_consent_page(
+            "https://www.youtube.com/results",
+            "var ytInitialData = {}; consent.youtube.com/save"
+        ));
+    }
+
+    #[test]
+    fn test_parse_pages() {
+        let video = |id: &str| json!({"videoRenderer": {"videoId": id, "title": {"runs": [{"text": "Lofi &amp; chill"}]}}});
+        let continuation = json!({"continuationItemRenderer": {"continuationEndpoint": {"continuationCommand": {"token": "NEXT"}}}});
+
+        let first = json!({"contents": {"twoColumnSearchResultsRenderer": {"primaryContents": {"sectionListRenderer": {
+            "contents": [{"itemSectionRenderer": {"contents": [video("a"), {"shelfRenderer": {}}, video("b")]}}, continuation]
+        }}}}});
+        let html = format!("<script>var ytInitialData = {};</script><script>var more = {{}};</script>", first);
+        let page = parse_sections(&extract_result_sections(&html).unwrap());
+        assert_eq!(page.videos.iter().map(|v| v.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(page.videos[0].title, "Lofi & chill");
+        assert_eq!(page.continuation.as_deref(), Some("NEXT"));
+
+        let next = json!({"onResponseReceivedCommands": [{"appendContinuationItemsAction": {
+            "continuationItems": [{"itemSectionRenderer": {"contents": [video("c")]}}]
+        }}]});
+        let page = parse_sections(&continuation_sections(&next.to_string()).unwrap());
+        assert_eq!(page.videos[0].id, "c");
+        assert_eq!(page.continuation, None);
+    }
+
+    #[test]
+    fn test_extract_result_sections() {
+        // Unrelated parts of the data are skipped, whatever their shape
+        let html = r#"var ytInitialData = {"responseContext":{"serviceTrackingParams":[{"params":[1,2,3]}]},"contents":{"twoColumnSearchResultsRenderer":{"primaryContents":{"sectionListRenderer":{"contents":[{"itemSectionRenderer":{}}]}}}},"topbar":{"x":null}};</script>"#;
+        assert_eq!(extract_result_sections(html).unwrap().len(), 1);
+
+        let moved = r#"var ytInitialData = {"contents":{"singleColumnBrowseResultsRenderer":{}}};"#;
+        assert!(extract_result_sections(moved).unwrap().is_empty());
+        assert!(matches!(extract_result_sections("<html></html>"), Err(YtChillError::YouTubeParse(_))));
+        assert!(matches!(extract_result_sections("var ytInitialData = {oops"), Err(YtChillError::YouTubeParse(_))));
+    }
+
+    #[test]
+    fn test_parse_video_item_stats() {
+        let item = json!({"videoRenderer": {
+            "videoId": "abc",
+            "lengthText": {"simpleText": "1:02:03"},
+            "viewCountText": {"simpleText": "1,234,567 views"},
+        }});
+        let video = parse_video_item(&item).unwrap();
+        assert_eq!(video.duration, "1:02:03");
+        assert_eq!(video.duration_secs, Some(3723));
+        assert_eq!(video.view_count, Some(1_234_567));
+
+        let live = parse_video_item(&json!({"videoRenderer": {"videoId": "x", "viewCountText": {"simpleText": "5.3K watching"}}})).unwrap();
+        assert_eq!(live.duration_secs, None);
+        assert_eq!(live.view_count, Some(5300));
+
+        assert_eq!(crate::types::parse_view_count("1.2M views"), Some(1_200_000));
+        assert_eq!(crate::types::parse_view_count("No views"), Some(0));
+        assert_eq!(crate::types::parse_view_count(""), None);
+    }
+
+    #[test]
+    fn test_parse_channel_results() {
+        let sections = |items: Vec<Value>| vec![json!({"itemSectionRenderer": {"contents": items}})];
+        let data = sections(vec![
+            json!({"channelRenderer": {
+                "channelId": "UCSJ4gkVC6NrvII8umztf0Ow",
+                "title": {"simpleText": "Lofi Girl"},
+                "navigationEndpoint": {"browseEndpoint": {"canonicalBaseUrl": "/@LofiGirl"}},
+                "thumbnail": {"thumbnails": [{"url": "//yt3.ggpht.com/small"}, {"url": "//yt3.ggpht.com/big"}]},
+                "subscriberCountText": {"simpleText": "@LofiGirl"},
+                "videoCountText": {"simpleText": "14.6M subscribers"},
+            }}),
+            json!({"channelRenderer": {
+                "channelId": "UCxxxxxxxxxxxxxxxxxxxxxx",
+                "title": {"simpleText": "Old &amp; Handleless"},
+                "subscriberCountText": {"simpleText": "1.2K subscribers"},
+                "videoCountText": {"runs": [{"text": "42"}, {"text": " videos"}]},
+            }}),
+        ]);
+
+        let channels = parse_channel_results(&data, 10);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].handle, "@LofiGirl");
+        assert_eq!(channels[0].subscribers, "14.6M subscribers");
+        assert_eq!(channels[0].thumbnail, "https://yt3.ggpht.com/big");
+        assert_eq!(channels[1].name, "Old & Handleless");
+        assert_eq!(channels[1].handle, "/channel/UCxxxxxxxxxxxxxxxxxxxxxx");
+        assert_eq!(channels[1].subscribers, "1.2K subscribers");
+        assert_eq!(channels[1].video_count, "42 videos");
+    }
+
+    #[test]
+    fn test_parse_watch_channel() {
+        let html = r#"var ytInitialPlayerResponse = {"videoDetails":{"videoId":"abc","channelId":"UCSJ4gkVC6NrvII8umztf0Ow","author":"Lofi Girl"},
+            "microformat":{"playerMicroformatRenderer":{"ownerProfileUrl":"http://www.youtube.com/@LofiGirl","externalChannelId":"UCSJ4gkVC6NrvII8umztf0Ow","ownerChannelName":"Lofi \u0026 Chill \"Girl\""}}};"#;
+        let channel = parse_watch_channel(html).unwrap();
+        assert_eq!(channel.id, "UCSJ4gkVC6NrvII8umztf0Ow");
+        assert_eq!(channel.name, "Lofi & Chill \"Girl\"");
+        assert_eq!(channel.handle, "@LofiGirl");
+
+        let bare = r#"{"channelId":"UCSJ4gkVC6NrvII8umztf0Ow"}"#;
+        assert_eq!(parse_watch_channel(bare).unwrap().handle, "/channel/UCSJ4gkVC6NrvII8umztf0Ow");
+        assert!(parse_watch_channel("<html></html>").is_none());
+    }
+
+    #[test]
+    fn test_extract_innertube() {
+        let html = r#"ytcfg.set({"INNERTUBE_API_KEY":"AIzaKey","INNERTUBE_CLIENT_VERSION":"2.20250101.01.00"});"#;
+        assert_eq!(
+            extract_innertube(html),
+            Innertube { api_key: Some("AIzaKey".into()), client_version: "2.20250101.01.00".into() }
+        );
+        assert_eq!(extract_innertube("").client_version, FALLBACK_CLIENT_VERSION);
+    }
+
+    #[test]
+    fn test_is_valid_proxy() {
+        assert!(is_valid_proxy("http://proxy.corp:3128"));
+        assert!(is_valid_proxy("socks5h://127.0.0.1:9050"));
+        assert!(!is_valid_proxy("127.0.0.1:9050"));
+        assert!(!is_valid_proxy("ftp://x"));
+    }
 }
 
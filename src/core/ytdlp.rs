@@ -0,0 +1,113 @@
+//! Finding and running yt-dlp, and noticing when it's too old to work
+
+use crate::error::{Result, YtChillError};
+use crate::utils::paths::{expand_tilde, find_executable};
+use colored::Colorize;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+/// Oldest yt-dlp release known to still get past YouTube's player changes.
+/// Most "this video won't play" reports come down to a yt-dlp older than this.
+pub const MIN_VERSION: &str = "2025.03.31";
+
+static PATH: OnceLock<Option<String>> = OnceLock::new();
+
+/// Set once the version has been looked at, so the warning shows up once a run
+static VERSION_CHECKED: OnceCell<()> = OnceCell::const_new();
+
+/// Use yt-dlp from this path instead of PATH (first call wins)
+pub fn set_path(path: Option<String>) {
+    let _ = PATH.set(path.map(|p| expand_tilde(&p)));
+}
+
+/// The configured yt-dlp path, if not the one in PATH
+pub fn custom_path() -> Option<&'static str> {
+    PATH.get().and_then(|p| p.as_deref())
+}
+
+/// What to run: the configured path, or `yt-dlp` from PATH
+pub fn program() -> &'static str {
+    custom_path().unwrap_or("yt-dlp")
+}
+
+/// A yt-dlp command that's killed if we stop waiting for it
+pub fn command() -> Command {
+    let mut command = Command::new(program());
+    command.kill_on_drop(true);
+    command
+}
+
+/// Make sure yt-dlp is there, warning (once) if it's older than [`MIN_VERSION`]
+pub async fn ensure_available() -> Result<()> {
+    if find_executable(program()).is_none() {
+        return Err(YtChillError::MissingDependency("yt-dlp".into()));
+    }
+    VERSION_CHECKED
+        .get_or_init(|| async {
+            if let Some(version) = version().await
+                && is_outdated(&version)
+            {
+                eprintln!(
+                    "{} yt-dlp {} is older than {}, and old versions often can't play YouTube videos. {}",
+                    "Warning:".yellow(),
+                    version,
+                    MIN_VERSION,
+                    "Update it with `yt-chill deps update`.".dimmed()
+                );
+            }
+        })
+        .await;
+    Ok(())
+}
+
+/// `yt-dlp --version`, e.g. "2025.06.30"
+pub async fn version() -> Option<String> {
+    let output = Command::new(program()).arg("--version").output().await.ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    debug!("yt-dlp version {:?}", version);
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+/// Whether a yt-dlp version (a date like "2024.12.13", nightlies add a
+/// build number) is older than [`MIN_VERSION`]. Unknown formats aren't.
+pub fn is_outdated(version: &str) -> bool {
+    let parse = |v: &str| -> Option<Vec<u32>> { v.split('.').map(|n| n.parse().ok()).collect() };
+    match (parse(version.trim()), parse(MIN_VERSION)) {
+        (Some(version), Some(min)) => version < min,
+        _ => false,
+    }
+}
+
+/// Run `yt-dlp -U`, showing its output. Installs from pip or a package
+/// manager refuse and say how to update instead.
+pub async fn update() -> Result<bool> {
+    if find_executable(program()).is_none() {
+        return Err(YtChillError::MissingDependency("yt-dlp".into()));
+    }
+    let status = Command::new(program())
+        .arg("-U")
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| YtChillError::Spawn(format!("Failed to start yt-dlp: {}", e)))?;
+    Ok(status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_outdated() {
+        assert!(is_outdated("2023.03.04"));
+        assert!(is_outdated("2025.03.30"));
+        assert!(!is_outdated(MIN_VERSION));
+        assert!(!is_outdated("2025.06.30"));
+        // Nightly builds carry a build number
+        assert!(!is_outdated("2025.07.01.232917"));
+        assert!(!is_outdated("unknown"));
+    }
+}
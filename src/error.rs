@@ -3,13 +3,18 @@
 use thiserror::Error;
 
 /// Error codes matching the TypeScript implementation
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorCode {
     // Network errors
     NetworkError,
     YouTubeParseError,
 
+    // YouTube refusing us
+    RateLimited,
+    ConsentRequired,
+    GeoBlocked,
+    AgeRestricted,
+
     // Dependency errors
     MissingDependency,
 
@@ -21,11 +26,13 @@ pub enum ErrorCode {
     // System errors
     FileError,
     SpawnError,
+
+    // Files we couldn't make sense of
+    ParseError,
 }
 
 /// Main error type for yt-chill
 #[derive(Error, Debug)]
-#[allow(dead_code)]
 pub enum YtChillError {
     #[error("Network error: {0}")]
     Network(String),
@@ -33,6 +40,18 @@ pub enum YtChillError {
     #[error("Failed to parse YouTube response: {0}")]
     YouTubeParse(String),
 
+    #[error("YouTube is rate limiting requests: {0}")]
+    RateLimited(String),
+
+    #[error("YouTube wants cookie consent before showing results: {0}")]
+    ConsentRequired(String),
+
+    #[error("This video isn't available in your country: {0}")]
+    GeoBlocked(String),
+
+    #[error("This video is age-restricted: {0}")]
+    AgeRestricted(String),
+
     #[error("Missing dependency: {0}. Please install it.")]
     MissingDependency(String),
 
@@ -51,29 +70,154 @@ pub enum YtChillError {
     #[error("Failed to spawn process: {0}")]
     Spawn(String),
 
+    #[error("Not enough disk space: {0}")]
+    DiskFull(String),
+
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Database error: {0}")]
+    Database(String),
+
+    #[error("Couldn't parse {0}")]
+    Parse(String),
 }
 
 impl YtChillError {
-    #[allow(dead_code)]
     pub fn code(&self) -> ErrorCode {
         match self {
             Self::Network(_) => ErrorCode::NetworkError,
             Self::YouTubeParse(_) => ErrorCode::YouTubeParseError,
+            Self::RateLimited(_) => ErrorCode::RateLimited,
+            Self::ConsentRequired(_) => ErrorCode::ConsentRequired,
+            Self::GeoBlocked(_) => ErrorCode::GeoBlocked,
+            Self::AgeRestricted(_) => ErrorCode::AgeRestricted,
             Self::MissingDependency(_) => ErrorCode::MissingDependency,
             Self::NoResults => ErrorCode::NoResults,
             Self::NoSelection => ErrorCode::NoSelection,
             Self::InvalidConfig(_) => ErrorCode::InvalidConfig,
             Self::File(_) => ErrorCode::FileError,
             Self::Spawn(_) => ErrorCode::SpawnError,
+            Self::DiskFull(_) => ErrorCode::FileError,
             Self::Http(_) => ErrorCode::NetworkError,
-            Self::Json(_) => ErrorCode::YouTubeParseError,
+            // YouTube's JSON is mapped to YouTubeParse where it's parsed, so
+            // these come from our own files and data
+            Self::Json(_) => ErrorCode::FileError,
+            Self::Database(_) => ErrorCode::FileError,
+            Self::Parse(_) => ErrorCode::ParseError,
+        }
+    }
+}
+
+impl ErrorCode {
+    /// Process exit code, so scripts can tell failures apart (see README).
+    /// 2 is taken by invalid arguments, 130/143 by Ctrl+C/SIGTERM.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::NetworkError => 3,
+            Self::RateLimited => 4,
+            Self::ConsentRequired => 5,
+            Self::YouTubeParseError => 6,
+            Self::GeoBlocked => 7,
+            Self::AgeRestricted => 8,
+            Self::MissingDependency => 9,
+            Self::NoResults | Self::NoSelection => 10,
+            Self::InvalidConfig => 11,
+            Self::FileError => 12,
+            Self::SpawnError => 13,
+            Self::ParseError => 14,
+        }
+    }
+
+    /// Whether trying again later could help
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::NetworkError | Self::RateLimited)
+    }
+
+    /// What to do about it, when there's something better than retrying
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            Self::NetworkError => Some("Check your connection or `proxy`; `yt-chill doctor` tests reaching YouTube"),
+            Self::RateLimited => Some(
+                "Wait a while before trying again, lower `requests_per_minute` under [network], or set `cookies_from_browser` to use your login",
+            ),
+            Self::ConsentRequired => Some("Set `cookies_from_browser` to reuse a browser where you've accepted YouTube's cookie banner"),
+            Self::YouTubeParseError => Some(
+                "YouTube may have changed its pages; run with `-vv` to see what came back, and check for a yt-chill update",
+            ),
+            Self::GeoBlocked => Some("Try a `proxy` in a country where the video is available"),
+            Self::AgeRestricted => Some("Set `cookies_from_browser` (or --cookies-from-browser) to a browser signed in to an adult account"),
+            Self::MissingDependency => Some("`yt-chill doctor` lists what's missing and how to install it"),
+            Self::InvalidConfig => Some("`yt-chill -e` opens the config; `yt-chill doctor` checks it"),
+            _ => None,
         }
     }
 }
 
+impl YtChillError {
+    /// Classify a yt-dlp failure from its stderr, so refusals like age
+    /// restrictions get their own error instead of a generic one
+    pub fn from_ytdlp(stderr: &str) -> Self {
+        let message = stderr
+            .lines()
+            .rev()
+            .find(|l| l.contains("ERROR"))
+            .or_else(|| stderr.lines().last())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let lower = stderr.to_lowercase();
+        if lower.contains("confirm your age") || lower.contains("age-restricted") || lower.contains("inappropriate for some users") {
+            Self::AgeRestricted(message)
+        } else if lower.contains("available in your country") || lower.contains("geo restrict") || lower.contains("geo-restrict") {
+            Self::GeoBlocked(message)
+        } else if lower.contains("http error 429") || lower.contains("too many requests") || lower.contains("not a bot") {
+            Self::RateLimited(message)
+        } else {
+            Self::Spawn(format!("yt-dlp failed: {}", message))
+        }
+    }
+
+    /// Whether trying again later could help
+    pub fn is_retryable(&self) -> bool {
+        self.code().is_retryable()
+    }
+
+    /// Process exit code for this error
+    pub fn exit_code(&self) -> u8 {
+        self.code().exit_code()
+    }
+
+    /// What the user can do about it, if anything specific
+    pub fn hint(&self) -> Option<&'static str> {
+        self.code().hint()
+    }
+}
+
 pub type Result<T> = std::result::Result<T, YtChillError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_ytdlp() {
+        let age = "[youtube] abc: Downloading webpage\nERROR: [youtube] abc: Sign in to confirm your age. This video may be inappropriate for some users.";
+        assert!(matches!(YtChillError::from_ytdlp(age), YtChillError::AgeRestricted(m) if m.starts_with("ERROR")));
+        let geo = "ERROR: [youtube] abc: The uploader has not made this video available in your country";
+        assert_eq!(YtChillError::from_ytdlp(geo).code(), ErrorCode::GeoBlocked);
+        let bot = "ERROR: [youtube] abc: Sign in to confirm you're not a bot";
+        assert!(YtChillError::from_ytdlp(bot).is_retryable());
+        assert_eq!(YtChillError::from_ytdlp("ERROR: Private video").code(), ErrorCode::SpawnError);
+    }
+
+    #[test]
+    fn test_local_json_is_a_file_error() {
+        let local: YtChillError = serde_json::from_str::<u32>("{").unwrap_err().into();
+        assert_eq!(local.code(), ErrorCode::FileError);
+        assert_eq!(local.hint(), None);
+    }
+}
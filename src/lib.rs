@@ -2,9 +2,12 @@
 //!
 //! Core functionality for the yt-chill CLI.
 
+pub mod client;
 pub mod core;
 pub mod error;
 pub mod storage;
 pub mod types;
 pub mod ui;
 pub mod utils;
+
+pub use client::{YtChill, YtChillBuilder};
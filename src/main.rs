@@ -2,28 +2,44 @@
 //!
 //! A distraction-free CLI for searching, streaming, and downloading audio from YouTube.
 
-mod core;
-mod error;
-mod storage;
-mod types;
-mod ui;
-mod utils;
+mod commands;
+
+// The library's modules, so they're compiled once and `crate::core::...` paths
+// work the same here as in the library
+use yt_chill::{core, error, storage, types, ui, utils};
 
 use clap::Parser;
 use colored::Colorize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use crate::commands::Command;
+use crate::core::blocklist::Blocklist;
+use crate::core::{downloader, events, feed, player, thumbnail, timebox, youtube, ytdlp};
+use crate::error::YtChillError;
+use crate::storage::{cache, config, history, history::History, searches::SearchHistory};
+use crate::types::{AppState, AudioFormat, Channel, Config, ConfigIssue, HistorySort, DownloadJob, DownloadOptions, LibraryItem, MenuItem, PlayOptions, PlayedItem, RandomSource, ResultSort, SelectAction, Session, SessionList, Video};
+use crate::ui::format::{self, format_channel_label, format_history_label, format_search_label, format_video_label};
+use crate::ui::selector::create_selector;
+use crate::ui::theme::{self, Icon, with_icon};
+use crate::utils::paths::{ensure_app_dirs, expand_tilde, get_history_path, get_log_path};
+use crate::utils::{clipboard, logging, shutdown, time};
 
-use crate::core::{downloader, player, youtube};
-use crate::storage::{config, history::History};
-use crate::types::{AppState, DownloadOptions, MenuItem, PlayOptions, Video};
-use crate::ui::selector::{create_selector, detect_selector};
-use crate::utils::paths::{ensure_app_dirs, get_history_path};
+/// How many history matches a unified search lists above YouTube's results
+const UNIFIED_HISTORY_HITS: usize = 5;
 
 /// YouTube audio in your terminal. Clean and distraction-free.
 #[derive(Parser, Debug)]
 #[command(name = "yt-chill")]
 #[command(version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Search query
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Search query (after `--` if it starts with a subcommand's name, e.g. `-- history of jazz`)
     #[arg(trailing_var_arg = true)]
     query: Vec<String>,
 
@@ -31,14 +47,62 @@ struct Cli {
     #[arg(long)]
     video: bool,
 
+    /// Log requests, parsing and child processes to stderr (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Also log to a file, `--log-file=PATH` (default: yt-chill.log in the cache directory), at debug level or as -v says
+    #[arg(long, value_name = "PATH", require_equals = true, global = true)]
+    log_file: Option<Option<PathBuf>>,
+
+    /// Add the selected results to the queue instead of playing them
+    #[arg(long)]
+    enqueue: bool,
+
     /// Download instead of streaming
     #[arg(short, long)]
     download: bool,
 
+    /// Download again even if it's already in the download archive
+    #[arg(long)]
+    force: bool,
+
+    /// Download filename template, e.g. "%(uploader)s/%(title)s.%(ext)s" (default: `output_template` from config)
+    #[arg(short, long, value_name = "TEMPLATE", value_parser = parse_output_template)]
+    output: Option<String>,
+
+    /// Extra argument for mpv, repeatable (added after `mpv_args` from config)
+    #[arg(long = "mpv-arg", value_name = "ARG", allow_hyphen_values = true)]
+    mpv_args: Vec<String>,
+
+    /// Use this browser's YouTube cookies, e.g. firefox (default: `cookies_from_browser` from config)
+    #[arg(long, value_name = "BROWSER", value_parser = parse_cookie_browser)]
+    cookies_from_browser: Option<String>,
+
+    /// Codec for audio downloads (default: `audio_format` from config, mp3)
+    #[arg(long, value_enum)]
+    audio_format: Option<AudioFormat>,
+
+    /// Cap download speed, e.g. 500K or 2M (default: `limit_rate` from config)
+    #[arg(long, value_name = "RATE", value_parser = parse_rate)]
+    limit_rate: Option<String>,
+
+    /// Browse and play downloaded files
+    #[arg(long)]
+    library: bool,
+
     /// Show and replay from viewing history
     #[arg(long)]
     history: bool,
 
+    /// Order of search results (ctrl-s in the list switches between them)
+    #[arg(long, value_enum, value_name = "ORDER")]
+    sort_by: Option<ResultSort>,
+
+    /// Order of the history menu (default: `history_sort` from config)
+    #[arg(long, value_enum, value_name = "ORDER")]
+    history_sort: Option<HistorySort>,
+
     /// View videos from your feed
     #[arg(short = 'F', long)]
     feed: bool,
@@ -47,207 +111,1015 @@ struct Cli {
     #[arg(short, long)]
     subscribe: bool,
 
+    /// Subscribe to the channel of the video you pick instead of playing it
+    #[arg(long)]
+    subscribe_from_video: bool,
+
     /// Watch with friends via syncplay
     #[arg(long)]
     syncplay: bool,
 
-    /// Limit search results
-    #[arg(short, long, default_value = "15")]
-    limit: usize,
+    /// Host a new syncplay room and print an invite (the queue joins the shared playlist)
+    #[arg(long)]
+    syncplay_host: bool,
+
+    /// Podcast profile: faster playback, resume, long-form search and chapters (see `[podcast]` in config)
+    #[arg(long)]
+    podcast: bool,
+
+    /// Loop a section of the video, e.g. "1:23-2:45" (mpv; press l in mpv to set a new loop)
+    #[arg(long, value_name = "A-B", value_parser = parse_ab_loop)]
+    ab_loop: Option<(u64, u64)>,
+
+    /// Search your watch history too; matches you've played are listed first, marked with their play count
+    #[arg(long)]
+    unified: bool,
+
+    /// Hide live streams from search and feed results
+    #[arg(long)]
+    no_live: bool,
+
+    /// Save mobile data: low-bitrate streams, no thumbnails, fewer feed videos per channel
+    #[arg(long)]
+    data_saver: bool,
+
+    /// Hide search and feed results shorter than this, e.g. "1h" (default: `min_duration` from config)
+    #[arg(long, value_name = "DURATION", value_parser = parse_time_budget)]
+    min_duration: Option<Duration>,
+
+    /// Hide search and feed results longer than this, e.g. "3h" (default: `max_duration` from config)
+    #[arg(long, value_name = "DURATION", value_parser = parse_time_budget)]
+    max_duration: Option<Duration>,
+
+    /// Play live streams from the beginning instead of the live edge
+    #[arg(long)]
+    live_from_start: bool,
+
+    /// Play the queue and take commands over HTTP at this address, e.g. 127.0.0.1:8080 (see README)
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<std::net::SocketAddr>,
+
+    /// Play the first search result straight away, without picking
+    #[arg(long)]
+    first: bool,
+
+    /// Search and play the top result, then exit: no menus at all
+    #[arg(long, value_name = "QUERY", conflicts_with_all = ["query", "first", "last", "history", "feed", "library", "subscribe"])]
+    lucky: Option<String>,
+
+    /// Replay the most recently played video, no menus
+    #[arg(long, conflicts_with_all = ["query", "history", "feed", "library", "subscribe"])]
+    last: bool,
+
+    /// Play something random from history (the default), the feed or favorites
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FROM",
+        num_args = 0..=1,
+        default_missing_value = "history",
+        conflicts_with_all = ["query", "last", "history", "feed", "library", "subscribe"]
+    )]
+    random: Option<RandomSource>,
+
+    /// Go back to the list you were on last time (search results, feed, history or favorites)
+    #[arg(long, conflicts_with_all = ["query", "lucky", "last", "random", "history", "feed", "library", "subscribe"])]
+    resume_session: bool,
+
+    /// Remember where playback stopped and pick up from there next time (mpv)
+    #[arg(long)]
+    resume: bool,
+
+    /// Limit search results (default: `limit` from config, 15)
+    #[arg(short, long)]
+    limit: Option<usize>,
 
     /// Copy or display the video link
     #[arg(long)]
     copy_url: bool,
 
+    /// No colors or icons, for minimal terminals and screen readers (NO_COLOR turns off colors too)
+    #[arg(long)]
+    plain: bool,
+
+    /// Fetch fresh results instead of using the cache (still refreshes it)
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Fetch every channel in the feed again, even ones fetched within `feed_ttl_secs`
+    #[arg(long)]
+    refresh: bool,
+
     /// Edit the configuration file
     #[arg(short, long)]
     edit: bool,
+
+    /// Keep playing through the results for this long, e.g. "2h" or "45m"
+    #[arg(long = "for", value_name = "DURATION", value_parser = parse_time_budget)]
+    time_budget: Option<Duration>,
+}
+
+/// clap value parser for `--ab-loop`
+fn parse_ab_loop(s: &str) -> Result<(u64, u64), String> {
+    time::parse_clock_range(s).ok_or_else(|| format!("invalid loop '{}' (try 1:23-2:45)", s))
+}
+
+/// clap value parser for `--for`, `--min-duration` and `--max-duration`
+fn parse_time_budget(s: &str) -> Result<Duration, String> {
+    time::parse_duration(s).ok_or_else(|| format!("invalid duration '{}' (try 2h, 45m, 1h30m)", s))
+}
+
+/// Parse `--limit-rate`
+fn parse_rate(s: &str) -> Result<String, String> {
+    if downloader::is_valid_rate(s) {
+        Ok(s.to_string())
+    } else {
+        Err(format!("invalid rate '{}' (try 500K, 2M)", s))
+    }
+}
+
+/// Parse `--cookies-from-browser`
+fn parse_cookie_browser(s: &str) -> Result<String, String> {
+    if player::is_valid_cookie_browser(s) {
+        Ok(s.to_string())
+    } else {
+        Err(format!("unsupported browser '{}' (try firefox, chrome, safari)", s))
+    }
+}
+
+/// Parse `--output`
+fn parse_output_template(s: &str) -> Result<String, String> {
+    downloader::check_output_template(s)
+        .map(|_| s.to_string())
+        .map_err(|problem| format!("output template {}", problem))
+}
+
+/// Ask the first-run questions and write config.toml. Defaults are written
+/// even if the questions are skipped, so they're only asked once.
+async fn first_run_setup() -> anyhow::Result<()> {
+    let (config, answered) = match ui::setup::run(Config::default()) {
+        Ok(config) => (config, true),
+        Err(_) => (Config::default(), false),
+    };
+    config::save_config(&config).await?;
+
+    let path = utils::paths::get_config_path();
+    if answered {
+        println!("\n{} Saved to {} {}\n", "✓".green(), path, "(change it any time with `yt-chill -e`)".dimmed());
+    } else {
+        println!("\n{}\n", format!("Using the defaults, saved to {} (change them with `yt-chill -e`)", path).dimmed());
+    }
+    Ok(())
+}
+
+/// Warn about config problems without aborting
+fn print_config_issues(issues: &[ConfigIssue]) {
+    for issue in issues {
+        let field = if issue.field.is_empty() {
+            String::new()
+        } else {
+            format!("`{}` ", issue.field)
+        };
+        eprintln!("{} {}{}", "Config:".yellow(), field, issue.problem);
+        if let Some(ref suggestion) = issue.suggestion {
+            eprintln!("  {} {}", "→".dimmed(), suggestion);
+        }
+    }
+}
+
+/// Print what played during a time-boxed session
+fn print_session_summary(played: &[PlayedItem]) {
+    let total: Duration = played.iter().map(|p| p.listened).sum();
+    println!(
+        "{} {} tracks in {}",
+        "Session done:".green(),
+        played.len(),
+        time::format_duration(total)
+    );
+    for item in played {
+        println!(
+            "  {} {}",
+            format!("[{}]", time::format_duration(item.listened)).dimmed(),
+            item.video.title
+        );
+    }
+}
+
+/// Menu items for a list of videos, with thumbnails
+fn video_menu_items(videos: &[Video]) -> Vec<MenuItem<Video>> {
+    videos
+        .iter()
+        .map(|v| MenuItem {
+            label: format_video_label(v),
+            value: v.clone(),
+            preview: Some(v.id.clone()),
+        })
+        .collect()
+}
+
+/// Menu items for a unified search, marking videos already in history
+fn unified_menu_items(videos: &[Video], history: &History) -> Vec<MenuItem<Video>> {
+    videos
+        .iter()
+        .map(|v| MenuItem {
+            label: format_search_label(v, history.get(&v.id)),
+            value: v.clone(),
+            preview: Some(v.id.clone()),
+        })
+        .collect()
+}
+
+/// Where the last selection sits in a list, to put the cursor back on it
+fn list_position(videos: &[Video], selected: Option<&Video>) -> usize {
+    selected
+        .and_then(|s| videos.iter().position(|v| v.id == s.id))
+        .unwrap_or(0)
+}
+
+/// Any one of `items`. Hash keys are seeded randomly, which is plenty for
+/// picking a song.
+fn pick_random<T>(items: &[T]) -> Option<&T> {
+    use std::hash::{BuildHasher, Hasher, RandomState};
+    let roll = RandomState::new().build_hasher().finish();
+    items.get((roll % items.len().max(1) as u64) as usize)
+}
+
+/// What `--random` can pick from, minus blocked videos and (if there's
+/// anything else) the one that played last
+async fn random_candidates(source: RandomSource, history: &History, blocklist: &Blocklist, refresh: bool) -> anyhow::Result<Vec<Video>> {
+    let mut videos: Vec<Video> = match source {
+        RandomSource::History => history.get_all().iter().map(|e| e.video.clone()).collect(),
+        RandomSource::Favorites => {
+            storage::favorites::load_favorites().await?.into_iter().map(|f| f.video).collect()
+        }
+        RandomSource::Feed => {
+            let subs = storage::subscriptions::load_subscriptions().await?;
+            let subs: Vec<_> = subs.into_iter().filter(|s| !s.muted).collect();
+            if subs.is_empty() {
+                anyhow::bail!("No subscriptions yet. Use --subscribe to add channels.");
+            }
+            println!("{} {} subscriptions", "Loading feed from".dimmed(), subs.len());
+            feed::load_feed(&subs, refresh).await?.videos()
+        }
+    };
+    blocklist.filter(&mut videos);
+
+    let last = history.get_all().first().map(|e| e.video.id.as_str());
+    if videos.len() > 1 && let Some(last) = last {
+        videos.retain(|v| v.id != last);
+    }
+    Ok(videos)
+}
+
+/// Save the list being browsed and the last pick for `--resume-session`.
+/// Best-effort: a failed save shouldn't get in the way of playing.
+async fn remember_session(list: AppState, query: &str, tag: Option<&str>, results: &[Video], selected: Option<&Video>) {
+    let Some(list) = SessionList::from_state(list) else {
+        return;
+    };
+    let session = Session {
+        list,
+        query: query.to_string(),
+        tag: tag.map(str::to_string),
+        selected: selected.and_then(|s| results.iter().position(|v| v.id == s.id)),
+        results: results.to_vec(),
+        saved_at: chrono::Utc::now().timestamp(),
+    };
+    if let Err(e) = storage::session::save_session(&session).await {
+        tracing::debug!("Couldn't save the session: {}", e);
+    }
 }
 
-/// Format video for display in selector
-fn format_video_label(video: &Video) -> String {
-    format!(
-        "{} {} - {}",
-        video.title,
-        format!("[{}]", video.duration).dimmed(),
-        video.author.cyan()
-    )
+/// Replace a leading `@name` with the search saved as `alias.name` in config.
+/// Anything else, including an `@name` that isn't an alias (a channel
+/// handle like `@LofiGirl`), is searched for as typed.
+fn expand_alias(query: &str, cfg: &Config) -> String {
+    let Some(rest) = query.strip_prefix('@') else {
+        return query.to_string();
+    };
+    let (name, extra) = rest.split_once(' ').unwrap_or((rest, ""));
+    let Some(expansion) = cfg.alias.get(name) else {
+        tracing::debug!("No alias @{}, searching for it as typed", name);
+        return query.to_string();
+    };
+    format!("{} {}", expansion, extra).trim().to_string()
 }
 
 /// Determine initial state from CLI options
 fn determine_initial_state(cli: &Cli) -> AppState {
-    if cli.history {
+    if cli.last || cli.random.is_some() {
+        return AppState::Play;
+    }
+    if cli.history || matches!(cli.command, Some(Command::History { action: None })) {
         return AppState::History;
     }
+    if matches!(cli.command, Some(Command::Fav { action: None })) {
+        return AppState::Favorites;
+    }
     if cli.feed {
         return AppState::Feed;
     }
+    if cli.library {
+        return AppState::Library;
+    }
     if cli.subscribe {
         return AppState::Subscribe;
     }
-    if !cli.query.is_empty() {
+    if !cli.query.is_empty() || cli.lucky.is_some() {
         return AppState::Search;
     }
     AppState::Init
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> anyhow::Result<ExitCode> {
     let cli = Cli::parse();
 
-    // Ensure app directories exist
-    ensure_app_dirs().await?;
+    // On Ctrl+C/SIGTERM, dropping `run` kills yt-dlp and anything else still
+    // running (they're spawned with kill_on_drop). During playback Ctrl+C is
+    // left to the player, which quits and saves its position as if q was pressed.
+    tokio::select! {
+        result = run(cli) => match result {
+            Ok(()) => Ok(ExitCode::SUCCESS),
+            Err(e) => {
+                print_error(&e);
+                let code = e.chain().find_map(|c| c.downcast_ref::<YtChillError>()).map_or(1, YtChillError::exit_code);
+                Ok(ExitCode::from(code))
+            }
+        },
+        code = shutdown::signal() => {
+            shutdown::restore_terminal();
+            eprintln!("{}", "Interrupted".dimmed());
+            Ok(ExitCode::from(code))
+        }
+    }
+}
+
+/// Subscribe to the channel that uploaded `video`, looking up its ID on the
+/// watch page rather than searching for the channel by name
+async fn subscribe_to_uploader(video: &Video) {
+    use crate::storage::subscriptions::{add_subscription, known_channel_id, load_subscriptions};
+    use crate::types::Subscription;
+
+    println!("{}", format!("Looking up the channel of {}...", video.title).dimmed());
+    let channel = match youtube::fetch_video_channel(&video.id).await {
+        Ok(channel) => channel,
+        Err(e) => return print_error(&e.into()),
+    };
+    let name = if channel.name.is_empty() { video.author.clone() } else { channel.name };
+
+    let subs = load_subscriptions().await.unwrap_or_default();
+    if subs.iter().any(|s| known_channel_id(s).as_deref() == Some(channel.id.as_str()) || s.handle == channel.handle) {
+        println!("{}", format!("Already subscribed to {}", name).dimmed());
+        return;
+    }
+
+    let sub = Subscription {
+        name: name.clone(),
+        handle: channel.handle,
+        channel_id: Some(channel.id),
+        ..Default::default()
+    };
+    match add_subscription(&sub).await {
+        Ok(_) => println!("{} Subscribed to {}", "✓".green(), name),
+        Err(e) => eprintln!("{} Failed to subscribe: {}", "Error:".red(), e),
+    }
+}
+
+/// Print an error, plus what to do about it when we know
+fn print_error(e: &anyhow::Error) {
+    eprintln!("{} {:#}", "Error:".red(), e);
+    if let Some(hint) = e.chain().find_map(|c| c.downcast_ref::<YtChillError>()).and_then(YtChillError::hint) {
+        eprintln!("  {}", hint.dimmed());
+    }
+}
+
+/// Everything after argument parsing; cancelled as a whole on Ctrl+C
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // Ensure app directories exist (`doctor` reports on them instead of failing)
+    let dirs_ready = ensure_app_dirs().await;
+    if !matches!(cli.command, Some(Command::Doctor)) {
+        dirs_ready?;
+    }
+
+    // `--log-file` without a path logs to the cache directory
+    let log_file = cli.log_file.clone().map(|path| path.unwrap_or_else(|| PathBuf::from(get_log_path())));
+    logging::init(cli.verbose, log_file.as_deref())?;
+    tracing::info!("yt-chill {} starting", env!("CARGO_PKG_VERSION"));
+
+    // Offer a one-time migration from config.json to config.toml
+    if config::needs_migration() && std::io::stdin().is_terminal() {
+        let migrate = dialoguer::Confirm::new()
+            .with_prompt("Found config.json. Migrate to the new config.toml format?")
+            .default(true)
+            .interact()?;
+
+        if migrate {
+            // A config.json we can't convert is still usable, so report it and carry on
+            match config::migrate_to_toml().await {
+                Ok(path) => println!("{} Config migrated to {}", "✓".green(), path.display()),
+                Err(e) => print_config_issues(&[ConfigIssue {
+                    field: String::new(),
+                    problem: format!("config.json could not be migrated: {}", e),
+                    suggestion: Some("Fix it with `yt-chill -e` and restart; using config.json for now".into()),
+                }]),
+            }
+        } else {
+            config::decline_migration().await?;
+        }
+    }
+
+    // First run: ask about the basics instead of silently using defaults
+    if config::find_config_file().is_none()
+        && cli.command.is_none()
+        && std::io::stdin().is_terminal()
+        && std::io::stdout().is_terminal()
+    {
+        first_run_setup().await?;
+    }
+
+    // Load config, reporting anything we had to fall back on
+    let report = config::load_config_checked().await?;
+    theme::set_theme(report.config.theme.clone(), cli.plain);
+    print_config_issues(&report.issues);
+    let mut cfg = report.config;
+    let data_saver = cli.data_saver || cfg.data_saver;
+    if data_saver {
+        cfg.thumbnail_previews = false;
+    }
+
+    // CLI flags override config, which overrides defaults
+    let limit = cli.limit.unwrap_or(cfg.limit);
+    let video_mode = cli.video || cfg.video_mode;
+    let unified = cli.unified || cfg.unified_search;
+    let blocklist = Blocklist::from_config(&cfg)
+        .hide_live(cli.no_live)
+        .durations(cli.min_duration, cli.max_duration);
+    let non_empty = |s: &String| Some(s.clone()).filter(|s| !s.is_empty());
+    let cookies_from_browser = cli
+        .cookies_from_browser
+        .clone()
+        .or_else(|| non_empty(&cfg.cookies_from_browser));
+    let max_height = Some(cfg.max_resolution).filter(|&h| h > 0);
+    let play_defaults = PlayOptions {
+        video: video_mode,
+        pre_play_hook: non_empty(&cfg.pre_play_hook),
+        player: cfg.player.clone(),
+        mpd_host: non_empty(&cfg.mpd_host),
+        mpv_args: cfg.mpv_args.iter().chain(&cli.mpv_args).cloned().collect(),
+        cookies_from_browser: cookies_from_browser.clone(),
+        proxy: non_empty(&cfg.proxy),
+        live_from_start: cli.live_from_start,
+        speed: cli.podcast.then_some(cfg.podcast.speed).filter(|s| *s != 1.0),
+        resume: cli.resume || (cli.podcast && cfg.podcast.resume),
+        chapters: cli.podcast && cfg.podcast.chapters,
+        ab_loop: cli.ab_loop,
+        format: data_saver.then(|| player::data_saver_format(video_mode, &cfg.player).to_string()),
+        max_height,
+        ..Default::default()
+    };
+    let download_defaults = DownloadOptions {
+        video: video_mode,
+        max_height,
+        force: cli.force,
+        limit_rate: cli.limit_rate.clone().or_else(|| non_empty(&cfg.limit_rate)),
+        audio_format: cli.audio_format.unwrap_or(cfg.audio_format),
+        audio_quality: non_empty(&cfg.audio_quality),
+        output_template: cli.output.clone().or_else(|| non_empty(&cfg.output_template)),
+        organize: cfg.organize_downloads,
+        embed_metadata: cfg.embed_metadata,
+        title_pattern: non_empty(&cfg.title_pattern),
+        external_downloader: non_empty(&cfg.external_downloader),
+        external_downloader_args: non_empty(&cfg.external_downloader_args),
+        output_dir: if cfg.download_dir.is_empty() {
+            dirs::download_dir()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| ".".into())
+        } else {
+            cfg.download_dir.clone()
+        },
+        cookies_from_browser,
+        proxy: non_empty(&cfg.proxy),
+        notify: cfg.notify,
+        ..Default::default()
+    };
+
+    storage::set_backend(cfg.storage);
+    youtube::set_proxy(Some(cfg.proxy.clone()).filter(|p| !p.is_empty()));
+    youtube::set_network(cfg.network.clone());
+    youtube::init_backend()?;
+    ytdlp::set_path(non_empty(&cfg.yt_dlp_path));
+    #[cfg(unix)]
+    crate::core::now_playing_file::set_path(non_empty(&cfg.now_playing_file));
+    cache::configure(cfg.cache.clone(), cli.no_cache);
+    events::configure(cfg.events.clone());
+    feed::set_data_saver(data_saver);
+
+    // Called by the fzf preview pane for each highlighted result
+    if let Some(Command::Thumbnail { ref id }) = cli.command {
+        let path = thumbnail::fetch_thumbnail(id).await?;
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    if let Some(Command::Doctor) = cli.command {
+        return commands::doctor::run(&cfg, &report.issues).await;
+    }
 
     // Handle --edit flag
     if cli.edit {
-        let cfg = config::load_config().await?;
         config::edit_config(&cfg.editor).await?;
         return Ok(());
     }
 
-    // Load config
-    let cfg = config::load_config().await?;
-
-    // Load history
+    // Load history (moving it out of the cache dir if needed)
+    history::migrate_legacy_history().await?;
     let mut history = History::new(&get_history_path(), cfg.max_history_entries);
     history.load().await?;
 
     // Create selector
-    let selector = create_selector(detect_selector());
+    let selector = create_selector(cfg.selector, &cfg);
+    if selector.has_preview()
+        && let Some(width) = format::terminal_width()
+    {
+        // Fit labels beside fzf's thumbnail pane
+        format::set_list_width(width * (100 - ui::fzf::PREVIEW_PERCENT) / 100);
+    }
+
+    // Subcommands run and exit without entering the interactive flow
+    match cli.command {
+        Some(Command::History { action: Some(action) }) => {
+            return commands::history::run(action, &mut history).await;
+        }
+        Some(Command::Fav { action: Some(action) }) => {
+            return commands::favorites::run(action, &history).await;
+        }
+        Some(Command::Download { resume, clean, ref playlist, jobs, schedule }) => {
+            if let Some(time) = schedule {
+                let job = match playlist {
+                    Some(url) => DownloadJob::Playlist { url: url.clone() },
+                    None if resume => DownloadJob::Resume,
+                    None => anyhow::bail!("--schedule needs --playlist <URL> or --resume"),
+                };
+                let jobs = jobs.map(usize::from);
+                return commands::schedule::schedule(job, time, &download_defaults, jobs, cfg.download_jobs).await;
+            }
+            if clean {
+                return commands::download::clean().await;
+            }
+            let jobs = jobs.map_or(cfg.download_jobs, usize::from);
+            if let Some(url) = playlist {
+                return commands::download::playlist(url, &download_defaults, jobs).await;
+            }
+            return commands::download::run(resume, &download_defaults).await;
+        }
+        Some(Command::Archive { ref channel, limit, since, video, jobs, schedule }) => {
+            let opts = DownloadOptions { video: video || download_defaults.video, ..download_defaults.clone() };
+            if let Some(time) = schedule {
+                let job = DownloadJob::Archive { channel: channel.clone(), limit, since };
+                let jobs = jobs.map(usize::from);
+                return commands::schedule::schedule(job, time, &opts, jobs, cfg.download_jobs).await;
+            }
+            let jobs = jobs.map_or(cfg.download_jobs, usize::from);
+            return commands::archive::run(channel, limit, since, &opts, jobs).await;
+        }
+        Some(Command::Schedule { action }) => {
+            return commands::schedule::run(action, &download_defaults, cfg.download_jobs).await;
+        }
+        Some(Command::Deps { action }) => {
+            return commands::deps::run(action).await;
+        }
+        Some(Command::Info { ref video, json }) => {
+            return commands::info::run(video, json).await;
+        }
+        Some(Command::Cache { action }) => {
+            return commands::cache::run(action).await;
+        }
+        Some(Command::Searches { action }) => {
+            return commands::searches::run(action, cfg.max_search_history).await;
+        }
+        Some(Command::Takeout { path }) => {
+            return commands::takeout::run(&path, &mut history).await;
+        }
+        Some(Command::Subs { action }) => {
+            return commands::subs::run(action, &selector).await;
+        }
+        Some(Command::Watch { interval, once }) => {
+            let opts = play_defaults.clone();
+            return commands::watch::run(interval, once, &cfg, &mut history, &opts).await;
+        }
+        Some(Command::Daemon) => {
+            #[cfg(unix)]
+            return commands::serve::run(None, None, &mut history, &play_defaults).await;
+            #[cfg(not(unix))]
+            anyhow::bail!("The daemon needs mpv's IPC socket, which yt-chill only supports on Unix");
+        }
+        Some(Command::Ctl { action }) => {
+            #[cfg(unix)]
+            return commands::ctl::run(action).await;
+            #[cfg(not(unix))]
+            anyhow::bail!("`yt-chill ctl {:?}` needs a Unix socket, which yt-chill only supports on Unix", action);
+        }
+        Some(Command::Queue { action }) => {
+            let opts = play_defaults.clone();
+            return commands::queue::run(action, &mut history, &opts).await;
+        }
+        _ => {}
+    }
+
+    if let Some(addr) = cli.serve {
+        #[cfg(unix)]
+        return commands::serve::run(Some(addr), non_empty(&cfg.serve_token), &mut history, &play_defaults).await;
+        #[cfg(not(unix))]
+        anyhow::bail!("--serve {} needs mpv's IPC socket, which yt-chill only supports on Unix", addr);
+    }
 
     // State machine
     let mut state = determine_initial_state(&cli);
+    // Esc in a list goes back to the main menu if we started there, otherwise exits
+    // (always the menu with `return_to_menu`)
+    let back = if state == AppState::Init || cfg.return_to_menu { AppState::Init } else { AppState::Exit };
+    // Where to go once playback, a download or another action is done
+    let after_action = |list: AppState| if cfg.return_to_menu { AppState::Init } else { list };
     let mut selected_video: Option<Video> = None;
-    let query = cli.query.join(" ");
+    // Picked with an fzf key binding; overrides the -d/--copy-url flags
+    let mut selected_action = SelectAction::Play;
+    let mut result_sort = cli.sort_by.unwrap_or_default();
+    // With -d or --enqueue, search and feed results can be marked in bulk
+    let batch_mode = cli.download || cli.enqueue;
+    let mut batch: Vec<Video> = Vec::new();
+    // The list the selection came from, used as the queue for `--for`;
+    // after playback we return to it
+    let mut results: Vec<Video> = Vec::new();
+    let mut list_state = back;
+    // Kept so going back to them doesn't search or fetch again
+    let mut search_results: Option<Vec<Video>> = None;
+    let mut feed_results: Option<Vec<Video>> = None;
+    // Tag picked in the favorites menu (`Some(None)` = all favorites)
+    let mut fav_tag: Option<Option<String>> = None;
+    let mut played_any = false;
+    let mut query = expand_alias(&cli.lucky.clone().unwrap_or_else(|| cli.query.join(" ")), &cfg);
+    // `--first` skips picking once; going back shows the whole list
+    let mut pick_first = cli.first || cli.lucky.is_some();
+
+    if cli.last {
+        let Some(last) = history.get_all().first() else {
+            anyhow::bail!("Nothing to replay yet; your history is empty");
+        };
+        selected_video = Some(last.video.clone());
+    }
+    if let Some(source) = cli.random {
+        let candidates = random_candidates(source, &history, &blocklist, cli.refresh).await?;
+        let Some(video) = pick_random(&candidates) else {
+            anyhow::bail!("Nothing to pick from in your {}", format!("{:?}", source).to_lowercase());
+        };
+        selected_video = Some(video.clone());
+    }
+    if cli.resume_session {
+        let Some(session) = storage::session::load_session().await? else {
+            anyhow::bail!("No session to resume yet");
+        };
+        let list = format!("{:?}", session.list).to_lowercase();
+        println!(
+            "{}",
+            format!("Back to your {} from {}", list, time::format_timestamp(session.saved_at)).dimmed()
+        );
+        state = session.list.state();
+        selected_video = session.selected.and_then(|i| session.results.get(i)).cloned();
+        query = session.query;
+        // Searches and the feed come back as they were; the others are reloaded
+        match session.list {
+            SessionList::Search => search_results = Some(session.results),
+            SessionList::Feed => feed_results = Some(session.results),
+            SessionList::Favorites => fav_tag = Some(session.tag),
+            SessionList::History => {}
+        }
+    }
 
     while state != AppState::Exit {
         match state {
             AppState::Init => {
+                // Starting over: new searches, a fresh feed
+                query.clear();
+                search_results = None;
+                feed_results = None;
+                fav_tag = None;
+
                 // Show main menu
                 let menu_items = vec![
-                    MenuItem { label: "🔍 Search YouTube".into(), value: AppState::Search },
-                    MenuItem { label: "📜 View your history".into(), value: AppState::History },
-                    MenuItem { label: "➕ Add subscription".into(), value: AppState::Subscribe },
-                    MenuItem { label: "📺 View your feed".into(), value: AppState::Feed },
+                    MenuItem::new(with_icon(Icon::Search, "Search YouTube"), AppState::Search),
+                    MenuItem::new(with_icon(Icon::History, "View your history"), AppState::History),
+                    MenuItem::new(with_icon(Icon::Subscribe, "Add subscription"), AppState::Subscribe),
+                    MenuItem::new(with_icon(Icon::Feed, "View your feed"), AppState::Feed),
+                    MenuItem::new(with_icon(Icon::Favorites, "Favorites"), AppState::Favorites),
+                    MenuItem::new(with_icon(Icon::Library, "Downloaded library"), AppState::Library),
+                    MenuItem::new(with_icon(Icon::Settings, "Settings"), AppState::Settings),
                 ];
 
                 state = selector.select(&menu_items, "Select Action").unwrap_or(AppState::Exit);
             }
 
             AppState::Search => {
-                let search_query = if query.is_empty() {
-                    // Prompt for query using dialoguer
-                    let input: String = dialoguer::Input::new()
-                        .with_prompt("Search YouTube")
-                        .interact_text()?;
-                    input
+                let videos = match search_results.take() {
+                    Some(videos) => videos,
+                    None => {
+                        let mut searches = SearchHistory::load(cfg.max_search_history).await?;
+                        let search_query = if query.is_empty() {
+                            // Prompt for query using dialoguer; ↑ recalls past queries
+                            let input: String = dialoguer::Input::new()
+                                .with_prompt(if unified { "Search history + YouTube" } else { "Search YouTube" })
+                                .history_with(&mut searches)
+                                .interact_text()?;
+                            input
+                        } else {
+                            query.clone()
+                        };
+
+                        if search_query.is_empty() {
+                            state = back;
+                            continue;
+                        }
+                        query = search_query.clone();
+                        searches.add(&search_query);
+                        searches.save().await?;
+
+                        println!("{}", "Searching...".dimmed());
+                        let found = if cli.podcast && cfg.podcast.long_form {
+                            youtube::search_long_videos(&search_query, limit).await
+                        } else {
+                            youtube::search_videos(&search_query, limit).await
+                        };
+                        let found = match found {
+                            Ok(videos) if unified => Ok(history.merge_search(&search_query, videos, UNIFIED_HISTORY_HITS)),
+                            // Offline or blocked, history matches are still worth showing
+                            Err(e) if unified && !history.search(&search_query).is_empty() => {
+                                print_error(&e.into());
+                                Ok(history.merge_search(&search_query, Vec::new(), UNIFIED_HISTORY_HITS))
+                            }
+                            found => found,
+                        };
+                        match found {
+                            Ok(mut videos) => {
+                                blocklist.filter(&mut videos);
+                                videos
+                            }
+                            // A one-shot search reports failure through the exit code
+                            Err(e) if back == AppState::Exit => return Err(e.into()),
+                            Err(e) => {
+                                print_error(&e.into());
+                                state = back;
+                                continue;
+                            }
+                        }
+                    }
+                };
+
+                // `videos` stays in YouTube's order so ctrl-s can go back to it
+                let mut shown = videos.clone();
+                result_sort.apply(&mut shown);
+                let menu_items = if unified {
+                    unified_menu_items(&shown, &history)
                 } else {
-                    query.clone()
+                    video_menu_items(&shown)
                 };
+                let start = list_position(&shown, selected_video.as_ref());
+                results = shown.clone();
+                // `--lucky` never shows the list, not even after playing
+                list_state = if cli.lucky.is_some() { back } else { AppState::Search };
+                if cli.lucky.is_some() && shown.is_empty() {
+                    return Err(YtChillError::NoResults.into());
+                }
+                remember_session(list_state, &query, None, &results, selected_video.as_ref()).await;
 
-                if search_query.is_empty() {
-                    state = AppState::Exit;
+                if batch_mode && !pick_first {
+                    batch = selector.select_many(&menu_items, "Select Videos");
+                    if batch.is_empty() {
+                        state = back;
+                    } else {
+                        search_results = Some(videos);
+                        state = AppState::Batch;
+                    }
                     continue;
                 }
 
-                println!("{}", "Searching...".dimmed());
-                match youtube::search_videos(&search_query, cli.limit).await {
-                    Ok(videos) => {
-                        let menu_items: Vec<MenuItem<Video>> = videos
-                            .into_iter()
-                            .map(|v| MenuItem {
-                                label: format_video_label(&v),
-                                value: v,
-                            })
-                            .collect();
-
-                        selected_video = selector.select(&menu_items, "Select Video");
-                        state = if selected_video.is_some() {
-                            AppState::Play
-                        } else {
-                            AppState::Exit
-                        };
+                let picked = if pick_first {
+                    pick_first = false;
+                    shown.first().map(|v| (SelectAction::Play, v.clone()))
+                } else {
+                    selector.select_with_action(&menu_items, "Select Video", start, true)
+                };
+                match picked {
+                    Some((SelectAction::Sort, video)) => {
+                        result_sort = result_sort.next();
+                        println!("{}", format!("Sorted by {:?}", result_sort).to_lowercase().dimmed());
+                        selected_video = Some(video);
+                        search_results = Some(videos);
                     }
-                    Err(e) => {
-                        eprintln!("{} {}", "Error:".red(), e);
-                        state = AppState::Exit;
+                    Some((action, video)) => {
+                        selected_action = action;
+                        selected_video = Some(video);
+                        search_results = Some(videos);
+                        state = AppState::Play;
                     }
+                    None => state = back,
                 }
             }
 
             AppState::History => {
-                let entries = history.get_all();
+                let entries = history.sorted(cli.history_sort.unwrap_or(cfg.history_sort));
 
                 if entries.is_empty() {
                     println!("{}", "No history yet.".yellow());
-                    state = AppState::Exit;
+                    state = back;
                     continue;
                 }
 
                 let menu_items: Vec<MenuItem<Video>> = entries
                     .iter()
                     .map(|e| MenuItem {
-                        label: format_video_label(&e.video),
+                        label: format_history_label(e),
                         value: e.video.clone(),
+                        preview: Some(e.video.id.clone()),
                     })
                     .collect();
+                results = entries.iter().map(|e| e.video.clone()).collect();
+                list_state = AppState::History;
+                remember_session(list_state, "", None, &results, selected_video.as_ref()).await;
 
-                selected_video = selector.select(&menu_items, "Select from History");
-                state = if selected_video.is_some() {
-                    AppState::Play
-                } else {
-                    AppState::Exit
-                };
+                let start = list_position(&results, selected_video.as_ref());
+                match selector.select_with_action(&menu_items, "Select from History", start, false) {
+                    Some((action, video)) => {
+                        selected_action = action;
+                        selected_video = Some(video);
+                        state = AppState::Play;
+                    }
+                    None => state = back,
+                }
             }
 
-            AppState::Feed => {
-                use crate::storage::subscriptions::load_subscriptions;
+            AppState::Library => {
+                use crate::storage::downloads::load_downloads;
+                use crate::storage::library::scan_library;
 
-                // Load subscriptions
-                let subs = load_subscriptions().await?;
+                let dir = expand_tilde(&cfg.download_dir);
+                let items = scan_library(Path::new(&dir), &load_downloads().await?);
+                if items.is_empty() {
+                    println!("{} {}", "No downloads with a [video id] in their name in".yellow(), dir);
+                    state = back;
+                    continue;
+                }
 
-                if subs.is_empty() {
-                    println!("{}", "No subscriptions yet. Use --subscribe to add channels.".yellow());
-                    state = AppState::Exit;
+                let start = selected_video
+                    .as_ref()
+                    .and_then(|s| items.iter().position(|item| item.id == s.id))
+                    .unwrap_or(0);
+                let menu_items: Vec<MenuItem<LibraryItem>> = items
+                    .into_iter()
+                    .map(|item| MenuItem {
+                        label: format_video_label(&item.to_video()),
+                        preview: Some(item.id.clone()),
+                        value: item,
+                    })
+                    .collect();
+
+                // Local files play straight away, no stream/download prompt
+                let Some(item) = selector.select_at(&menu_items, "Select from Library", start) else {
+                    state = back;
                     continue;
+                };
+                println!("{} {}", "Playing:".dimmed(), item.title);
+                let opts = PlayOptions {
+                    video: video_mode && item.has_video,
+                    title: Some(item.title.clone()),
+                    id: Some(item.id.clone()),
+                    ..play_defaults.clone()
+                };
+                match player::play(&item.path.to_string_lossy(), &opts).await {
+                    Ok(_) => history.add(&item.to_video()).await?,
+                    Err(e) => print_error(&e.into()),
                 }
+                played_any = true;
+                selected_video = Some(item.to_video());
+                state = after_action(AppState::Library);
+            }
 
-                println!("{} {} subscriptions", "Loading feed from".dimmed(), subs.len());
+            AppState::Favorites => {
+                use crate::storage::favorites::{all_tags, load_favorites, with_tag};
 
-                // Fetch videos from each subscription
-                let mut all_videos: Vec<Video> = Vec::new();
-                for sub in &subs {
-                    match youtube::fetch_channel_videos(&sub.handle, 5).await {
-                        Ok(videos) => {
-                            all_videos.extend(videos);
-                        }
-                        Err(_) => {
-                            // Silently skip failed channels
+                let favorites = load_favorites().await?;
+                if favorites.is_empty() {
+                    println!("{}", "No favorites yet. Star what's playing with `yt-chill fav add -t <tag>`.".yellow());
+                    state = back;
+                    continue;
+                }
+
+                // Pick a tag first when there are any
+                let tags = all_tags(&favorites);
+                let has_tags = !tags.is_empty();
+                let tag = match fav_tag.take() {
+                    Some(tag) => tag,
+                    None if !has_tags => None,
+                    None => {
+                        let mut tag_items = vec![MenuItem::new("★ All favorites", None)];
+                        tag_items.extend(tags.into_iter().map(|t| MenuItem::new(format!("#{}", t), Some(t))));
+                        match selector.select(&tag_items, "Select Tag") {
+                            Some(tag) => tag,
+                            None => {
+                                state = back;
+                                continue;
+                            }
                         }
                     }
+                };
+
+                let videos: Vec<Video> = with_tag(&favorites, tag.as_deref())
+                    .into_iter()
+                    .map(|f| f.video.clone())
+                    .collect();
+                let menu_items = video_menu_items(&videos);
+                let start = list_position(&videos, selected_video.as_ref());
+                results = videos;
+                list_state = AppState::Favorites;
+                remember_session(list_state, "", tag.as_deref(), &results, selected_video.as_ref()).await;
+
+                match selector.select_with_action(&menu_items, "Select Favorite", start, false) {
+                    Some((action, video)) => {
+                        selected_action = action;
+                        selected_video = Some(video);
+                        fav_tag = Some(tag);
+                        state = AppState::Play;
+                    }
+                    // Back to the tag menu, if there is one
+                    None if has_tags => state = AppState::Favorites,
+                    None => state = back,
                 }
+            }
+
+            AppState::Feed => {
+                use crate::storage::subscriptions::load_subscriptions;
+
+                let all_videos = match feed_results.take() {
+                    Some(videos) => videos,
+                    None => {
+                        // Load subscriptions
+                        let subs = load_subscriptions().await?;
+
+                        if subs.is_empty() {
+                            println!("{}", "No subscriptions yet. Use --subscribe to add channels.".yellow());
+                            state = back;
+                            continue;
+                        }
+
+                        // Muted channels stay subscribed but out of the feed
+                        let subs: Vec<_> = subs.into_iter().filter(|s| !s.muted).collect();
+
+                        println!("{} {} subscriptions", "Loading feed from".dimmed(), subs.len());
+
+                        // Channels fetched within `feed_ttl_secs` come from the cache
+                        let loaded = feed::load_feed(&subs, cli.refresh).await?;
+                        if loaded.failed > 0 {
+                            println!("{}", format!("Couldn't update {} channel(s)", loaded.failed).yellow());
+                        }
+
+                        let mut all_videos = loaded.videos();
+                        blocklist.filter(&mut all_videos);
+                        all_videos
+                    }
+                };
 
                 if all_videos.is_empty() {
                     println!("{}", "No videos found in your feed.".yellow());
-                    state = AppState::Exit;
+                    state = back;
                     continue;
                 }
 
                 // Sort by... nothing for now, just show them
-                let menu_items: Vec<MenuItem<Video>> = all_videos
-                    .into_iter()
-                    .map(|v| MenuItem {
-                        label: format_video_label(&v),
-                        value: v,
-                    })
-                    .collect();
+                let menu_items = video_menu_items(&all_videos);
+                let start = list_position(&all_videos, selected_video.as_ref());
+                results = all_videos.clone();
+                list_state = AppState::Feed;
+                remember_session(list_state, "", None, &results, selected_video.as_ref()).await;
 
-                selected_video = selector.select(&menu_items, "Select from Feed");
-                state = if selected_video.is_some() {
-                    AppState::Play
-                } else {
-                    AppState::Exit
-                };
+                if batch_mode {
+                    batch = selector.select_many(&menu_items, "Select from Feed");
+                    if batch.is_empty() {
+                        state = back;
+                    } else {
+                        feed_results = Some(all_videos);
+                        state = AppState::Batch;
+                    }
+                    continue;
+                }
+
+                match selector.select_with_action(&menu_items, "Select from Feed", start, false) {
+                    Some((action, video)) => {
+                        selected_action = action;
+                        selected_video = Some(video);
+                        feed_results = Some(all_videos);
+                        state = AppState::Play;
+                    }
+                    None => state = back,
+                }
             }
 
             AppState::Subscribe => {
@@ -260,25 +1132,24 @@ async fn main() -> anyhow::Result<()> {
                     .interact_text()?;
 
                 if search_query.is_empty() {
-                    state = AppState::Exit;
+                    state = back;
                     continue;
                 }
 
                 println!("{}", "Searching for channels...".dimmed());
                 match youtube::search_channels(&search_query, 10).await {
                     Ok(channels) => {
-                        let menu_items: Vec<MenuItem<youtube::ChannelInfo>> = channels
+                        let menu_items: Vec<MenuItem<Channel>> = channels
                             .into_iter()
-                            .map(|c| MenuItem {
-                                label: format!("{} ({})", c.name, c.handle.cyan()),
-                                value: c,
-                            })
+                            .map(|c| MenuItem::new(format_channel_label(&c), c))
                             .collect();
 
                         if let Some(channel) = selector.select(&menu_items, "Select Channel") {
                             let sub = Subscription {
                                 name: channel.name.clone(),
                                 handle: channel.handle.clone(),
+                                channel_id: Some(channel.id.clone()).filter(|id| !id.is_empty()),
+                                ..Default::default()
                             };
 
                             match add_subscription(&sub).await {
@@ -292,33 +1163,55 @@ async fn main() -> anyhow::Result<()> {
                         }
                     }
                     Err(e) => {
-                        eprintln!("{} {}", "Error:".red(), e);
+                        print_error(&e.into());
                     }
                 }
-                state = AppState::Exit;
+                state = back;
             }
 
             AppState::Play => {
                 let Some(ref video) = selected_video else {
-                    state = AppState::Exit;
+                    state = list_state;
                     continue;
                 };
 
                 let url = player::build_video_url(&video.id);
+                let tag = fav_tag.clone().flatten();
+                remember_session(list_state, &query, tag.as_deref(), &results, Some(video)).await;
+
+                // Queueing isn't watching, so it stays out of history
+                if selected_action == SelectAction::Queue {
+                    storage::queue::enqueue(std::slice::from_ref(video)).await?;
+                    println!("{} Queued {}", "✓".green(), video.title);
+                    state = after_action(list_state);
+                    continue;
+                }
+
+                // Neither is subscribing
+                if cli.subscribe_from_video || selected_action == SelectAction::Subscribe {
+                    subscribe_to_uploader(video).await;
+                    state = after_action(list_state);
+                    continue;
+                }
 
                 // Add to history
                 history.add(video).await?;
 
                 // Handle copy URL option
-                if cli.copy_url {
+                if cli.copy_url || selected_action == SelectAction::CopyUrl {
                     println!("{} {}", "Video URL:".green(), url);
-                    state = AppState::Exit;
+                    if selected_action == SelectAction::CopyUrl && clipboard::copy(&url).await {
+                        println!("{}", "(copied to clipboard)".dimmed());
+                    }
+                    state = after_action(list_state);
                     continue;
                 }
 
-                // Determine action based on flags (no menu)
-                let action = if cli.download {
+                // Determine action from the key binding or flags (no menu)
+                let action = if cli.download || selected_action == SelectAction::Download {
                     "download"
+                } else if cli.syncplay_host {
+                    "syncplay-host"
                 } else if cli.syncplay {
                     "syncplay"
                 } else {
@@ -329,46 +1222,149 @@ async fn main() -> anyhow::Result<()> {
 
                 match action {
                     "stream" => {
-                        let opts = PlayOptions {
-                            video: cli.video,
-                            format: None,
-                        };
-                        if let Err(e) = player::play(&url, &opts).await {
-                            eprintln!("{} {}", "Error:".red(), e);
+                        let opts = play_defaults.clone();
+
+                        if let Some(budget) = cli.time_budget {
+                            // Play the selection, then keep going through the rest of the list
+                            let start = results.iter().position(|v| v.id == video.id).unwrap_or(0);
+                            let mut queue = vec![video.clone()];
+                            queue.extend(results.iter().skip(start + 1).cloned());
+
+                            match timebox::play_for(&queue, budget, &opts).await {
+                                Ok(played) => {
+                                    for item in played.iter().skip(1) {
+                                        history.add(&item.video).await?;
+                                    }
+                                    print_session_summary(&played);
+                                }
+                                Err(e) => print_error(&e.into()),
+                            }
+                        } else if let Err(e) = player::play_video(video, &opts).await {
+                            print_error(&e.into());
                         }
+                        played_any = true;
                     }
                     "download" => {
-                        let download_dir = if cfg.download_dir.is_empty() {
-                            dirs::download_dir()
-                                .map(|p| p.to_string_lossy().to_string())
-                                .unwrap_or_else(|| ".".into())
-                        } else {
-                            cfg.download_dir.clone()
-                        };
+                        if let Err(e) = downloader::download(video, &download_defaults).await {
+                            print_error(&e.into());
+                        }
+                    }
+                    "syncplay-host" => {
+                        let room = player::new_room_name();
+                        let invite = player::syncplay_invite(&cfg.syncplay_server, &room);
+                        let copied = clipboard::copy(&invite).await;
+                        println!("{} {}", "Invite your friends:".green(), invite.bold());
+                        if copied {
+                            println!("{}", "(copied to clipboard)".dimmed());
+                        }
 
-                        let opts = DownloadOptions {
-                            video: cli.video,
-                            format: None,
-                            output_dir: download_dir,
-                        };
-                        if let Err(e) = downloader::download(&url, &opts).await {
-                            eprintln!("{} {}", "Error:".red(), e);
+                        // The selection first, then anything in the queue
+                        let mut urls = vec![url.clone()];
+                        for queued in storage::queue::load_queue().await? {
+                            if queued.id != video.id {
+                                urls.push(player::build_video_url(&queued.id));
+                            }
+                        }
+                        if urls.len() > 1 {
+                            println!("{} {} videos in the shared playlist", "▶".dimmed(), urls.len());
+                        }
+
+                        if let Err(e) = player::host_syncplay(&urls, &cfg.syncplay_server, &room).await {
+                            print_error(&e.into());
                         }
                     }
                     "syncplay" => {
                         if let Err(e) = player::play_with_syncplay(&url).await {
-                            eprintln!("{} {}", "Error:".red(), e);
+                            print_error(&e.into());
                         }
                     }
                     _ => {}
                 }
 
-                state = AppState::Exit;
+                state = after_action(list_state);
+            }
+
+            AppState::Batch => {
+                if cli.download {
+                    match downloader::download_many(&batch, &download_defaults, cfg.download_jobs).await {
+                        Ok(summary) => commands::download::print_summary(&summary),
+                        Err(e) => print_error(&e.into()),
+                    }
+                } else {
+                    storage::queue::enqueue(&batch).await?;
+                    println!(
+                        "{} Queued {} videos {}",
+                        "✓".green(),
+                        batch.len(),
+                        "(play them with `yt-chill queue play`)".dimmed()
+                    );
+                }
+                state = after_action(list_state);
+            }
+
+            AppState::Settings => {
+                let mut saved = config::load_saved_config().await?;
+                let items = ui::setup::settings_menu(&saved);
+                let Some(setting) = selector.select(&items, "Settings") else {
+                    state = AppState::Init;
+                    continue;
+                };
+
+                let before = setting.value(&saved);
+                // Ctrl+C or a closed terminal in the prompt: leave it as it was
+                if setting.edit(&mut saved).is_err() || setting.value(&saved) == before {
+                    continue;
+                }
+                config::save_setting(&saved, setting.key()).await?;
+                println!(
+                    "{} {}: {} {}",
+                    "✓".green(),
+                    setting.name(),
+                    setting.value(&saved),
+                    "(applies next time yt-chill starts)".dimmed()
+                );
             }
 
             AppState::Exit => break,
         }
     }
 
+    if played_any {
+        println!("{}", with_icon(Icon::Goodbye, "Thanks for chilling."));
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_for_a_subcommand_name() {
+        let cli = Cli::try_parse_from(["yt-chill", "history"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::History { action: None })));
+
+        // `--` makes every word after it part of the query
+        let cli = Cli::try_parse_from(["yt-chill", "--video", "--", "history", "of", "jazz"]).unwrap();
+        assert!(cli.command.is_none());
+        assert!(cli.video);
+        assert_eq!(cli.query, ["history", "of", "jazz"]);
+
+        let cli = Cli::try_parse_from(["yt-chill", "lofi", "history"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.query, ["lofi", "history"]);
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let mut cfg = Config::default();
+        cfg.alias.insert("focus".into(), "lofi hip hop radio".into());
+
+        assert_eq!(expand_alias("@focus", &cfg), "lofi hip hop radio");
+        assert_eq!(expand_alias("@focus live", &cfg), "lofi hip hop radio live");
+        assert_eq!(expand_alias("jazz @focus", &cfg), "jazz @focus");
+        // Not an alias: searched for literally
+        assert_eq!(expand_alias("@LofiGirl", &cfg), "@LofiGirl");
+        assert_eq!(expand_alias("@LofiGirl study", &cfg), "@LofiGirl study");
+    }
+}
@@ -1,20 +1,126 @@
 //! Search result caching
-#![allow(dead_code)]
+//!
+//! Entries are stored as postcard, compressed with zstd when built with the
+//! `zstd` feature. A leading format byte tells the encodings apart; files
+//! written as JSON by older versions start with `{` and are still read, so
+//! the file names keep their `.json` extension.
 
 use crate::error::Result;
-use crate::types::CacheEntry;
+use crate::types::{CacheConfig, CacheEntry};
 use crate::utils::paths::{ensure_dir, get_cache_dir};
 use sha2::{Digest, Sha256};
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::SystemTime;
 use tokio::fs;
 use chrono::Utc;
 
-const DEFAULT_TTL: u64 = 3600; // 1 hour
+/// Bump when cached data (e.g. `Video`) changes shape, so entries written by
+/// older versions are dropped instead of failing to load on every lookup
+pub const CACHE_VERSION: u32 = 1;
 
-/// Generate cache key from query
+static SETTINGS: OnceLock<CacheConfig> = OnceLock::new();
+static BYPASS: OnceLock<bool> = OnceLock::new();
+
+/// What's being cached, which decides how long it stays fresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    Search,
+    Feed,
+    Video,
+}
+
+impl CacheKind {
+    /// Time-to-live in seconds from the `[cache]` config (0 = don't cache)
+    fn ttl(self, config: &CacheConfig) -> u64 {
+        match self {
+            CacheKind::Search => config.search_ttl_secs,
+            CacheKind::Feed => config.feed_ttl_secs,
+            CacheKind::Video => config.video_ttl_secs,
+        }
+    }
+}
+
+/// Set TTLs for this run, and whether to skip cached data (`--no-cache`).
+/// First call wins.
+pub fn configure(config: CacheConfig, bypass: bool) {
+    SETTINGS.get_or_init(|| config);
+    BYPASS.get_or_init(|| bypass);
+}
+
+/// Whether cached data is skipped this run (`--no-cache`)
+pub fn bypassed() -> bool {
+    BYPASS.get().copied().unwrap_or(false)
+}
+
+/// How long `kind` stays fresh, in seconds (0 = not cached)
+pub fn ttl_secs(kind: CacheKind) -> u64 {
+    kind.ttl(&settings())
+}
+
+/// Active cache settings (defaults unless configured)
+fn settings() -> CacheConfig {
+    SETTINGS.get().cloned().unwrap_or_default()
+}
+
+/// Format byte: postcard
+const FORMAT_POSTCARD: u8 = 1;
+/// Format byte: zstd-compressed postcard
+const FORMAT_POSTCARD_ZSTD: u8 = 2;
+
+/// zstd level: fast to write, still several times smaller than JSON
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Serialize a cache file's contents: a format byte, then the payload
+pub(crate) fn encode<T: serde::Serialize>(data: &T) -> Result<Vec<u8>> {
+    let payload = postcard::to_stdvec(data)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    let (format, payload) = compress(payload)?;
+
+    let mut content = Vec::with_capacity(payload.len() + 1);
+    content.push(format);
+    content.extend(payload);
+    Ok(content)
+}
+
+/// Deserialize a cache file's contents (None if it's in a format this build
+/// can't read, or the data has a different shape)
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(content: &[u8]) -> Option<T> {
+    match content.split_first()? {
+        (&FORMAT_POSTCARD, payload) => postcard::from_bytes(payload).ok(),
+        (&FORMAT_POSTCARD_ZSTD, payload) => postcard::from_bytes(&decompress(payload)?).ok(),
+        // Written as JSON before the switch
+        (b'{', _) => serde_json::from_slice(content).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn compress(payload: Vec<u8>) -> Result<(u8, Vec<u8>)> {
+    Ok((FORMAT_POSTCARD_ZSTD, zstd::encode_all(payload.as_slice(), ZSTD_LEVEL)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress(payload: Vec<u8>) -> Result<(u8, Vec<u8>)> {
+    Ok((FORMAT_POSTCARD, payload))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress(payload: &[u8]) -> Option<Vec<u8>> {
+    zstd::decode_all(payload).ok()
+}
+
+/// Compressed entries can't be read without the `zstd` feature
+#[cfg(not(feature = "zstd"))]
+fn decompress(_payload: &[u8]) -> Option<Vec<u8>> {
+    None
+}
+
+/// Generate cache key from query, scoped to [`CACHE_VERSION`]
 pub fn get_cache_key(query: &str) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(query.as_bytes());
+    hasher.update(format!("v{}:{}", CACHE_VERSION, query).as_bytes());
     format!("{:x}", hasher.finalize())
 }
 
@@ -23,43 +129,164 @@ fn cache_path(key: &str) -> PathBuf {
     PathBuf::from(get_cache_dir()).join(format!("{}.json", key))
 }
 
-/// Get cached data if valid
+/// Get cached data if valid (always a miss with `--no-cache`)
 pub async fn get_cached<T: serde::de::DeserializeOwned>(key: &str) -> Option<T> {
+    if bypassed() {
+        return None;
+    }
+
     let path = cache_path(key);
 
     if !path.exists() {
         return None;
     }
 
-    let content = fs::read_to_string(&path).await.ok()?;
-    let entry: CacheEntry<T> = serde_json::from_str(&content).ok()?;
+    let content = fs::read(&path).await.ok()?;
+    let entry: CacheEntry<T> = match decode(&content) {
+        Some(entry) => entry,
+        // Written by another version; don't trip over it again
+        None => {
+            remove_entry(key).await;
+            return None;
+        }
+    };
+    if entry.version != CACHE_VERSION {
+        remove_entry(key).await;
+        return None;
+    }
 
     // Check if expired
     let now = Utc::now().timestamp();
     if now - entry.timestamp > entry.ttl as i64 {
-        // Expired, remove cache file
-        let _ = fs::remove_file(&path).await;
+        remove_entry(key).await;
         return None;
     }
 
+    // Bump the modified time so size-limit eviction drops least recently used entries first
+    let _ = std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+
     Some(entry.data)
 }
 
-/// Set cache data
-pub async fn set_cache<T: serde::Serialize>(key: &str, data: &T) -> Result<()> {
+/// Delete one cache entry (file and database record)
+async fn remove_entry(key: &str) {
+    let _ = fs::remove_file(cache_path(key)).await;
+
+    #[cfg(feature = "sqlite")]
+    if super::backend() == crate::types::StorageBackend::Sqlite
+        && let Ok(store) = super::sqlite::SqliteStore::open_default().await
+    {
+        let _ = store.remove_cache_entry(key);
+    }
+}
+
+/// Set cache data, fresh for as long as the TTL for `kind`
+pub async fn set_cache<T: serde::Serialize>(key: &str, kind: CacheKind, data: &T) -> Result<()> {
+    let ttl = kind.ttl(&settings());
+    if ttl == 0 {
+        return Ok(());
+    }
+
     ensure_dir(&get_cache_dir()).await?;
 
     let entry = CacheEntry {
+        version: CACHE_VERSION,
         data,
         timestamp: Utc::now().timestamp(),
-        ttl: DEFAULT_TTL,
+        ttl,
     };
 
-    let content = serde_json::to_string(&entry)?;
-    fs::write(cache_path(key), content).await?;
+    let content = encode(&entry)?;
+    fs::write(cache_path(key), &content).await?;
+
+    // Track size/age in the database so it can be queried without scanning files
+    #[cfg(feature = "sqlite")]
+    if super::backend() == crate::types::StorageBackend::Sqlite {
+        let store = super::sqlite::SqliteStore::open_default().await?;
+        store.record_cache_entry(key, entry.timestamp, entry.ttl, content.len() as u64)?;
+    }
+
+    enforce_size_limit().await?;
     Ok(())
 }
 
+/// A cache file on disk
+#[derive(Debug, Clone)]
+pub struct CacheFile {
+    pub key: String,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Every cache entry currently on disk
+pub async fn list_cache_files() -> Result<Vec<CacheFile>> {
+    let dir = PathBuf::from(get_cache_dir());
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(key) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let meta = entry.metadata().await?;
+        if !meta.is_file() {
+            continue;
+        }
+        files.push(CacheFile {
+            key: key.to_string(),
+            size: meta.len(),
+            last_used: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+    }
+    Ok(files)
+}
+
+/// Entries to drop, least recently used first, to get under `max_bytes`
+fn pick_evictions(mut files: Vec<CacheFile>, max_bytes: u64) -> Vec<CacheFile> {
+    let mut total: u64 = files.iter().map(|f| f.size).sum();
+    files.sort_by_key(|f| f.last_used);
+
+    let mut evict = Vec::new();
+    for file in files {
+        if total <= max_bytes {
+            break;
+        }
+        total -= file.size;
+        evict.push(file);
+    }
+    evict
+}
+
+/// Evict least recently used entries while the cache is over `max_cache_size_mb`.
+/// Returns how many entries were removed.
+pub async fn enforce_size_limit() -> Result<usize> {
+    let max_bytes = max_size_bytes();
+    if max_bytes == 0 {
+        return Ok(0);
+    }
+
+    let evict = pick_evictions(list_cache_files().await?, max_bytes);
+    for file in &evict {
+        remove_entry(&file.key).await;
+    }
+    Ok(evict.len())
+}
+
+/// The configured size limit in bytes (0 = unlimited)
+pub fn max_size_bytes() -> u64 {
+    settings().max_cache_size_mb.saturating_mul(1024 * 1024)
+}
+
 /// Clear all cache
 pub async fn clear_cache() -> Result<()> {
     let cache_dir = get_cache_dir();
@@ -68,3 +295,54 @@ pub async fn clear_cache() -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn file(key: &str, size: u64, age_secs: u64) -> CacheFile {
+        CacheFile {
+            key: key.into(),
+            size,
+            last_used: SystemTime::now() - Duration::from_secs(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_cache_keys_and_entries_are_versioned() {
+        let unversioned = format!("{:x}", Sha256::digest(b"video:lofi:15"));
+        assert_ne!(get_cache_key("video:lofi:15"), unversioned);
+
+        // Entries from before versioning read as version 0, which never matches
+        let old: CacheEntry<Vec<String>> = serde_json::from_str(r#"{"data":[],"timestamp":0,"ttl":60}"#).unwrap();
+        assert_ne!(old.version, CACHE_VERSION);
+    }
+
+    #[test]
+    fn test_encode_round_trips_and_reads_json() {
+        let entry = CacheEntry { version: CACHE_VERSION, data: vec!["lofi".to_string()], timestamp: 1, ttl: 60 };
+        let content = encode(&entry).unwrap();
+        assert_ne!(content[0], b'{');
+        let decoded: CacheEntry<Vec<String>> = decode(&content).unwrap();
+        assert_eq!(decoded.data, ["lofi"]);
+
+        // Entries written as JSON by older versions
+        let json = serde_json::to_vec(&entry).unwrap();
+        let decoded: CacheEntry<Vec<String>> = decode(&json).unwrap();
+        assert_eq!(decoded.data, ["lofi"]);
+
+        assert!(decode::<CacheEntry<Vec<String>>>(b"\xffgarbage").is_none());
+        assert!(decode::<CacheEntry<Vec<String>>>(b"").is_none());
+    }
+
+    #[test]
+    fn test_pick_evictions_drops_least_recently_used() {
+        let files = vec![file("new", 40, 10), file("old", 40, 1000), file("mid", 40, 100)];
+
+        let keys = |v: Vec<CacheFile>| v.into_iter().map(|f| f.key).collect::<Vec<_>>();
+        assert_eq!(keys(pick_evictions(files.clone(), 100)), vec!["old"]);
+        assert_eq!(keys(pick_evictions(files.clone(), 40)), vec!["old", "mid"]);
+        assert!(pick_evictions(files, 120).is_empty());
+    }
+}
@@ -1,61 +1,721 @@
 //! Configuration management
+//!
+//! `config.toml` is the preferred format; a legacy `config.json` is still
+//! read when no TOML file exists, and can be migrated once.
 
-use crate::error::Result;
-use crate::types::Config;
-use crate::utils::paths::{ensure_dir, get_config_dir, get_config_path};
-use std::path::Path;
+use crate::core::downloader::{check_output_template, is_valid_audio_quality, is_valid_rate};
+use crate::core::player::is_valid_cookie_browser;
+use crate::core::youtube::is_valid_proxy;
+use crate::error::{Result, YtChillError};
+use crate::types::{Config, ConfigIssue, ConfigReport, PlayerType, StorageBackend};
+use crate::ui::theme::is_valid_color;
+use crate::utils::paths::{
+    ensure_dir, expand_tilde, find_executable, get_config_dir, get_config_path,
+    get_legacy_config_path,
+};
+use crate::utils::time::parse_duration;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::process::Command;
 
+/// Prefix for per-field environment overrides
+const ENV_PREFIX: &str = "YT_CHILL_";
+
+/// Marker file recording that the user declined the TOML migration
+const MIGRATION_DECLINED_MARKER: &str = ".toml-migration-declined";
+
+/// Comments written above each key when saving TOML
+const FIELD_DOCS: &[(&str, &str)] = &[
+    ("limit", "Number of search results to show"),
+    ("video_mode", "Include video by default (false = audio-only)"),
+    ("download_dir", "Where downloads are saved (empty = ~/Downloads)"),
+    ("max_history_entries", "Maximum number of watch history entries kept"),
+    ("max_search_history", "Maximum number of past search queries kept for ↑ recall (0 = don't keep any)"),
+    ("editor", "Editor used by `yt-chill -e`"),
+    ("player", "Player: \"mpv\", \"vlc\", \"mpd\", \"syncplay\", or a command with {url}/{title}, e.g. \"celluloid {url}\""),
+    ("selector", "Menu selector: \"fzf\", \"builtin\" (no external tools), \"rofi\", \"dmenu\", \"wofi\" or \"dialoguer\""),
+    ("notify", "Show desktop notifications (new uploads, finished downloads)"),
+    ("thumbnail_previews", "Show video thumbnails in the fzf preview pane (kitty, or chafa for sixel and other terminals)"),
+    ("return_to_menu", "After playback or a download, go back to the main menu instead of the list (Esc in a list always goes to the menu)"),
+    ("unified_search", "Search history and YouTube together: past plays that match come first, marked with their play count"),
+    ("yt_dlp_path", "yt-dlp to use, e.g. \"~/.local/bin/yt-dlp\" for a self-updating copy (empty = the one in PATH)"),
+    ("max_resolution", "Tallest video to stream or download in video mode, e.g. 720 or 1080 (0 = no limit)"),
+    ("data_saver", "For metered connections: stream at low bitrate (64 kbps audio, 360p video), skip thumbnails and fetch fewer videos per channel"),
+    ("history_sort", "History menu order: \"recent\" or \"frequent\""),
+    ("storage", "Storage backend: \"json\" or \"sqlite\" (needs the `sqlite` build feature)"),
+    ("blocked_channels", "Channels hidden from search and feed results (case-insensitive names)"),
+    ("blocked_keywords", "Videos whose titles contain any of these words are hidden"),
+    ("min_duration", "Hide videos shorter than this, e.g. \"1h\" for long mixes (empty = no minimum)"),
+    ("max_duration", "Hide videos longer than this, e.g. \"3h\" to skip 10-hour loops (empty = no maximum)"),
+    ("limit_rate", "Maximum download speed, e.g. \"500K\" or \"2M\" (empty = unlimited)"),
+    ("download_jobs", "Videos downloaded at once from a playlist or multi-select (the rate limit applies to each)"),
+    ("external_downloader", "Downloader yt-dlp hands off to, e.g. \"aria2c\" (empty = built-in)"),
+    ("external_downloader_args", "Arguments for the external downloader, e.g. \"-x 16 -s 16 -k 1M\""),
+    ("audio_format", "Audio download codec: \"mp3\", \"opus\", \"m4a\" or \"flac\" (opus avoids transcoding)"),
+    ("audio_quality", "Audio quality: 0 (best) to 10, or a bitrate like \"192K\" (empty = yt-dlp default)"),
+    ("embed_metadata", "Write tags and cover art into downloaded files"),
+    ("title_pattern", "How to split video titles into tags, e.g. \"%(artist)s - %(title)s\" (empty = off)"),
+    ("output_template", "Download filename (yt-dlp template, relative to download_dir; keep [%(id)s] for the library)"),
+    ("organize_downloads", "Sort downloads into folders: \"off\", \"channel\" or \"artist\""),
+    ("mpd_host", "MPD server for player = \"mpd\", e.g. \"localhost:6600\" or \"/run/mpd/socket\" (empty = $MPD_HOST)"),
+    ("mpv_args", "Extra mpv arguments, e.g. [\"--audio-device=pulse/headphones\", \"--cache-secs=60\"]"),
+    ("syncplay_server", "Syncplay server for `--syncplay-host` rooms"),
+    ("serve_token", "Secret the `--serve` API requires as \"Authorization: Bearer <token>\" (empty = none; then only localhost and IP addresses are accepted)"),
+    ("cookies_from_browser", "Use this browser's YouTube login for age-restricted/members-only videos, e.g. \"firefox\""),
+    ("proxy", "Proxy for YouTube requests, yt-dlp and mpv, e.g. \"http://proxy:3128\" or \"socks5h://127.0.0.1:9050\""),
+    ("pre_play_hook", "Shell command run before playback; gets $YT_CHILL_URL, $YT_CHILL_TITLE and $YT_CHILL_ID"),
+    ("now_playing_file", "Text file with the title, artist and elapsed time of what mpv is playing, rewritten every few seconds for OBS text sources (empty = off)"),
+    ("[network]", "Timeouts and retries for YouTube requests"),
+    ("connect_timeout_secs", "Seconds to wait for a connection"),
+    ("timeout_secs", "Seconds to wait for a whole response before giving up"),
+    ("max_attempts", "Tries per request on timeouts, failed connections and 429/5xx responses, with exponential backoff (1 = no retries)"),
+    ("requests_per_minute", "Most YouTube requests per minute, so big feed refreshes don't get you temp-banned (0 = unlimited)"),
+    ("[cache]", "Cache lifetimes in seconds (0 = don't cache that type) and size limit"),
+    ("search_ttl_secs", "Search results"),
+    ("feed_ttl_secs", "Subscription feeds, per channel (`--refresh` fetches them all again)"),
+    ("video_ttl_secs", "Video details from yt-dlp, e.g. `yt-chill info`"),
+    ("max_cache_size_mb", "Size limit for the cache; least recently used entries are evicted beyond it (0 = unlimited)"),
+    ("[selector_args]", "Extra arguments per selector, e.g. rofi = [\"-theme\", \"nord\"] or dmenu = [\"-fn\", \"monospace-12\"]"),
+    ("[alias]", "Saved searches for `yt-chill @name`, e.g. focus = \"lofi hip hop radio beats to study\""),
+    ("[podcast]", "Used with `yt-chill --podcast`"),
+    ("speed", "Playback speed, e.g. 1.5"),
+    ("resume", "Resume episodes where you stopped"),
+    ("long_form", "Only search for videos longer than 20 minutes"),
+    ("chapters", "Show the current chapter while playing (PgUp/PgDn jump between chapters)"),
+    ("[events]", "JSON events (track-started, track-finished, paused, resumed, download-done) for scripts and overlays"),
+    ("command", "Shell command run for each event, with the JSON on stdin and in $YT_CHILL_EVENT (empty = none)"),
+    ("socket", "Unix socket to write each event to as a line of JSON, e.g. one made by `socat UNIX-LISTEN:...` (empty = none)"),
+    ("[theme]", "Icons and colors (colors: names like \"cyan\" or \"bright black\", \"dimmed\" or \"none\")"),
+    ("icons", "Menu icons: \"emoji\", \"nerd\" (Nerd Font glyphs) or \"none\""),
+    ("channel_color", "Channel names in result lists"),
+    ("duration_color", "Durations in result lists"),
+    ("play_count_color", "Replay counts in the history list"),
+    ("live_color", "The LIVE marker on live streams"),
+];
+
+/// On-disk config format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+}
+
+/// Find the config file in use, preferring TOML over legacy JSON
+pub fn find_config_file() -> Option<(PathBuf, ConfigFormat)> {
+    let toml_path = PathBuf::from(get_config_path());
+    if toml_path.exists() {
+        return Some((toml_path, ConfigFormat::Toml));
+    }
+
+    let json_path = PathBuf::from(get_legacy_config_path());
+    if json_path.exists() {
+        return Some((json_path, ConfigFormat::Json));
+    }
+
+    None
+}
+
+/// Parse config file contents in the given format
+fn parse_config(content: &str, format: ConfigFormat) -> Result<Config> {
+    match format {
+        ConfigFormat::Toml => {
+            toml::from_str(content).map_err(|e| YtChillError::InvalidConfig(e.to_string()))
+        }
+        ConfigFormat::Json => Ok(serde_json::from_str(content)?),
+    }
+}
+
+/// Parse config file contents into a generic JSON value
+fn parse_config_value(content: &str, format: ConfigFormat) -> std::result::Result<Value, String> {
+    match format {
+        ConfigFormat::Toml => toml::from_str::<toml::Value>(content)
+            .map_err(|e| e.to_string())
+            .and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string())),
+        ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+    }
+}
+
 /// Load configuration from file, merging with defaults
 pub async fn load_config() -> Result<Config> {
-    let config_path = get_config_path();
+    Ok(load_config_checked().await?.config)
+}
 
-    if !Path::new(&config_path).exists() {
-        return Ok(Config::default());
+/// Load configuration, validating every field.
+/// Precedence: `YT_CHILL_*` env vars > config file > defaults.
+/// Invalid fields fall back to their defaults and are reported as issues.
+pub async fn load_config_checked() -> Result<ConfigReport> {
+    let mut issues = Vec::new();
+    let mut fields = default_fields();
+
+    if let Some((path, format)) = find_config_file() {
+        let content = fs::read_to_string(&path).await?;
+        match parse_config_value(&content, format) {
+            Ok(user) => merge_fields(&mut fields, user, &mut issues),
+            Err(e) => issues.push(ConfigIssue {
+                field: String::new(),
+                problem: format!("{} could not be parsed: {}", path.display(), e),
+                suggestion: Some("Fix the syntax with `yt-chill -e`; using defaults for now".into()),
+            }),
+        }
     }
 
-    let content = fs::read_to_string(&config_path).await?;
-    let user_config: Config = serde_json::from_str(&content)?;
+    apply_env_overrides(&mut fields, &mut issues, |name| std::env::var(name).ok());
 
-    // Merge with defaults (user config takes precedence)
-    let mut config = Config::default();
-    config.limit = user_config.limit;
-    config.video_mode = user_config.video_mode;
-    config.max_history_entries = user_config.max_history_entries;
-    config.editor = user_config.editor;
-    config.player = user_config.player;
-    config.selector = user_config.selector;
-    config.notify = user_config.notify;
+    let mut config: Config = serde_json::from_value(Value::Object(fields)).unwrap_or_default();
+    validate(&mut config, &mut issues);
 
     // Set download_dir with default if empty
-    config.download_dir = if user_config.download_dir.is_empty() {
-        dirs::download_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "~/Downloads".into())
-    } else {
-        user_config.download_dir
-    };
+    if config.download_dir.is_empty() {
+        config.download_dir = default_download_dir();
+    }
+
+    Ok(ConfigReport { config, issues })
+}
+
+/// The config as written in the file, without env overrides or filled-in
+/// runtime defaults, for changing a setting and saving it back
+pub async fn load_saved_config() -> Result<Config> {
+    let mut fields = default_fields();
+    let mut issues = Vec::new();
+
+    if let Some((path, format)) = find_config_file() {
+        let content = fs::read_to_string(&path).await?;
+        // Saving over a file we couldn't read would throw away the user's settings
+        let user = parse_config_value(&content, format)
+            .map_err(|e| YtChillError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+        merge_fields(&mut fields, user, &mut issues);
+    }
 
+    let mut config: Config = serde_json::from_value(Value::Object(fields)).unwrap_or_default();
+    validate(&mut config, &mut issues);
     Ok(config)
 }
 
-/// Save configuration to file
+/// Default download directory (~/Downloads)
+pub fn default_download_dir() -> String {
+    dirs::download_dir()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "~/Downloads".into())
+}
+
+/// Default config as a key/value map
+fn default_fields() -> Map<String, Value> {
+    match serde_json::to_value(Config::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Set one field if the result still deserializes into a valid Config
+fn try_set_field(fields: &mut Map<String, Value>, key: &str, value: Value) -> std::result::Result<(), String> {
+    let mut candidate = fields.clone();
+    candidate.insert(key.to_string(), value.clone());
+    serde_json::from_value::<Config>(Value::Object(candidate)).map_err(|e| e.to_string())?;
+    fields.insert(key.to_string(), value);
+    Ok(())
+}
+
+/// Rewrite settings that were renamed into their current form, so older
+/// config files keep working
+fn upgrade_renamed_keys(user: &mut Value) {
+    // `[network] retries` counted extra tries; `max_attempts` counts all of them
+    if let Some(network) = user.get_mut("network").and_then(Value::as_object_mut)
+        && let Some(retries) = network.remove("retries")
+        && !network.contains_key("max_attempts")
+    {
+        let attempts = retries.as_u64().map_or(retries, |r| Value::from(r.saturating_add(1)));
+        network.insert("max_attempts".into(), attempts);
+    }
+}
+
+/// Apply user values on top of the defaults one field at a time,
+/// so a single bad value doesn't discard the rest of the file
+fn merge_fields(fields: &mut Map<String, Value>, mut user: Value, issues: &mut Vec<ConfigIssue>) {
+    upgrade_renamed_keys(&mut user);
+    let Value::Object(user) = user else {
+        issues.push(ConfigIssue {
+            field: String::new(),
+            problem: "config must be a table of key/value pairs".into(),
+            suggestion: Some("Start over with `yt-chill -e` after deleting the file".into()),
+        });
+        return;
+    };
+
+    let known: Vec<String> = fields.keys().cloned().collect();
+
+    for (key, value) in user {
+        if !fields.contains_key(&key) {
+            let suggestion = closest_match(&key, &known)
+                .map(|k| format!("Did you mean `{}`?", k))
+                .or_else(|| Some(format!("Known keys: {}", known.join(", "))));
+            issues.push(ConfigIssue {
+                field: key,
+                problem: "unknown setting, ignored".into(),
+                suggestion,
+            });
+            continue;
+        }
+
+        if let Err(e) = try_set_field(fields, &key, value.clone()) {
+            issues.push(ConfigIssue {
+                field: key,
+                problem: format!("invalid value {}: {}", value, e),
+                suggestion: Some("Using the default value instead".into()),
+            });
+        }
+    }
+}
+
+/// Override fields from `YT_CHILL_<FIELD>` environment variables,
+/// e.g. `YT_CHILL_LIMIT=30` or `YT_CHILL_DOWNLOAD_DIR=/data`.
+/// Keys inside a section use `YT_CHILL_<SECTION>_<KEY>`, e.g. `YT_CHILL_NETWORK_TIMEOUT_SECS=60`
+fn apply_env_overrides(
+    fields: &mut Map<String, Value>,
+    issues: &mut Vec<ConfigIssue>,
+    lookup: impl Fn(&str) -> Option<String>,
+) {
+    let mut targets: Vec<(String, Option<String>)> = Vec::new();
+    for (key, value) in fields.iter() {
+        match value {
+            Value::Object(section) => {
+                targets.extend(section.keys().map(|sub| (key.clone(), Some(sub.clone()))));
+            }
+            _ => targets.push((key.clone(), None)),
+        }
+    }
+
+    for (key, sub) in targets {
+        let name = match &sub {
+            Some(sub) => format!("{}_{}", key, sub),
+            None => key.clone(),
+        };
+        let var = format!("{}{}", ENV_PREFIX, name.to_uppercase());
+        let Some(raw) = lookup(&var) else {
+            continue;
+        };
+
+        // Numbers and booleans parse as JSON; anything else is taken as a string,
+        // or as a comma-separated list for list settings
+        let parsed = serde_json::from_str(&raw).unwrap_or_else(|_| Value::String(raw.clone()));
+        let list = raw
+            .split(',')
+            .map(|item| Value::String(item.trim().to_string()))
+            .filter(|item| item.as_str() != Some(""))
+            .collect();
+
+        let set = |fields: &mut Map<String, Value>, value: Value| match &sub {
+            Some(sub) => {
+                let mut section = fields.get(&key).cloned().unwrap_or_default();
+                if let Value::Object(map) = &mut section {
+                    map.insert(sub.clone(), value);
+                }
+                try_set_field(fields, &key, section)
+            }
+            None => try_set_field(fields, &key, value),
+        };
+        let applied = set(fields, parsed)
+            .or_else(|_| set(fields, Value::String(raw.clone())))
+            .or_else(|_| set(fields, Value::Array(list)));
+
+        if let Err(e) = applied {
+            issues.push(ConfigIssue {
+                field: var,
+                problem: format!("invalid value {:?}: {}", raw, e),
+                suggestion: Some("Ignoring this environment variable".into()),
+            });
+        }
+    }
+}
+
+/// Semantic checks that serde can't express; offending fields are reset
+fn validate(config: &mut Config, issues: &mut Vec<ConfigIssue>) {
+    let defaults = Config::default();
+
+    if config.limit == 0 {
+        issues.push(ConfigIssue {
+            field: "limit".into(),
+            problem: "must be greater than 0".into(),
+            suggestion: Some(format!("Using {}; set e.g. `limit = 20`", defaults.limit)),
+        });
+        config.limit = defaults.limit;
+    }
+
+    if config.max_history_entries == 0 {
+        issues.push(ConfigIssue {
+            field: "max_history_entries".into(),
+            problem: "must be greater than 0".into(),
+            suggestion: Some(format!("Using {}", defaults.max_history_entries)),
+        });
+        config.max_history_entries = defaults.max_history_entries;
+    }
+
+    let editor_cmd = config.editor.split_whitespace().next().unwrap_or("");
+    if find_executable(editor_cmd).is_none() {
+        let fallback = std::env::var("EDITOR").ok().filter(|e| !e.is_empty());
+
+        // A missing default editor isn't the user's mistake; only report explicit choices
+        if config.editor != defaults.editor {
+            issues.push(ConfigIssue {
+                field: "editor".into(),
+                problem: format!("`{}` was not found in PATH", editor_cmd),
+                suggestion: Some(match &fallback {
+                    Some(e) => format!("Using $EDITOR (`{}`) instead", e),
+                    None => "Install it or set `editor` to one you have, e.g. \"nano\"".into(),
+                }),
+            });
+        }
+        if let Some(e) = fallback {
+            config.editor = e;
+        }
+    }
+
+    if !config.cookies_from_browser.is_empty() && !is_valid_cookie_browser(&config.cookies_from_browser) {
+        issues.push(ConfigIssue {
+            field: "cookies_from_browser".into(),
+            problem: format!("{:?} is not a browser yt-dlp can read", config.cookies_from_browser),
+            suggestion: Some("Use firefox, chrome, chromium, brave, edge, opera, safari, vivaldi or whale".into()),
+        });
+        config.cookies_from_browser = String::new();
+    }
+
+    if !config.proxy.is_empty() && !is_valid_proxy(&config.proxy) {
+        issues.push(ConfigIssue {
+            field: "proxy".into(),
+            problem: format!("{:?} is not a proxy URL", config.proxy),
+            suggestion: Some("Use scheme://host:port with http, https, socks4, socks5 or socks5h; connecting directly for now".into()),
+        });
+        config.proxy = String::new();
+    }
+
+    if config.network.connect_timeout_secs == 0 {
+        issues.push(ConfigIssue {
+            field: "network.connect_timeout_secs".into(),
+            problem: "must be greater than 0".into(),
+            suggestion: Some(format!("Using {}", defaults.network.connect_timeout_secs)),
+        });
+        config.network.connect_timeout_secs = defaults.network.connect_timeout_secs;
+    }
+
+    if config.network.timeout_secs == 0 {
+        issues.push(ConfigIssue {
+            field: "network.timeout_secs".into(),
+            problem: "must be greater than 0".into(),
+            suggestion: Some(format!("Using {}", defaults.network.timeout_secs)),
+        });
+        config.network.timeout_secs = defaults.network.timeout_secs;
+    }
+
+    if config.network.max_attempts == 0 {
+        issues.push(ConfigIssue {
+            field: "network.max_attempts".into(),
+            problem: "must be at least 1".into(),
+            suggestion: Some(format!("Using {}", defaults.network.max_attempts)),
+        });
+        config.network.max_attempts = defaults.network.max_attempts;
+    }
+
+    if !(0.25..=4.0).contains(&config.podcast.speed) {
+        issues.push(ConfigIssue {
+            field: "podcast.speed".into(),
+            problem: format!("{} is outside 0.25 to 4", config.podcast.speed),
+            suggestion: Some(format!("Using {}", defaults.podcast.speed)),
+        });
+        config.podcast.speed = defaults.podcast.speed;
+    }
+
+    let theme_colors = [
+        ("theme.channel_color", &mut config.theme.channel_color, defaults.theme.channel_color.clone()),
+        ("theme.duration_color", &mut config.theme.duration_color, defaults.theme.duration_color.clone()),
+        ("theme.play_count_color", &mut config.theme.play_count_color, defaults.theme.play_count_color.clone()),
+        ("theme.live_color", &mut config.theme.live_color, defaults.theme.live_color.clone()),
+    ];
+    for (field, color, default) in theme_colors {
+        if !is_valid_color(color) {
+            issues.push(ConfigIssue {
+                field: field.into(),
+                problem: format!("{:?} is not a color", color),
+                suggestion: Some(format!("Using {:?}; try red, green, yellow, blue, magenta, cyan, white, \"bright black\", dimmed or none", default)),
+            });
+            *color = default;
+        }
+    }
+
+    if let PlayerType::Custom(ref template) = config.player {
+        let cmd = template.split_whitespace().next().unwrap_or("");
+        if find_executable(cmd).is_none() {
+            issues.push(ConfigIssue {
+                field: "player".into(),
+                problem: format!("`{}` was not found in PATH", cmd),
+                suggestion: Some("Use \"mpv\", \"vlc\", \"syncplay\" or a command template like \"vlc {url}\"; using mpv for now".into()),
+            });
+            config.player = PlayerType::Mpv;
+        }
+    }
+
+    if config.storage == StorageBackend::Sqlite && !cfg!(feature = "sqlite") {
+        issues.push(ConfigIssue {
+            field: "storage".into(),
+            problem: "this build doesn't include SQLite support".into(),
+            suggestion: Some("Reinstall with `cargo install yt-chill --features sqlite`; using JSON for now".into()),
+        });
+        config.storage = StorageBackend::Json;
+    }
+
+    if !config.download_dir.is_empty() {
+        let dir = PathBuf::from(expand_tilde(&config.download_dir));
+        let problem = match std::fs::metadata(&dir) {
+            Ok(meta) if !meta.is_dir() => Some("exists but is not a directory"),
+            Ok(meta) if meta.permissions().readonly() => Some("is not writable"),
+            Ok(_) => None,
+            // Missing directories are created on first download
+            Err(_) => None,
+        };
+
+        if let Some(problem) = problem {
+            issues.push(ConfigIssue {
+                field: "download_dir".into(),
+                problem: format!("{} {}", dir.display(), problem),
+                suggestion: Some(format!("Using {} instead", default_download_dir())),
+            });
+            config.download_dir = String::new();
+        }
+    }
+
+    for (field, value) in [("min_duration", &mut config.min_duration), ("max_duration", &mut config.max_duration)] {
+        if !value.is_empty() && parse_duration(value).is_none() {
+            issues.push(ConfigIssue {
+                field: field.into(),
+                problem: format!("{:?} is not a duration", value),
+                suggestion: Some("Use e.g. \"10m\", \"1h\" or \"1h30m\" (a bare number is minutes); not filtering for now".into()),
+            });
+            value.clear();
+        }
+    }
+
+    if !config.limit_rate.is_empty() && !is_valid_rate(&config.limit_rate) {
+        issues.push(ConfigIssue {
+            field: "limit_rate".into(),
+            problem: format!("{:?} is not a rate", config.limit_rate),
+            suggestion: Some("Use bytes per second with an optional K/M/G suffix, e.g. \"500K\"; downloading unlimited for now".into()),
+        });
+        config.limit_rate = String::new();
+    }
+
+    if config.download_jobs == 0 {
+        issues.push(ConfigIssue {
+            field: "download_jobs".into(),
+            problem: "must be at least 1".into(),
+            suggestion: Some("Downloading one at a time for now".into()),
+        });
+        config.download_jobs = 1;
+    }
+
+    if !config.yt_dlp_path.is_empty() && find_executable(&config.yt_dlp_path).is_none() {
+        issues.push(ConfigIssue {
+            field: "yt_dlp_path".into(),
+            problem: format!("{} was not found", config.yt_dlp_path),
+            suggestion: Some("Point it at the yt-dlp binary; using the one in PATH for now".into()),
+        });
+        config.yt_dlp_path = String::new();
+    }
+
+    if !config.now_playing_file.is_empty() && Path::new(&expand_tilde(&config.now_playing_file)).is_dir() {
+        issues.push(ConfigIssue {
+            field: "now_playing_file".into(),
+            problem: format!("{} is a folder", config.now_playing_file),
+            suggestion: Some("Give a file path, e.g. \"~/obs/now-playing.txt\"; not writing one for now".into()),
+        });
+        config.now_playing_file = String::new();
+    }
+
+    if !config.external_downloader.is_empty() && find_executable(&config.external_downloader).is_none() {
+        issues.push(ConfigIssue {
+            field: "external_downloader".into(),
+            problem: format!("`{}` was not found in PATH", config.external_downloader),
+            suggestion: Some("Install it (e.g. `brew install aria2`); using yt-dlp's built-in downloader for now".into()),
+        });
+        config.external_downloader = String::new();
+    }
+
+    if !config.audio_quality.is_empty() && !is_valid_audio_quality(&config.audio_quality) {
+        issues.push(ConfigIssue {
+            field: "audio_quality".into(),
+            problem: format!("{:?} is not an audio quality", config.audio_quality),
+            suggestion: Some("Use 0 (best) to 10, or a bitrate like \"192K\"; using yt-dlp's default".into()),
+        });
+        config.audio_quality = String::new();
+    }
+
+    let pattern = &config.title_pattern;
+    let has_placeholder = pattern.contains("%(") && pattern.contains(")s");
+    if !pattern.is_empty() && !has_placeholder {
+        issues.push(ConfigIssue {
+            field: "title_pattern".into(),
+            problem: format!("{:?} has no %(field)s placeholders", pattern),
+            suggestion: Some(format!("Use e.g. {:?}; using the default for now", defaults.title_pattern)),
+        });
+        config.title_pattern = defaults.title_pattern.clone();
+    }
+
+    if let Err(problem) = check_output_template(&config.output_template) {
+        issues.push(ConfigIssue {
+            field: "output_template".into(),
+            problem: format!("{:?} {}", config.output_template, problem),
+            suggestion: Some(format!("Using {:?}", defaults.output_template)),
+        });
+        config.output_template = defaults.output_template.clone();
+    }
+}
+
+/// Closest known key by edit distance, if it's plausibly a typo
+fn closest_match<'a>(input: &str, candidates: &'a [String]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(input, c), c))
+        .filter(|(d, _)| *d <= 3)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c.as_str())
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+/// Serialize config as TOML with a comment above each documented key
+pub fn to_commented_toml(config: &Config) -> Result<String> {
+    let body = toml::to_string_pretty(config)
+        .map_err(|e| YtChillError::InvalidConfig(e.to_string()))?;
+
+    let mut out = String::from("# yt-chill configuration\n\n");
+    for line in body.lines() {
+        let key = line.split('=').next().unwrap_or("").trim();
+        if let Some((_, doc)) = FIELD_DOCS.iter().find(|(k, _)| *k == key) {
+            out.push_str(&format!("# {}\n", doc));
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Save configuration to file (always TOML)
 pub async fn save_config(config: &Config) -> Result<()> {
     ensure_dir(&get_config_dir()).await?;
-    let content = serde_json::to_string_pretty(config)?;
-    fs::write(get_config_path(), content).await?;
+    fs::write(get_config_path(), to_commented_toml(config)?).await?;
     Ok(())
 }
 
-/// Open config file in editor
-pub async fn edit_config(editor: &str) -> Result<()> {
-    let config_path = get_config_path();
+/// Change one top-level setting in the config file, leaving the rest of it
+/// (comments, keys we don't know, values we couldn't use) as it was
+pub async fn save_setting(config: &Config, key: &str) -> Result<()> {
+    let value = match serde_json::to_value(config)? {
+        Value::Object(mut fields) => fields.remove(key),
+        _ => None,
+    }
+    .ok_or_else(|| YtChillError::InvalidConfig(format!("there's no setting called {}", key)))?;
 
-    // Ensure config file exists
-    if !Path::new(&config_path).exists() {
-        save_config(&Config::default()).await?;
+    let Some((path, format)) = find_config_file() else {
+        return save_config(config).await;
+    };
+    let content = fs::read_to_string(&path).await?;
+    let updated = match format {
+        ConfigFormat::Toml => set_toml_key(&content, key, &value),
+        ConfigFormat::Json => set_json_key(&content, key, value),
     }
+    .map_err(|e| YtChillError::InvalidConfig(format!("{}: {}", path.display(), e)))?;
+    fs::write(&path, updated).await?;
+    Ok(())
+}
+
+/// Set a top-level key in a TOML document, keeping its comments and layout
+fn set_toml_key(content: &str, key: &str, value: &Value) -> std::result::Result<String, String> {
+    let mut doc: toml_edit::DocumentMut = content.parse().map_err(|e: toml_edit::TomlError| e.to_string())?;
+    let mut new = match value {
+        Value::String(s) => toml_edit::Value::from(s.as_str()),
+        Value::Bool(b) => toml_edit::Value::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml_edit::Value::from(i),
+            None => toml_edit::Value::from(n.as_f64().unwrap_or_default()),
+        },
+        _ => return Err(format!("{} can't be set from the settings menu", key)),
+    };
+    match doc.get_mut(key).and_then(|item| item.as_value_mut()) {
+        Some(old) => {
+            // Keeps a trailing comment like `selector = "fzf"  # or builtin`
+            *new.decor_mut() = old.decor().clone();
+            *old = new;
+        }
+        None => {
+            doc.insert(key, toml_edit::Item::Value(new));
+        }
+    }
+    Ok(doc.to_string())
+}
+
+/// Set a top-level key in a legacy config.json, keeping the other keys
+fn set_json_key(content: &str, key: &str, value: Value) -> std::result::Result<String, String> {
+    let mut doc: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    doc.as_object_mut()
+        .ok_or_else(|| "expected a JSON object".to_string())?
+        .insert(key.to_string(), value);
+    serde_json::to_string_pretty(&doc).map_err(|e| e.to_string())
+}
+
+/// Whether a legacy JSON config exists that hasn't been migrated or declined
+pub fn needs_migration() -> bool {
+    matches!(find_config_file(), Some((_, ConfigFormat::Json)))
+        && !Path::new(&get_config_dir())
+            .join(MIGRATION_DECLINED_MARKER)
+            .exists()
+}
+
+/// Convert config.json to config.toml, keeping the old file as config.json.bak
+pub async fn migrate_to_toml() -> Result<PathBuf> {
+    let json_path = get_legacy_config_path();
+    let content = fs::read_to_string(&json_path).await?;
+    let config = parse_config(&content, ConfigFormat::Json)?;
+
+    save_config(&config).await?;
+    fs::rename(&json_path, format!("{}.bak", json_path)).await?;
+
+    Ok(PathBuf::from(get_config_path()))
+}
+
+/// Remember that the user doesn't want to migrate, so we only ask once
+pub async fn decline_migration() -> Result<()> {
+    ensure_dir(&get_config_dir()).await?;
+    fs::write(
+        Path::new(&get_config_dir()).join(MIGRATION_DECLINED_MARKER),
+        "",
+    )
+    .await?;
+    Ok(())
+}
+
+/// Open config file in editor
+pub async fn edit_config(editor: &str) -> Result<()> {
+    // Ensure a config file exists (new configs are written as TOML)
+    let config_path = match find_config_file() {
+        Some((path, _)) => path,
+        None => {
+            save_config(&Config::default()).await?;
+            PathBuf::from(get_config_path())
+        }
+    };
 
     Command::new(editor)
         .arg(&config_path)
@@ -64,3 +724,116 @@ pub async fn edit_config(editor: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commented_toml_round_trips() {
+        let config = Config::default();
+        let content = to_commented_toml(&config).unwrap();
+        assert!(content.contains("# Number of search results to show\nlimit = 15"));
+        assert!(content.contains("[network]\n# Seconds to wait for a connection\nconnect_timeout_secs = 10"));
+
+        let parsed = parse_config(&content, ConfigFormat::Toml).unwrap();
+        assert_eq!(parsed.limit, config.limit);
+        assert_eq!(parsed.selector, config.selector);
+    }
+
+    #[test]
+    fn test_set_toml_key_keeps_the_rest() {
+        let content = "# my setup\nselector = \"fzf\"  # fast\nlimt = 30\nlimit = \"lots\"\n\n[network]\ntimeout_secs = 5\n";
+        let updated = set_toml_key(content, "selector", &Value::from("builtin")).unwrap();
+        assert_eq!(
+            updated,
+            "# my setup\nselector = \"builtin\"  # fast\nlimt = 30\nlimit = \"lots\"\n\n[network]\ntimeout_secs = 5\n"
+        );
+
+        // Missing keys go with the other top-level settings, not under [network]
+        let updated = set_toml_key(content, "notify", &Value::from(false)).unwrap();
+        let parsed: toml::Value = toml::from_str(&updated).unwrap();
+        assert_eq!(parsed["notify"].as_bool(), Some(false));
+        assert_eq!(parsed["limt"].as_integer(), Some(30));
+        assert!(parsed["network"].get("notify").is_none());
+
+        assert!(set_toml_key("not = [toml", "limit", &Value::from(5)).is_err());
+    }
+
+    #[test]
+    fn test_set_json_key_keeps_the_rest() {
+        let updated = set_json_key(r#"{"limit": 5, "custom": true}"#, "limit", Value::from(20)).unwrap();
+        let parsed: Value = serde_json::from_str(&updated).unwrap();
+        assert_eq!(parsed["limit"], 20);
+        assert_eq!(parsed["custom"], true);
+    }
+
+    #[test]
+    fn test_selector_args_table() {
+        use crate::types::SelectorType;
+        let content = "selector = \"rofi\"\n\n[selector_args]\nrofi = [\"-theme\", \"nord\"]\n";
+        let config = parse_config(content, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.selector, SelectorType::Rofi);
+        assert_eq!(config.selector_args["rofi"], vec!["-theme", "nord"]);
+    }
+
+    #[test]
+    fn test_invalid_fields_fall_back_per_field() {
+        let user = serde_json::json!({
+            "limit": 30,
+            "selector": "fzz",
+            "limt": 5,
+        });
+        let mut issues = Vec::new();
+        let mut fields = default_fields();
+        merge_fields(&mut fields, user, &mut issues);
+        let config: Config = serde_json::from_value(Value::Object(fields)).unwrap();
+
+        assert_eq!(config.limit, 30);
+        assert_eq!(config.selector, Config::default().selector);
+        assert_eq!(issues.len(), 2);
+        let typo = issues.iter().find(|i| i.field == "limt").unwrap();
+        assert_eq!(typo.suggestion.as_deref(), Some("Did you mean `limit`?"));
+    }
+
+    #[test]
+    fn test_retries_is_read_as_max_attempts() {
+        let mut fields = default_fields();
+        let mut issues = Vec::new();
+        merge_fields(&mut fields, serde_json::json!({ "network": { "retries": 4 } }), &mut issues);
+        let config: Config = serde_json::from_value(Value::Object(fields)).unwrap();
+        assert_eq!(config.network.max_attempts, 5);
+        assert!(issues.is_empty());
+
+        // The new key wins when both are there
+        let mut fields = default_fields();
+        let user = serde_json::json!({ "network": { "retries": 4, "max_attempts": 2 } });
+        merge_fields(&mut fields, user, &mut issues);
+        let config: Config = serde_json::from_value(Value::Object(fields)).unwrap();
+        assert_eq!(config.network.max_attempts, 2);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut fields = default_fields();
+        let mut issues = Vec::new();
+        apply_env_overrides(&mut fields, &mut issues, |name| match name {
+            "YT_CHILL_LIMIT" => Some("40".into()),
+            "YT_CHILL_DOWNLOAD_DIR" => Some("/data/music".into()),
+            "YT_CHILL_NOTIFY" => Some("maybe".into()),
+            "YT_CHILL_BLOCKED_KEYWORDS" => Some("reaction, prank".into()),
+            "YT_CHILL_NETWORK_TIMEOUT_SECS" => Some("60".into()),
+            _ => None,
+        });
+        let config: Config = serde_json::from_value(Value::Object(fields)).unwrap();
+
+        assert_eq!(config.limit, 40);
+        assert_eq!(config.download_dir, "/data/music");
+        assert!(config.notify);
+        assert_eq!(config.blocked_keywords, vec!["reaction", "prank"]);
+        assert_eq!(config.network.timeout_secs, 60);
+        assert_eq!(config.network.max_attempts, 3);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "YT_CHILL_NOTIFY");
+    }
+}
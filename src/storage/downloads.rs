@@ -0,0 +1,92 @@
+//! Download archive: what has been downloaded and where it ended up
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::types::{DownloadRecord, PendingDownload};
+use crate::utils::paths::{ensure_dir, get_downloads_path, get_pending_downloads_path, get_state_dir};
+use std::path::Path;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// Held while the archive or pending list is rewritten, so parallel
+/// downloads finishing together don't drop each other's records
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Load the download archive, most recent first
+pub async fn load_downloads() -> Result<Vec<DownloadRecord>> {
+    read_json(&get_downloads_path()).await
+}
+
+/// Save the download archive
+pub async fn save_downloads(downloads: &[DownloadRecord]) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(downloads)?;
+    fs::write(get_downloads_path(), content).await?;
+    Ok(())
+}
+
+/// A previous download of this video that's still on disk
+pub async fn find_download(id: &str, video: bool) -> Result<Option<DownloadRecord>> {
+    Ok(load_downloads()
+        .await?
+        .into_iter()
+        .find(|d| d.id == id && d.video == video && Path::new(&d.path).exists()))
+}
+
+/// Remember a finished download, replacing any older record for the same video
+pub async fn record_download(record: DownloadRecord) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut downloads = load_downloads().await?;
+    downloads.retain(|d| !(d.id == record.id && d.video == record.video));
+    downloads.insert(0, record);
+    save_downloads(&downloads).await
+}
+
+/// Load downloads that were started but never finished, oldest first
+pub async fn load_pending() -> Result<Vec<PendingDownload>> {
+    read_json(&get_pending_downloads_path()).await
+}
+
+/// Save the pending download list
+async fn save_pending(pending: &[PendingDownload]) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(pending)?;
+    fs::write(get_pending_downloads_path(), content).await?;
+    Ok(())
+}
+
+/// Note that a download has started (keeps the original start time on retries)
+pub async fn mark_pending(download: PendingDownload) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut pending = load_pending().await?;
+    let same = |p: &PendingDownload| {
+        p.video.id == download.video.id && p.include_video == download.include_video
+    };
+    if !pending.iter().any(same) {
+        pending.push(download);
+        save_pending(&pending).await?;
+    }
+    Ok(())
+}
+
+/// Note that a download has finished
+pub async fn clear_pending(id: &str, include_video: bool) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut pending = load_pending().await?;
+    let before = pending.len();
+    pending.retain(|p| !(p.video.id == id && p.include_video == include_video));
+    if pending.len() != before {
+        save_pending(&pending).await?;
+    }
+    Ok(())
+}
+
+/// Forget every unfinished download, returning how many there were
+pub async fn clear_all_pending() -> Result<usize> {
+    let _guard = WRITE_LOCK.lock().await;
+    let count = load_pending().await?.len();
+    if count > 0 {
+        save_pending(&[]).await?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,76 @@
+//! Starred videos with tags
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::types::{Favorite, Video};
+use crate::utils::paths::{ensure_dir, get_favorites_path, get_state_dir};
+use chrono::Utc;
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// Held while favorites are rewritten, so two changes at once don't drop
+/// each other's
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Load favorites, most recently starred first
+pub async fn load_favorites() -> Result<Vec<Favorite>> {
+    read_json(&get_favorites_path()).await
+}
+
+/// Save favorites to file
+pub async fn save_favorites(favorites: &[Favorite]) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(favorites)?;
+    fs::write(get_favorites_path(), content).await?;
+    Ok(())
+}
+
+/// Star a video, merging tags if it's already a favorite
+pub async fn add_favorite(video: &Video, tags: &[String]) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut favorites = load_favorites().await?;
+
+    match favorites.iter_mut().find(|f| f.video.id == video.id) {
+        Some(existing) => {
+            for tag in tags {
+                if !existing.tags.contains(tag) {
+                    existing.tags.push(tag.clone());
+                }
+            }
+        }
+        None => favorites.insert(
+            0,
+            Favorite {
+                video: video.clone(),
+                tags: tags.to_vec(),
+                added: Utc::now().timestamp(),
+            },
+        ),
+    }
+
+    save_favorites(&favorites).await
+}
+
+/// Unstar a video by ID
+pub async fn remove_favorite(id: &str) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut favorites = load_favorites().await?;
+    favorites.retain(|f| f.video.id != id);
+    save_favorites(&favorites).await
+}
+
+/// All tags in use, sorted
+pub fn all_tags(favorites: &[Favorite]) -> Vec<String> {
+    let mut tags: Vec<String> = favorites.iter().flat_map(|f| f.tags.clone()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Favorites carrying `tag` (all favorites if None)
+pub fn with_tag<'a>(favorites: &'a [Favorite], tag: Option<&str>) -> Vec<&'a Favorite> {
+    favorites
+        .iter()
+        .filter(|f| tag.is_none_or(|t| f.tags.iter().any(|ft| ft == t)))
+        .collect()
+}
@@ -0,0 +1,29 @@
+//! Recent uploads per subscribed channel, with when each was fetched, so the
+//! feed only refetches channels that have gone stale
+
+use crate::error::Result;
+use crate::types::ChannelFeed;
+use crate::utils::paths::{ensure_dir, get_cache_dir, get_feed_cache_path};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Channel handle -> its last fetch
+pub type FeedCache = HashMap<String, ChannelFeed>;
+
+/// Load the feed cache (empty if missing or unreadable)
+pub async fn load_feed_cache() -> FeedCache {
+    let path = PathBuf::from(get_feed_cache_path());
+    match fs::read(&path).await {
+        Ok(content) => super::cache::decode(&content).unwrap_or_default(),
+        Err(_) => FeedCache::new(),
+    }
+}
+
+/// Save the feed cache
+pub async fn save_feed_cache(cache: &FeedCache) -> Result<()> {
+    ensure_dir(&get_cache_dir()).await?;
+    let content = super::cache::encode(cache)?;
+    fs::write(get_feed_cache_path(), content).await?;
+    Ok(())
+}
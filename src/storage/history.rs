@@ -1,9 +1,17 @@
 //! Watch history management
 
+use crate::core::player::build_video_url;
 use crate::error::Result;
-use crate::types::{HistoryEntry, Video};
-use crate::utils::paths::ensure_dir;
-use chrono::Utc;
+use crate::storage::read_json;
+#[cfg(feature = "sqlite")]
+use crate::storage::sqlite::{HISTORY_IMPORT, SqliteStore};
+#[cfg(feature = "sqlite")]
+use crate::types::StorageBackend;
+use crate::types::{HistoryEntry, HistorySort, MergeStats, Video};
+use crate::utils::fuzzy::fuzzy_score;
+use crate::utils::paths::{ensure_dir, get_history_path, get_legacy_history_path};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tokio::fs;
 
@@ -23,20 +31,60 @@ impl History {
         }
     }
 
-    /// Load history from file
+    /// Load history from file (or the database, with the SQLite backend)
     pub async fn load(&mut self) -> Result<()> {
-        if !self.path.exists() {
-            self.entries = Vec::new();
+        #[cfg(feature = "sqlite")]
+        if super::backend() == StorageBackend::Sqlite {
+            let mut store = SqliteStore::open_default().await?;
+            self.load_store(&mut store).await?;
+            self.backfill_first_played();
             return Ok(());
         }
 
-        let content = fs::read_to_string(&self.path).await?;
-        self.entries = serde_json::from_str(&content).unwrap_or_default();
+        self.load_json().await?;
+        self.backfill_first_played();
         Ok(())
     }
 
-    /// Save history to file
+    /// Load history from the database, seeding it from the JSON file once.
+    /// An empty table isn't a sign of a missing import: it's what `clear` leaves.
+    #[cfg(feature = "sqlite")]
+    async fn load_store(&mut self, store: &mut SqliteStore) -> Result<()> {
+        if !store.is_migrated(HISTORY_IMPORT)? {
+            // Databases from before the marker existed already hold the import
+            if !store.has_history()? {
+                self.load_json().await?;
+                store.save_history(&self.entries)?;
+            }
+            store.mark_migrated(HISTORY_IMPORT)?;
+        }
+
+        self.entries = store.load_history()?;
+        Ok(())
+    }
+
+    /// Entries written before play tracking existed only have `timestamp`
+    fn backfill_first_played(&mut self) {
+        for entry in &mut self.entries {
+            if entry.first_played == 0 {
+                entry.first_played = entry.timestamp;
+            }
+        }
+    }
+
+    /// Load history from the JSON file
+    async fn load_json(&mut self) -> Result<()> {
+        self.entries = read_json(&self.path.to_string_lossy()).await?;
+        Ok(())
+    }
+
+    /// Save history to file (or the database, with the SQLite backend)
     pub async fn save(&self) -> Result<()> {
+        #[cfg(feature = "sqlite")]
+        if super::backend() == StorageBackend::Sqlite {
+            return SqliteStore::open_default().await?.save_history(&self.entries);
+        }
+
         if let Some(parent) = self.path.parent() {
             ensure_dir(&parent.to_string_lossy()).await?;
         }
@@ -45,17 +93,29 @@ impl History {
         Ok(())
     }
 
-    /// Add video to history
+    /// Add video to history, bumping its play count if it's a replay
     pub async fn add(&mut self, video: &Video) -> Result<()> {
-        let entry = HistoryEntry {
-            video: video.clone(),
-            timestamp: Utc::now().timestamp(),
-        };
+        let now = Utc::now().timestamp();
 
-        // Remove any existing entry with the same ID
-        self.entries.retain(|e| e.video.id != video.id);
+        let entry = match self.entries.iter().position(|e| e.video.id == video.id) {
+            Some(index) => {
+                let previous = self.entries.remove(index);
+                HistoryEntry {
+                    video: video.clone(),
+                    timestamp: now,
+                    play_count: previous.play_count + 1,
+                    first_played: previous.first_played,
+                }
+            }
+            None => HistoryEntry {
+                video: video.clone(),
+                timestamp: now,
+                play_count: 1,
+                first_played: now,
+            },
+        };
 
-        // Add new entry at the beginning
+        // Most recent entry goes first
         self.entries.insert(0, entry);
 
         // Trim to max entries
@@ -66,18 +126,279 @@ impl History {
         self.save().await
     }
 
+    /// Merge imported entries by video ID, keeping the newest timestamps
+    pub async fn merge(&mut self, imported: Vec<HistoryEntry>) -> Result<MergeStats> {
+        let mut stats = MergeStats::default();
+        // Video ID -> its entry, so large imports don't rescan history per entry
+        let mut index: HashMap<String, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.video.id.clone(), i))
+            .collect();
+
+        for incoming in imported {
+            match index.get(&incoming.video.id).map(|&i| &mut self.entries[i]) {
+                Some(existing) => {
+                    let mut changed = false;
+                    if incoming.timestamp > existing.timestamp {
+                        existing.timestamp = incoming.timestamp;
+                        if !incoming.video.title.is_empty() {
+                            existing.video = incoming.video;
+                        }
+                        changed = true;
+                    }
+                    if incoming.first_played > 0 && incoming.first_played < existing.first_played {
+                        existing.first_played = incoming.first_played;
+                        changed = true;
+                    }
+                    // The same plays may be in both files, so don't add counts together
+                    if incoming.play_count > existing.play_count {
+                        existing.play_count = incoming.play_count;
+                        changed = true;
+                    }
+                    if changed {
+                        stats.updated += 1;
+                    }
+                }
+                None => {
+                    index.insert(incoming.video.id.clone(), self.entries.len());
+                    self.entries.push(incoming);
+                    stats.added += 1;
+                }
+            }
+        }
+
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        self.entries.truncate(self.max_entries);
+        self.save().await?;
+
+        Ok(stats)
+    }
+
     /// Get all history entries
     pub fn get_all(&self) -> &[HistoryEntry] {
         &self.entries
     }
 
+    /// Entries in the requested order
+    pub fn sorted(&self, sort: HistorySort) -> Vec<&HistoryEntry> {
+        let mut entries: Vec<&HistoryEntry> = self.entries.iter().collect();
+        if sort == HistorySort::Frequent {
+            // Stable sort keeps recency order among equal counts
+            entries.sort_by_key(|e| std::cmp::Reverse(e.play_count));
+        }
+        entries
+    }
+
+    /// Fuzzy-search title and author, best matches first
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        let mut matches: Vec<(i64, &HistoryEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|e| {
+                let haystack = format!("{} {}", e.video.title, e.video.author);
+                fuzzy_score(query, &haystack).map(|score| (score, e))
+            })
+            .collect();
+
+        // Stable sort keeps recency order among equal scores
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.into_iter().map(|(_, e)| e).collect()
+    }
+
+    /// The entry for a video, if it's been played
+    pub fn get(&self, id: &str) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|e| e.video.id == id)
+    }
+
+    /// Up to `max_hits` history matches for `query`, followed by the
+    /// YouTube results that aren't among them
+    pub fn merge_search(&self, query: &str, videos: Vec<Video>, max_hits: usize) -> Vec<Video> {
+        let mut merged: Vec<Video> = self
+            .search(query)
+            .into_iter()
+            .take(max_hits)
+            .map(|e| e.video.clone())
+            .collect();
+        for video in videos {
+            if !merged.iter().any(|v| v.id == video.id) {
+                merged.push(video);
+            }
+        }
+        merged
+    }
+
+    /// Render all entries as CSV (header row included)
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "id,title,author,duration,views,published,url,play_count,first_played,last_played\n",
+        );
+
+        for e in &self.entries {
+            let fields = [
+                e.video.id.clone(),
+                e.video.title.clone(),
+                e.video.author.clone(),
+                e.video.duration.clone(),
+                e.video.views.clone(),
+                e.video.published.clone(),
+                build_video_url(&e.video.id),
+                e.play_count.to_string(),
+                to_rfc3339(e.first_played),
+                to_rfc3339(e.timestamp),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_escape(f)).collect();
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Remove entries by video ID, returning how many were removed
+    pub async fn remove(&mut self, ids: &[String]) -> Result<usize> {
+        let before = self.entries.len();
+        self.entries.retain(|e| !ids.contains(&e.video.id));
+        self.save().await?;
+        Ok(before - self.entries.len())
+    }
+
+    /// Remove entries last played before `cutoff` (unix timestamp)
+    pub async fn prune_older_than(&mut self, cutoff: i64) -> Result<usize> {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.timestamp >= cutoff);
+        self.save().await?;
+        Ok(before - self.entries.len())
+    }
+
     /// Clear all history
-    #[allow(dead_code)]
     pub async fn clear(&mut self) -> Result<()> {
         self.entries.clear();
+
+        #[cfg(feature = "sqlite")]
+        if super::backend() == StorageBackend::Sqlite {
+            return self.save().await;
+        }
+
         if self.path.exists() {
             fs::remove_file(&self.path).await?;
         }
         Ok(())
     }
 }
+
+/// Move history from the cache dir (where older versions kept it) to the state dir.
+/// Returns true if a file was migrated.
+pub async fn migrate_legacy_history() -> Result<bool> {
+    let legacy = PathBuf::from(get_legacy_history_path());
+    let current = PathBuf::from(get_history_path());
+
+    if !legacy.exists() || current.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = current.parent() {
+        ensure_dir(&parent.to_string_lossy()).await?;
+    }
+
+    // rename fails across filesystems, so fall back to copy + remove
+    if fs::rename(&legacy, &current).await.is_err() {
+        fs::copy(&legacy, &current).await?;
+        fs::remove_file(&legacy).await?;
+    }
+
+    Ok(true)
+}
+
+/// Quote a CSV field if it contains separators, quotes or newlines
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Unix timestamp as an RFC 3339 UTC string
+fn to_rfc3339(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str, title: &str) -> Video {
+        Video {
+            id: id.into(),
+            title: title.into(),
+            author: "Someone".into(),
+            duration: "3:00".into(),
+            duration_secs: Some(180),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_search() {
+        let mut history = History::new("/nonexistent/history.json", 100);
+        history.entries = [video("a", "lofi beats to study to"), video("b", "jazz for rainy days")]
+            .into_iter()
+            .map(|video| HistoryEntry { video, timestamp: 0, play_count: 2, first_played: 0 })
+            .collect();
+
+        let found = vec![video("c", "lofi radio"), video("a", "lofi beats to study to")];
+        let merged = history.merge_search("lofi", found, 5);
+        let ids: Vec<&str> = merged.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["a", "c"]);
+        assert_eq!(history.get("a").map(|e| e.play_count), Some(2));
+        assert!(history.get("c").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_merge_matches_by_id() {
+        let path = std::env::temp_dir().join(format!("yt-chill-merge-{}.json", std::process::id()));
+        let mut history = History::new(&path.to_string_lossy(), 100);
+        let entry = |id: &str, timestamp: i64, play_count: u32| HistoryEntry {
+            video: video(id, id),
+            timestamp,
+            play_count,
+            first_played: timestamp,
+        };
+        history.entries = vec![entry("a", 100, 3)];
+
+        // "b" twice in one import: added once, then updated from the later entry
+        let stats = history.merge(vec![entry("a", 200, 1), entry("b", 50, 1), entry("b", 80, 2)]).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!((stats.added, stats.updated), (1, 2));
+        let ids: Vec<&str> = history.get_all().iter().map(|e| e.video.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(history.get("a").map(|e| (e.timestamp, e.play_count)), Some((200, 3)));
+        assert_eq!(history.get("b").map(|e| (e.timestamp, e.first_played, e.play_count)), Some((80, 50, 2)));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_cleared_history_stays_cleared() {
+        let path = std::env::temp_dir().join(format!("yt-chill-import-{}.json", std::process::id()));
+        let entries = vec![HistoryEntry { video: video("a", "lofi"), timestamp: 1, play_count: 1, first_played: 1 }];
+        std::fs::write(&path, serde_json::to_string(&entries).unwrap()).unwrap();
+        let mut store = SqliteStore::open(std::path::Path::new(":memory:")).unwrap();
+
+        let mut history = History::new(&path.to_string_lossy(), 100);
+        history.load_store(&mut store).await.unwrap();
+        assert_eq!(history.get_all().len(), 1);
+
+        // What `clear` does with the SQLite backend; history.json is still there
+        store.save_history(&[]).unwrap();
+        let mut history = History::new(&path.to_string_lossy(), 100);
+        history.load_store(&mut store).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(history.get_all().is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! Parsers for data exported by yt-chill and other YouTube clients
+
+use crate::error::{Result, YtChillError};
+use crate::types::{HistoryEntry, Subscription, Video};
+use crate::utils::time::format_clock;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Parse a watch-history file: yt-chill JSON/CSV exports,
+/// FreeTube `history.db` (one JSON object per line) or NewPipe-style JSON
+pub fn parse_history(content: &str) -> Result<Vec<HistoryEntry>> {
+    let trimmed = content.trim_start();
+
+    let values: Vec<Value> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed).map_err(|e| YtChillError::Parse(format!("history: {}", e)))?
+    } else if trimmed.starts_with('{') {
+        // A single object wrapping a list, or newline-delimited JSON
+        match serde_json::from_str::<Value>(trimmed) {
+            Ok(Value::Object(obj)) => obj
+                .values()
+                .find_map(|v| v.as_array().cloned())
+                .unwrap_or_else(|| vec![Value::Object(obj)]),
+            _ => trimmed
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .filter_map(|l| serde_json::from_str(l).ok())
+                .collect(),
+        }
+    } else {
+        return parse_history_csv(content);
+    };
+
+    Ok(fold_repeats(values.iter().filter_map(history_entry_from_json).collect()))
+}
+
+/// Combine repeated watches of the same video (Takeout lists every view)
+/// into one entry with a play count and first/last played times
+fn fold_repeats(entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
+    let mut folded: Vec<HistoryEntry> = Vec::new();
+    // Video ID -> its entry in `folded`
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for entry in entries {
+        match index.get(&entry.video.id).map(|&i| &mut folded[i]) {
+            Some(existing) => {
+                existing.play_count += entry.play_count;
+                existing.first_played = existing.first_played.min(entry.first_played);
+                if entry.timestamp > existing.timestamp {
+                    existing.timestamp = entry.timestamp;
+                }
+            }
+            None => {
+                index.insert(entry.video.id.clone(), folded.len());
+                folded.push(entry);
+            }
+        }
+    }
+
+    folded
+}
+
+/// Build a history entry from any of the supported JSON shapes
+fn history_entry_from_json(value: &Value) -> Option<HistoryEntry> {
+    let id = str_field(value, &["videoId", "id"])
+        .or_else(|| str_field(value, &["url", "titleUrl"]).and_then(|u| video_id_from_url(&u)))?;
+
+    // Takeout titles read "Watched <title>" and keep the channel in "subtitles"
+    let title = str_field(value, &["title"])
+        .map(|t| t.strip_prefix("Watched ").map(|s| s.to_string()).unwrap_or(t))
+        .unwrap_or_default();
+    let author = str_field(value, &["author", "uploader", "channelName"])
+        .or_else(|| {
+            value
+                .get("subtitles")
+                .and_then(|s| s.get(0))
+                .and_then(|s| str_field(s, &["name"]))
+        })
+        .unwrap_or_default();
+
+    let duration = str_field(value, &["duration"])
+        .or_else(|| int_field(value, &["lengthSeconds", "duration"]).map(|s| format_clock(s as u64)))
+        .unwrap_or_default();
+
+    let timestamp = int_field(value, &["timestamp", "timeWatched", "access_date"])
+        .map(normalize_timestamp)
+        .or_else(|| {
+            str_field(value, &["time"])
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+                .map(|d| d.timestamp())
+        })
+        .unwrap_or(0);
+    let first_played = int_field(value, &["first_played"])
+        .map(normalize_timestamp)
+        .unwrap_or(timestamp);
+    let play_count = int_field(value, &["play_count", "repeat_count"])
+        .map(|c| c.max(1) as u32)
+        .unwrap_or(1);
+
+    Some(HistoryEntry {
+        video: Video {
+            id,
+            title,
+            author,
+            duration,
+            views: str_field(value, &["views"]).unwrap_or_default(),
+            published: str_field(value, &["published"]).unwrap_or_default(),
+            thumbnail: str_field(value, &["thumbnail"]).unwrap_or_default(),
+            ..Default::default()
+        }
+        .with_stats(),
+        timestamp,
+        play_count,
+        first_played,
+    })
+}
+
+/// Parse a yt-chill CSV export
+fn parse_history_csv(content: &str) -> Result<Vec<HistoryEntry>> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| YtChillError::Parse("history: the file is empty".into()))?;
+
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let (Some(id_col), Some(title_col)) = (col("id"), col("title")) else {
+        return Err(YtChillError::Parse(
+            "history: unrecognized format (expected yt-chill, FreeTube or NewPipe export)".into(),
+        ));
+    };
+
+    let get = |row: &[String], name: &str| -> String {
+        col(name)
+            .and_then(|i| row.get(i))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let time = |row: &[String], name: &str| -> i64 {
+        chrono::DateTime::parse_from_rfc3339(&get(row, name))
+            .map(|d| d.timestamp())
+            .unwrap_or(0)
+    };
+
+    Ok(rows
+        .filter(|row| row.get(id_col).is_some_and(|id| !id.is_empty()))
+        .map(|row| HistoryEntry {
+            video: Video {
+                id: row[id_col].clone(),
+                title: row.get(title_col).cloned().unwrap_or_default(),
+                author: get(&row, "author"),
+                duration: get(&row, "duration"),
+                views: get(&row, "views"),
+                published: get(&row, "published"),
+                ..Default::default()
+            }
+            .with_stats(),
+            timestamp: time(&row, "last_played"),
+            play_count: get(&row, "play_count").parse().unwrap_or(1),
+            first_played: time(&row, "first_played"),
+        })
+        .collect())
+}
+
+/// Parse a subscriptions export: NewPipe `subscriptions.json`
+/// or FreeTube `profiles.db` / subscription export (one JSON profile per line)
+pub fn parse_subscriptions(content: &str) -> Result<Vec<Subscription>> {
+    let trimmed = content.trim_start();
+
+    // NewPipe: a single object with a "subscriptions" array of {service_id, url, name}
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed)
+        && let Some(subs) = value.get("subscriptions").and_then(|s| s.as_array())
+        && value.get("app_version").is_some()
+    {
+        return Ok(subs
+            .iter()
+            // service_id 0 is YouTube; NewPipe also supports SoundCloud, PeerTube, ...
+            .filter(|s| s.get("service_id").and_then(|v| v.as_i64()).unwrap_or(0) == 0)
+            .filter_map(|s| {
+                let url = str_field(s, &["url"])?;
+                let name = str_field(s, &["name"]).unwrap_or_default();
+                Some(subscription_from_url(&url, name))
+            })
+            .collect());
+    }
+
+    // Google Takeout: subscriptions.csv with "Channel Id,Channel Url,Channel Title"
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return parse_takeout_subscriptions_csv(content);
+    }
+
+    // FreeTube: profiles with "subscriptions": [{id, name}], possibly overlapping
+    let mut subs: Vec<Subscription> = Vec::new();
+    for line in trimmed.lines().filter(|l| !l.trim().is_empty()) {
+        let profile: Value =
+            serde_json::from_str(line).map_err(|e| YtChillError::Parse(format!("subscriptions: {}", e)))?;
+        let Some(list) = profile.get("subscriptions").and_then(|s| s.as_array()) else {
+            continue;
+        };
+        for entry in list {
+            let Some(id) = str_field(entry, &["id"]) else {
+                continue;
+            };
+            if subs.iter().any(|s| s.channel_id.as_deref() == Some(id.as_str())) {
+                continue;
+            }
+            subs.push(Subscription {
+                name: str_field(entry, &["name"]).unwrap_or_else(|| id.clone()),
+                handle: format!("/channel/{}", id),
+                channel_id: Some(id),
+                ..Default::default()
+            });
+        }
+    }
+
+    if subs.is_empty() {
+        return Err(YtChillError::Parse(
+            "subscriptions: unrecognized format (expected OPML, NewPipe or FreeTube export)".into(),
+        ));
+    }
+    Ok(subs)
+}
+
+/// Parse Takeout's subscriptions.csv
+fn parse_takeout_subscriptions_csv(content: &str) -> Result<Vec<Subscription>> {
+    let mut rows = parse_csv(content).into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let (Some(id_col), Some(title_col)) = (col("channel id"), col("channel title")) else {
+        return Err(YtChillError::Parse(
+            "subscriptions: unrecognized format (expected OPML, NewPipe, FreeTube or Takeout export)".into(),
+        ));
+    };
+
+    Ok(rows
+        .filter_map(|row| {
+            let id = row.get(id_col)?.trim().to_string();
+            if id.is_empty() {
+                return None;
+            }
+            Some(Subscription {
+                name: row.get(title_col).cloned().unwrap_or_else(|| id.clone()),
+                handle: format!("/channel/{}", id),
+                channel_id: Some(id),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+/// Build a subscription from a channel URL (/channel/UC..., /@handle, /c/name)
+fn subscription_from_url(url: &str, name: String) -> Subscription {
+    let path = url
+        .split_once("youtube.com")
+        .map(|(_, p)| p)
+        .unwrap_or(url)
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('/');
+
+    let channel_id = path
+        .strip_prefix("/channel/")
+        .map(|id| id.to_string());
+    let handle = path.strip_prefix('/').filter(|h| h.starts_with('@')).map(|h| h.to_string());
+
+    Subscription {
+        name,
+        handle: handle.unwrap_or_else(|| path.to_string()),
+        channel_id,
+        ..Default::default()
+    }
+}
+
+/// Minimal RFC 4180 CSV reader (quoted fields, escaped quotes, embedded newlines)
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Extract the video ID from a watch/short/youtu.be URL
+pub fn video_id_from_url(url: &str) -> Option<String> {
+    let id = if let Some((_, rest)) = url.split_once("v=") {
+        rest.split(['&', '#']).next()
+    } else if let Some((_, rest)) = url.split_once("youtu.be/") {
+        rest.split(['?', '&', '#']).next()
+    } else if let Some((_, rest)) = url.split_once("/shorts/") {
+        rest.split(['?', '&', '#']).next()
+    } else {
+        None
+    }?;
+
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+/// First string value among `keys`
+fn str_field(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|k| value.get(*k).and_then(|v| v.as_str()))
+        .map(|s| s.to_string())
+}
+
+/// First integer value among `keys`
+fn int_field(value: &Value, keys: &[&str]) -> Option<i64> {
+    keys.iter().find_map(|k| value.get(*k).and_then(|v| v.as_i64()))
+}
+
+/// Some clients store milliseconds; yt-chill uses seconds
+fn normalize_timestamp(ts: i64) -> i64 {
+    if ts > 100_000_000_000 { ts / 1000 } else { ts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_freetube_history() {
+        let content = r#"{"videoId":"abc","title":"Rain Sounds","author":"Nature","lengthSeconds":3600,"timeWatched":1700000000000}
+{"videoId":"def","title":"Jazz","author":"Cafe","lengthSeconds":185,"timeWatched":1700000100000}"#;
+        let entries = parse_history(content).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].video.id, "abc");
+        assert_eq!(entries[0].video.duration, "1:00:00");
+        assert_eq!(entries[1].timestamp, 1_700_000_100);
+    }
+
+    #[test]
+    fn test_parse_newpipe_subscriptions() {
+        let content = r#"{"app_version":"0.26.1","app_version_int":998,"subscriptions":[
+            {"service_id":0,"url":"https://www.youtube.com/channel/UCSJ4gkVC6NrvII8umztf0Ow","name":"Lofi Girl"},
+            {"service_id":1,"url":"https://soundcloud.com/someone","name":"Not YouTube"}]}"#;
+        let subs = parse_subscriptions(content).unwrap();
+
+        assert_eq!(subs.len(), 1);
+        assert_eq!(subs[0].name, "Lofi Girl");
+        assert_eq!(subs[0].channel_id.as_deref(), Some("UCSJ4gkVC6NrvII8umztf0Ow"));
+    }
+
+    #[test]
+    fn test_parse_takeout_history_folds_repeats() {
+        let content = r#"[
+            {"header":"YouTube","title":"Watched Rain Sounds","titleUrl":"https://www.youtube.com/watch?v=abc","subtitles":[{"name":"Nature"}],"time":"2024-03-02T10:00:00.000Z"},
+            {"header":"YouTube","title":"Watched Rain Sounds","titleUrl":"https://www.youtube.com/watch?v=abc","subtitles":[{"name":"Nature"}],"time":"2024-03-01T10:00:00.000Z"},
+            {"header":"YouTube","title":"Watched a video that has been removed","time":"2024-03-01T09:00:00.000Z"}
+        ]"#;
+        let entries = parse_history(content).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].video.title, "Rain Sounds");
+        assert_eq!(entries[0].video.author, "Nature");
+        assert_eq!(entries[0].play_count, 2);
+        assert!(entries[0].first_played < entries[0].timestamp);
+    }
+
+    #[test]
+    fn test_parse_csv_quotes() {
+        let rows = parse_csv("id,title\nabc,\"Hello, \"\"world\"\"\"\n");
+        assert_eq!(rows[1], vec!["abc", "Hello, \"world\""]);
+    }
+}
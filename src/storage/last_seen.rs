@@ -0,0 +1,55 @@
+//! Newest video seen per channel, so `yt-chill watch` only reports new uploads
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::types::Video;
+use crate::utils::paths::{ensure_dir, get_last_seen_path, get_state_dir};
+use std::collections::HashMap;
+use tokio::fs;
+
+/// Channel handle -> ID of the newest video seen
+pub type LastSeen = HashMap<String, String>;
+
+/// Load the last-seen map
+pub async fn load_last_seen() -> Result<LastSeen> {
+    read_json(&get_last_seen_path()).await
+}
+
+/// Save the last-seen map
+pub async fn save_last_seen(last_seen: &LastSeen) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(last_seen)?;
+    fs::write(get_last_seen_path(), content).await?;
+    Ok(())
+}
+
+/// Videos uploaded after `last_seen_id` (channel videos come newest first).
+/// Nothing is new for a channel we haven't seen before, or when the last-seen
+/// video has dropped out of the list, so a first run doesn't flood notifications.
+pub fn new_uploads<'a>(videos: &'a [Video], last_seen_id: Option<&str>) -> &'a [Video] {
+    match last_seen_id.and_then(|id| videos.iter().position(|v| v.id == id)) {
+        Some(index) => &videos[..index],
+        None => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(id: &str) -> Video {
+        Video {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_uploads() {
+        let videos = vec![video("c"), video("b"), video("a")];
+
+        assert_eq!(new_uploads(&videos, Some("a")).len(), 2);
+        assert!(new_uploads(&videos, Some("c")).is_empty());
+        assert!(new_uploads(&videos, None).is_empty());
+    }
+}
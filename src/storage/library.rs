@@ -0,0 +1,116 @@
+//! Local library: downloaded files indexed by the `[videoId]` in their names
+
+use crate::types::{DownloadRecord, LibraryItem, Video};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "opus", "m4a", "flac", "ogg", "wav", "aac"];
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "mkv", "mov"];
+
+/// `Title [dQw4w9WgXcQ].ext`
+fn id_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"^(.*?)\s*\[([A-Za-z0-9_-]{11})\]$").unwrap())
+}
+
+/// Index media files under `dir` (including per-channel subfolders), newest first.
+/// Titles and channels come from the download archive when it knows the file.
+pub fn scan_library(dir: &Path, archive: &[DownloadRecord]) -> Vec<LibraryItem> {
+    let mut items = Vec::new();
+    scan_dir(dir, dir, archive, &mut items);
+    items.sort_by_key(|i| std::cmp::Reverse(i.modified));
+    items
+}
+
+fn scan_dir(root: &Path, dir: &Path, archive: &[DownloadRecord], items: &mut Vec<LibraryItem>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, archive, items);
+        } else if let Some(item) = library_item(root, &path, archive) {
+            items.push(item);
+        }
+    }
+}
+
+/// Build a library item if `path` is a media file with a video ID in its name
+fn library_item(root: &Path, path: &Path, archive: &[DownloadRecord]) -> Option<LibraryItem> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let has_video = VIDEO_EXTENSIONS.contains(&ext.as_str());
+    if !has_video && !AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+        return None;
+    }
+
+    let stem = path.file_stem()?.to_str()?;
+    let caps = id_pattern().captures(stem)?;
+    let id = caps[2].to_string();
+
+    let record = archive.iter().find(|r| Path::new(&r.path) == path);
+    let title = record
+        .map(|r| r.title.clone())
+        .unwrap_or_else(|| caps[1].to_string());
+
+    // Organized downloads live in download_dir/<channel>/
+    let author = path
+        .parent()
+        .filter(|p| *p != root)
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    Some(LibraryItem {
+        id,
+        title,
+        author,
+        path: path.to_path_buf(),
+        has_video,
+        modified,
+    })
+}
+
+impl LibraryItem {
+    /// As a Video, for history
+    pub fn to_video(&self) -> Video {
+        Video {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            author: self.author.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_library() {
+        let dir = std::env::temp_dir().join(format!("yt-chill-library-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("Lofi Girl")).unwrap();
+        std::fs::write(dir.join("Lofi Girl/beats to relax [jfKfPfyJRdk].opus"), b"").unwrap();
+        std::fs::write(dir.join("holiday photos.jpg"), b"").unwrap();
+        std::fs::write(dir.join("no id.mp3"), b"").unwrap();
+
+        let items = scan_library(&dir, &[]);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "jfKfPfyJRdk");
+        assert_eq!(items[0].title, "beats to relax");
+        assert_eq!(items[0].author, "Lofi Girl");
+        assert!(!items[0].has_video);
+    }
+}
@@ -0,0 +1,27 @@
+//! Extended M3U playlists of YouTube watch URLs
+
+use crate::core::player::build_video_url;
+use crate::types::Video;
+use crate::utils::time::parse_clock;
+
+/// Render videos as an `.m3u8` playlist that mpv, VLC and friends can open
+pub fn to_m3u(videos: &[Video]) -> String {
+    let mut out = String::from("#EXTM3U\n");
+
+    for video in videos {
+        // -1 = unknown length (live streams)
+        let secs = parse_clock(&video.duration).map(|s| s as i64).unwrap_or(-1);
+        let name = if video.author.is_empty() {
+            video.title.clone()
+        } else {
+            format!("{} - {}", video.author, video.title)
+        };
+
+        // Newlines would break the line-based format
+        out.push_str(&format!("#EXTINF:{},{}\n", secs, name.replace(['\n', '\r'], " ")));
+        out.push_str(&build_video_url(&video.id));
+        out.push('\n');
+    }
+
+    out
+}
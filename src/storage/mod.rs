@@ -1,6 +1,76 @@
-//! Storage modules: config, history, cache, subscriptions
+//! Storage modules: config, history, searches, cache, subscriptions, favorites, queue
 
 pub mod cache;
 pub mod config;
+pub mod downloads;
+pub mod favorites;
+pub mod feed_cache;
 pub mod history;
+pub mod import;
+pub mod last_seen;
+pub mod library;
+pub mod m3u;
+pub mod opml;
+pub mod queue;
+pub mod schedule;
+pub mod searches;
+pub mod session;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod subscriptions;
+
+use crate::error::{Result, YtChillError};
+use crate::types::StorageBackend;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::sync::OnceLock;
+use tokio::fs;
+
+static BACKEND: OnceLock<StorageBackend> = OnceLock::new();
+
+/// Select the storage backend for this run (first call wins).
+/// Falls back to JSON when the `sqlite` feature isn't compiled in.
+pub fn set_backend(backend: StorageBackend) -> StorageBackend {
+    let effective = if cfg!(feature = "sqlite") {
+        backend
+    } else {
+        StorageBackend::Json
+    };
+    *BACKEND.get_or_init(|| effective)
+}
+
+/// The active storage backend (JSON unless configured otherwise)
+pub fn backend() -> StorageBackend {
+    BACKEND.get().copied().unwrap_or_default()
+}
+
+/// Read a JSON state file, or the default if there isn't one yet. A file
+/// that doesn't parse is an error, so it gets reported instead of being
+/// read as empty and then overwritten.
+pub(crate) async fn read_json<T: DeserializeOwned + Default>(path: &str) -> Result<T> {
+    if !Path::new(path).exists() {
+        return Ok(T::default());
+    }
+
+    let content = fs::read_to_string(path).await?;
+    serde_json::from_str(&content).map_err(|e| YtChillError::Parse(format!("{}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_json_reports_damaged_files() {
+        let path = std::env::temp_dir().join(format!("yt-chill-read-json-{}.json", std::process::id()));
+        let path = path.to_string_lossy();
+
+        let missing: Vec<String> = read_json(&path).await.unwrap();
+        assert!(missing.is_empty());
+
+        std::fs::write(&*path, "[\"lofi\", ").unwrap();
+        let damaged = read_json::<Vec<String>>(&path).await;
+        let _ = std::fs::remove_file(&*path);
+        assert!(matches!(damaged, Err(YtChillError::Parse(_))));
+    }
+}
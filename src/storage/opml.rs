@@ -0,0 +1,89 @@
+//! OPML import/export of subscriptions as YouTube RSS feeds
+
+use crate::core::youtube::channel_feed_url;
+use crate::storage::subscriptions::known_channel_id;
+use crate::types::Subscription;
+use html_escape::{decode_html_entities, encode_double_quoted_attribute};
+use regex::Regex;
+
+/// Render subscriptions as OPML. Entries without a known channel ID are skipped.
+pub fn to_opml(subscriptions: &[Subscription]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"1.1\">\n\
+         \x20 <head><title>yt-chill subscriptions</title></head>\n\
+         \x20 <body>\n\
+         \x20   <outline text=\"YouTube Subscriptions\" title=\"YouTube Subscriptions\">\n",
+    );
+
+    for sub in subscriptions {
+        let Some(id) = known_channel_id(sub) else {
+            continue;
+        };
+        let name = encode_double_quoted_attribute(&sub.name);
+        out.push_str(&format!(
+            "      <outline text=\"{name}\" title=\"{name}\" type=\"rss\" \
+             xmlUrl=\"{feed}\" htmlUrl=\"https://www.youtube.com/channel/{id}\"/>\n",
+            feed = channel_feed_url(&id)
+        ));
+    }
+
+    out.push_str("    </outline>\n  </body>\n</opml>\n");
+    out
+}
+
+/// Parse YouTube channel feeds out of an OPML document
+pub fn parse_opml(content: &str) -> Vec<Subscription> {
+    let outline = Regex::new(r"<outline\b[^>]*>").expect("Invalid regex");
+    let attr = Regex::new(r#"(\w+)="([^"]*)""#).expect("Invalid regex");
+
+    outline
+        .find_iter(content)
+        .filter_map(|tag| {
+            let mut name = None;
+            let mut xml_url = None;
+            for cap in attr.captures_iter(tag.as_str()) {
+                let value = decode_html_entities(&cap[2]).to_string();
+                match &cap[1] {
+                    "text" | "title" if name.is_none() => name = Some(value),
+                    "xmlUrl" => xml_url = Some(value),
+                    _ => {}
+                }
+            }
+
+            let channel_id = xml_url?
+                .split("channel_id=")
+                .nth(1)?
+                .split('&')
+                .next()?
+                .to_string();
+
+            Some(Subscription {
+                name: name.unwrap_or_else(|| channel_id.clone()),
+                handle: format!("/channel/{}", channel_id),
+                channel_id: Some(channel_id),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opml_round_trip() {
+        let subs = vec![Subscription {
+            name: "Lofi & Chill".into(),
+            handle: "@lofi".into(),
+            channel_id: Some("UCSJ4gkVC6NrvII8umztf0Ow".into()),
+            ..Default::default()
+        }];
+
+        let parsed = parse_opml(&to_opml(&subs));
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "Lofi & Chill");
+        assert_eq!(parsed[0].channel_id.as_deref(), Some("UCSJ4gkVC6NrvII8umztf0Ow"));
+    }
+}
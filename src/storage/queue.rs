@@ -0,0 +1,49 @@
+//! Play queue, persisted between runs
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::types::Video;
+use crate::utils::paths::{ensure_dir, get_queue_path, get_state_dir};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// Held while the queue is rewritten: the daemon, `--serve` and gapless
+/// playback change it while it plays
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Load the queue, next item first
+pub async fn load_queue() -> Result<Vec<Video>> {
+    read_json(&get_queue_path()).await
+}
+
+/// Save the queue to file
+pub async fn save_queue(queue: &[Video]) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(queue)?;
+    fs::write(get_queue_path(), content).await?;
+    Ok(())
+}
+
+/// Append videos to the end of the queue
+pub async fn enqueue(videos: &[Video]) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut queue = load_queue().await?;
+    queue.extend_from_slice(videos);
+    save_queue(&queue).await
+}
+
+/// Empty the queue
+pub async fn clear_queue() -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    save_queue(&[]).await
+}
+
+/// Remove a video from the queue (the queue may have changed since it was loaded)
+pub async fn dequeue(id: &str) -> Result<()> {
+    let _guard = WRITE_LOCK.lock().await;
+    let mut queue = load_queue().await?;
+    if let Some(pos) = queue.iter().position(|v| v.id == id) {
+        queue.remove(pos);
+    }
+    save_queue(&queue).await
+}
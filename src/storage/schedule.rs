@@ -0,0 +1,43 @@
+//! Downloads scheduled for later (`--schedule`), persisted so a restart
+//! or `yt-chill schedule run` from cron still picks them up
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::types::ScheduledDownload;
+use crate::utils::paths::{ensure_dir, get_schedule_path, get_state_dir};
+use tokio::fs;
+
+/// Load scheduled downloads, soonest first
+pub async fn load_schedule() -> Result<Vec<ScheduledDownload>> {
+    read_json(&get_schedule_path()).await
+}
+
+/// Save the schedule
+pub async fn save_schedule(schedule: &[ScheduledDownload]) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(schedule)?;
+    fs::write(get_schedule_path(), content).await?;
+    Ok(())
+}
+
+/// Add a download, keeping the schedule in start order
+pub async fn add_scheduled(download: ScheduledDownload) -> Result<()> {
+    let mut schedule = load_schedule().await?;
+    if !schedule.contains(&download) {
+        schedule.push(download);
+        schedule.sort_by_key(|d| d.at);
+        save_schedule(&schedule).await?;
+    }
+    Ok(())
+}
+
+/// Take a download off the schedule (it may have changed since it was loaded)
+pub async fn remove_scheduled(download: &ScheduledDownload) -> Result<()> {
+    let mut schedule = load_schedule().await?;
+    let before = schedule.len();
+    schedule.retain(|d| d != download);
+    if schedule.len() != before {
+        save_schedule(&schedule).await?;
+    }
+    Ok(())
+}
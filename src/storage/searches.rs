@@ -0,0 +1,82 @@
+//! Past search queries (separate from watch history), recalled with ↑ at the search prompt
+
+use crate::error::Result;
+use crate::storage::read_json;
+use crate::utils::paths::{ensure_dir, get_search_history_path, get_state_dir};
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Recent queries, newest first, without duplicates
+pub struct SearchHistory {
+    queries: Vec<String>,
+    max_entries: usize,
+}
+
+impl SearchHistory {
+    /// Load saved queries, keeping at most `max_entries` (0 = remember nothing)
+    pub async fn load(max_entries: usize) -> Result<Self> {
+        let mut queries: Vec<String> = read_json(&get_search_history_path()).await?;
+        queries.truncate(max_entries);
+        Ok(Self { queries, max_entries })
+    }
+
+    /// Save queries to file
+    pub async fn save(&self) -> Result<()> {
+        ensure_dir(&get_state_dir()).await?;
+        let content = serde_json::to_string_pretty(&self.queries)?;
+        fs::write(get_search_history_path(), content).await?;
+        Ok(())
+    }
+
+    /// Put a query first, dropping an earlier copy of it (case-insensitive)
+    pub fn add(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.queries.retain(|q| !q.eq_ignore_ascii_case(query));
+        self.queries.insert(0, query.to_string());
+        self.queries.truncate(self.max_entries);
+    }
+
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+}
+
+/// ↑/↓ at the dialoguer search prompt steps through past queries
+impl dialoguer::History<String> for SearchHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.queries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &String) {
+        self.add(val);
+    }
+}
+
+/// Forget all past queries
+pub async fn clear_searches() -> Result<()> {
+    let path = PathBuf::from(get_search_history_path());
+    if path.exists() {
+        fs::remove_file(&path).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_dedupes_and_caps() {
+        let mut searches = SearchHistory { queries: Vec::new(), max_entries: 2 };
+        searches.add("lofi beats");
+        searches.add("jazz");
+        searches.add("Lofi Beats ");
+        assert_eq!(searches.queries(), ["Lofi Beats", "jazz"]);
+
+        searches.add("synthwave");
+        assert_eq!(searches.queries(), ["synthwave", "Lofi Beats"]);
+    }
+}
@@ -0,0 +1,28 @@
+//! The last interactive session (list, query, results and pick), saved as
+//! it changes so `--resume-session` can pick up after the terminal closes
+
+use crate::error::Result;
+use crate::types::Session;
+use crate::utils::paths::{ensure_dir, get_session_path, get_state_dir};
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Load the saved session, if there is one
+pub async fn load_session() -> Result<Option<Session>> {
+    let path = PathBuf::from(get_session_path());
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path).await?;
+    Ok(serde_json::from_str(&content).ok())
+}
+
+/// Save the session, replacing the previous one
+pub async fn save_session(session: &Session) -> Result<()> {
+    ensure_dir(&get_state_dir()).await?;
+    let content = serde_json::to_string_pretty(session)?;
+    fs::write(get_session_path(), content).await?;
+    Ok(())
+}
@@ -0,0 +1,264 @@
+//! SQLite storage backend (behind the `sqlite` feature)
+//!
+//! Keeps history, subscriptions and cache metadata in a single database.
+//! Rows carry indexed columns for querying plus the full record as JSON,
+//! so new fields on the Rust types don't need a schema migration.
+
+use crate::error::{Result, YtChillError};
+use crate::types::{HistoryEntry, Subscription};
+use crate::utils::paths::{ensure_dir, get_database_path};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS history (
+    video_id   TEXT PRIMARY KEY,
+    title      TEXT NOT NULL,
+    author     TEXT NOT NULL,
+    timestamp  INTEGER NOT NULL,
+    play_count INTEGER NOT NULL DEFAULT 1,
+    data       TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_history_timestamp ON history(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_history_play_count ON history(play_count DESC);
+CREATE INDEX IF NOT EXISTS idx_history_title ON history(title COLLATE NOCASE);
+
+CREATE TABLE IF NOT EXISTS subscriptions (
+    handle   TEXT PRIMARY KEY,
+    name     TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    data     TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cache_meta (
+    key       TEXT PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    ttl       INTEGER NOT NULL,
+    size      INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_cache_meta_timestamp ON cache_meta(timestamp);
+
+CREATE TABLE IF NOT EXISTS migrations (
+    name TEXT PRIMARY KEY
+);
+";
+
+/// Migration name for the one-time import of history.json
+pub const HISTORY_IMPORT: &str = "history.json";
+/// Migration name for the one-time import of subscriptions.toml
+pub const SUBSCRIPTIONS_IMPORT: &str = "subscriptions.toml";
+
+impl From<rusqlite::Error> for YtChillError {
+    fn from(e: rusqlite::Error) -> Self {
+        YtChillError::Database(e.to_string())
+    }
+}
+
+/// Handle to the yt-chill database
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the database at the default state-dir location
+    pub async fn open_default() -> Result<Self> {
+        let path = get_database_path();
+        if let Some(parent) = Path::new(&path).parent() {
+            ensure_dir(&parent.to_string_lossy()).await?;
+        }
+        Self::open(Path::new(&path))
+    }
+
+    /// Open (creating if needed) a database at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    // ------------------------------------------
+    // History
+    // ------------------------------------------
+
+    /// Load history, most recent first
+    pub fn load_history(&self) -> Result<Vec<HistoryEntry>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM history ORDER BY timestamp DESC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut entries = Vec::new();
+        for data in rows {
+            if let Ok(entry) = serde_json::from_str(&data?) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Replace all history with `entries`
+    pub fn save_history(&mut self, entries: &[HistoryEntry]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM history", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO history (video_id, title, author, timestamp, play_count, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for entry in entries {
+                stmt.execute(params![
+                    entry.video.id,
+                    entry.video.title,
+                    entry.video.author,
+                    entry.timestamp,
+                    entry.play_count,
+                    serde_json::to_string(entry)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Whether the history table has any rows
+    pub fn has_history(&self) -> Result<bool> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Play counts per video ID, most played first
+    pub fn play_counts(&self) -> Result<Vec<(String, u32)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT video_id, play_count FROM history ORDER BY play_count DESC")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // ------------------------------------------
+    // Subscriptions
+    // ------------------------------------------
+
+    /// Load subscriptions in the order they were added
+    pub fn load_subscriptions(&self) -> Result<Vec<Subscription>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT data FROM subscriptions ORDER BY position")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        let mut subs = Vec::new();
+        for data in rows {
+            if let Ok(sub) = serde_json::from_str(&data?) {
+                subs.push(sub);
+            }
+        }
+        Ok(subs)
+    }
+
+    /// Replace all subscriptions with `subscriptions`
+    pub fn save_subscriptions(&mut self, subscriptions: &[Subscription]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM subscriptions", [])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO subscriptions (handle, name, position, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (position, sub) in subscriptions.iter().enumerate() {
+                stmt.execute(params![
+                    sub.handle,
+                    sub.name,
+                    position as i64,
+                    serde_json::to_string(sub)?,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ------------------------------------------
+    // Migrations
+    // ------------------------------------------
+
+    /// Whether the one-time migration `name` has already run
+    pub fn is_migrated(&self, name: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM migrations WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Record that the one-time migration `name` has run
+    pub fn mark_migrated(&self, name: &str) -> Result<()> {
+        self.conn
+            .execute("INSERT OR IGNORE INTO migrations (name) VALUES (?1)", params![name])?;
+        Ok(())
+    }
+
+    // ------------------------------------------
+    // Cache metadata
+    // ------------------------------------------
+
+    /// Record (or refresh) metadata for a cache file
+    pub fn record_cache_entry(&self, key: &str, timestamp: i64, ttl: u64, size: u64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO cache_meta (key, timestamp, ttl, size) VALUES (?1, ?2, ?3, ?4)",
+            params![key, timestamp, ttl as i64, size as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Forget a cache entry
+    pub fn remove_cache_entry(&self, key: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM cache_meta WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+
+    /// Total size of tracked cache files, in bytes
+    pub fn cache_size(&self) -> Result<u64> {
+        let size: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(size), 0) FROM cache_meta",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(size as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Video;
+
+    #[test]
+    fn test_history_round_trip() {
+        let mut store = SqliteStore::open(Path::new(":memory:")).unwrap();
+        let entry = HistoryEntry {
+            video: Video {
+                id: "abc123".into(),
+                title: "lofi beats".into(),
+                author: "Lofi Girl".into(),
+                duration: "1:00:00".into(),
+                duration_secs: Some(3600),
+                ..Default::default()
+            },
+            timestamp: 1_700_000_000,
+            play_count: 2,
+            first_played: 1_600_000_000,
+        };
+
+        store.save_history(std::slice::from_ref(&entry)).unwrap();
+        let loaded = store.load_history().unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].video.id, "abc123");
+        assert_eq!(store.play_counts().unwrap(), vec![("abc123".to_string(), 2)]);
+        assert!(store.has_history().unwrap());
+    }
+}
@@ -1,53 +1,117 @@
 //! Subscription management
-#![allow(dead_code)]
 
-use crate::error::Result;
-use crate::types::Subscription;
-use crate::utils::paths::{ensure_dir, get_config_dir};
-use std::path::PathBuf;
+use crate::error::{Result, YtChillError};
+#[cfg(feature = "sqlite")]
+use crate::storage::sqlite::{SUBSCRIPTIONS_IMPORT, SqliteStore};
+#[cfg(feature = "sqlite")]
+use crate::types::StorageBackend;
+use crate::types::{MergeStats, Subscription};
+use crate::utils::paths::{
+    ensure_dir, get_config_dir, get_legacy_subscriptions_path, get_subscriptions_path,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tokio::fs;
 
-/// Get subscriptions file path
-fn get_subscriptions_path() -> PathBuf {
-    PathBuf::from(get_config_dir()).join("subscriptions.txt")
+/// On-disk layout of subscriptions.toml: one `[[subscription]]` table per channel
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SubscriptionsFile {
+    #[serde(default, rename = "subscription")]
+    subscriptions: Vec<Subscription>,
 }
 
-/// Load subscriptions from file
+/// Load subscriptions from file (or the database, with the SQLite backend)
 pub async fn load_subscriptions() -> Result<Vec<Subscription>> {
+    #[cfg(feature = "sqlite")]
+    if super::backend() == StorageBackend::Sqlite {
+        let mut store = SqliteStore::open_default().await?;
+        return load_store(&mut store, load_subscriptions_file()).await;
+    }
+
+    load_subscriptions_file().await
+}
+
+/// Load subscriptions from the database, seeding it from `file` once.
+/// An empty table after that means the user unsubscribed from everything.
+#[cfg(feature = "sqlite")]
+async fn load_store(
+    store: &mut SqliteStore,
+    file: impl Future<Output = Result<Vec<Subscription>>>,
+) -> Result<Vec<Subscription>> {
+    if !store.is_migrated(SUBSCRIPTIONS_IMPORT)? {
+        // Databases from before the marker existed already hold the import
+        if store.load_subscriptions()?.is_empty() {
+            store.save_subscriptions(&file.await?)?;
+        }
+        store.mark_migrated(SUBSCRIPTIONS_IMPORT)?;
+    }
+
+    store.load_subscriptions()
+}
+
+/// Load subscriptions from subscriptions.toml, migrating the legacy txt file
+async fn load_subscriptions_file() -> Result<Vec<Subscription>> {
     let path = get_subscriptions_path();
 
-    if !path.exists() {
+    if Path::new(&path).exists() {
+        let content = fs::read_to_string(&path).await?;
+        let file: SubscriptionsFile = toml::from_str(&content)
+            .map_err(|e| YtChillError::Parse(format!("{}: {}", path, e)))?;
+        return Ok(file.subscriptions);
+    }
+
+    let legacy = get_legacy_subscriptions_path();
+    if !Path::new(&legacy).exists() {
         return Ok(Vec::new());
     }
 
-    let content = fs::read_to_string(&path).await?;
-    let subscriptions = content
+    // Rewrite the old tab-separated file as TOML, keeping it as a backup
+    let subscriptions = parse_legacy(&fs::read_to_string(&legacy).await?);
+    save_subscriptions_file(&subscriptions).await?;
+    fs::rename(&legacy, format!("{}.bak", legacy)).await?;
+    Ok(subscriptions)
+}
+
+/// Parse the legacy `name<TAB>handle[<TAB>channel_id]` format
+fn parse_legacy(content: &str) -> Vec<Subscription> {
+    content
         .lines()
         .filter_map(|line| {
-            let parts: Vec<&str> = line.splitn(2, '\t').collect();
-            if parts.len() == 2 {
+            // name<TAB>handle[<TAB>channel_id]
+            let parts: Vec<&str> = line.splitn(3, '\t').collect();
+            if parts.len() >= 2 {
                 Some(Subscription {
                     name: parts[0].to_string(),
                     handle: parts[1].to_string(),
+                    channel_id: parts.get(2).filter(|id| !id.is_empty()).map(|id| id.to_string()),
+                    ..Default::default()
                 })
             } else {
                 None
             }
         })
-        .collect();
-
-    Ok(subscriptions)
+        .collect()
 }
 
-/// Save subscriptions to file
+/// Save subscriptions to file (or the database, with the SQLite backend)
 pub async fn save_subscriptions(subscriptions: &[Subscription]) -> Result<()> {
+    #[cfg(feature = "sqlite")]
+    if super::backend() == StorageBackend::Sqlite {
+        return SqliteStore::open_default().await?.save_subscriptions(subscriptions);
+    }
+
+    save_subscriptions_file(subscriptions).await
+}
+
+/// Write subscriptions.toml
+async fn save_subscriptions_file(subscriptions: &[Subscription]) -> Result<()> {
     ensure_dir(&get_config_dir()).await?;
 
-    let content: String = subscriptions
-        .iter()
-        .map(|s| format!("{}\t{}", s.name, s.handle))
-        .collect::<Vec<_>>()
-        .join("\n");
+    let file = SubscriptionsFile {
+        subscriptions: subscriptions.to_vec(),
+    };
+    let content = toml::to_string_pretty(&file)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
 
     fs::write(get_subscriptions_path(), content).await?;
     Ok(())
@@ -68,7 +132,134 @@ pub async fn add_subscription(subscription: &Subscription) -> Result<()> {
 
 /// Remove a subscription by handle
 pub async fn remove_subscription(handle: &str) -> Result<()> {
+    remove_subscriptions(&[handle.to_string()]).await
+}
+
+/// Remove several subscriptions by handle in one write
+pub async fn remove_subscriptions(handles: &[String]) -> Result<()> {
+    let mut subs = load_subscriptions().await?;
+    subs.retain(|s| !handles.contains(&s.handle));
+    save_subscriptions(&subs).await
+}
+
+/// Change a subscription's display name. Returns false if it wasn't found.
+pub async fn rename_subscription(handle: &str, name: &str) -> Result<bool> {
+    let mut subs = load_subscriptions().await?;
+    let Some(sub) = subs.iter_mut().find(|s| s.handle == handle) else {
+        return Ok(false);
+    };
+    sub.name = name.to_string();
+    save_subscriptions(&subs).await?;
+    Ok(true)
+}
+
+/// Update a subscription in place. Returns false if it wasn't found.
+pub async fn update_subscription(handle: &str, update: impl FnOnce(&mut Subscription)) -> Result<bool> {
+    let mut subs = load_subscriptions().await?;
+    let Some(sub) = subs.iter_mut().find(|s| s.handle == handle) else {
+        return Ok(false);
+    };
+    update(sub);
+    save_subscriptions(&subs).await?;
+    Ok(true)
+}
+
+/// Find a subscription by handle or (case-insensitive) name
+pub fn find_subscription<'a>(subs: &'a [Subscription], key: &str) -> Option<&'a Subscription> {
+    subs.iter()
+        .find(|s| s.handle == key)
+        .or_else(|| subs.iter().find(|s| s.name.eq_ignore_ascii_case(key)))
+}
+
+/// Channel ID for a subscription, from the stored field or a /channel/UC... handle
+pub fn known_channel_id(sub: &Subscription) -> Option<String> {
+    sub.channel_id.clone().or_else(|| channel_id_in(&sub.handle))
+}
+
+/// The UC... channel ID a handle like "/channel/UC..." spells out
+pub fn channel_id_in(handle: &str) -> Option<String> {
+    let id = handle.trim().trim_start_matches("/channel/").trim_start_matches('@');
+    (id.starts_with("UC") && id.len() == 24).then(|| id.to_string())
+}
+
+/// Remember channel IDs looked up for subscriptions (handle, ID), so each
+/// is only looked up once
+pub async fn save_channel_ids(ids: &[(String, String)]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
     let mut subs = load_subscriptions().await?;
-    subs.retain(|s| s.handle != handle);
+    for sub in subs.iter_mut() {
+        if let Some((_, id)) = ids.iter().find(|(handle, _)| *handle == sub.handle) {
+            sub.channel_id = Some(id.clone());
+        }
+    }
     save_subscriptions(&subs).await
 }
+
+/// Add subscriptions that aren't already present (matched by channel ID or handle)
+pub async fn merge_subscriptions(imported: Vec<Subscription>) -> Result<MergeStats> {
+    let mut subs = load_subscriptions().await?;
+    let mut stats = MergeStats::default();
+
+    for incoming in imported {
+        let incoming_id = known_channel_id(&incoming);
+        let existing = subs.iter_mut().find(|s| {
+            s.handle == incoming.handle
+                || (incoming_id.is_some() && known_channel_id(s) == incoming_id)
+        });
+
+        match existing {
+            Some(existing) => {
+                if existing.channel_id.is_none() && incoming_id.is_some() {
+                    existing.channel_id = incoming_id;
+                    stats.updated += 1;
+                }
+            }
+            None => {
+                subs.push(incoming);
+                stats.added += 1;
+            }
+        }
+    }
+
+    save_subscriptions(&subs).await?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_keeps_settings_and_reads_legacy() {
+        let legacy = parse_legacy("Lofi Girl\t@LofiGirl\tUCSJ4gkVC6NrvII8umztf0Ow\nChillhop\t@chillhop");
+        assert_eq!(legacy.len(), 2);
+        assert!(legacy[1].channel_id.is_none());
+        assert!(legacy[1].notify);
+
+        let mut subs = legacy;
+        subs[0].limit = Some(10);
+        subs[1].muted = true;
+        subs[1].notify = false;
+
+        let file = SubscriptionsFile { subscriptions: subs };
+        let parsed: SubscriptionsFile = toml::from_str(&toml::to_string_pretty(&file).unwrap()).unwrap();
+        assert_eq!(parsed.subscriptions[0].limit, Some(10));
+        assert!(parsed.subscriptions[1].muted);
+        assert!(!parsed.subscriptions[1].notify);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_unsubscribing_from_everything_sticks() {
+        let mut store = SqliteStore::open(Path::new(":memory:")).unwrap();
+        let file = || async { Ok(parse_legacy("Lofi Girl\t@LofiGirl\nChillhop\t@chillhop")) };
+
+        assert_eq!(load_store(&mut store, file()).await.unwrap().len(), 2);
+
+        // What removing the last subscription does with the SQLite backend
+        store.save_subscriptions(&[]).unwrap();
+        assert!(load_store(&mut store, file()).await.unwrap().is_empty());
+    }
+}
@@ -1,16 +1,16 @@
 //! Type definitions for yt-chill
 //!
 //! Source of truth for all data structures.
-#![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ============================================
 // Video Types
 // ============================================
 
 /// A video result from YouTube search or feed
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Video {
     pub id: String,
     pub title: String,
@@ -23,6 +23,42 @@ pub struct Video {
     pub published: String,
     /// URL to thumbnail image
     pub thumbnail: String,
+    /// `duration` in seconds (None for live streams and older saved entries)
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// `views` as a number
+    #[serde(default)]
+    pub view_count: Option<u64>,
+}
+
+impl Video {
+    /// Live streams have no length; search results show "LIVE" instead
+    pub fn is_live(&self) -> bool {
+        self.duration.eq_ignore_ascii_case("LIVE")
+    }
+
+    /// Fill in `duration_secs` and `view_count` from the raw strings
+    pub fn with_stats(mut self) -> Self {
+        self.duration_secs = crate::utils::time::parse_clock(&self.duration);
+        self.view_count = parse_view_count(&self.views);
+        self
+    }
+}
+
+/// Parse YouTube's "1,234,567 views", "1.2M views" or "No views" into a number
+pub fn parse_view_count(views: &str) -> Option<u64> {
+    let number = views.split_whitespace().next()?.replace(',', "");
+    if number.eq_ignore_ascii_case("no") {
+        return Some(0);
+    }
+    let (digits, scale) = match number.char_indices().last()? {
+        (i, 'K' | 'k') => (&number[..i], 1e3),
+        (i, 'M' | 'm') => (&number[..i], 1e6),
+        (i, 'B' | 'b') => (&number[..i], 1e9),
+        _ => (number.as_str(), 1.0),
+    };
+    let value: f64 = digits.parse().ok()?;
+    (value >= 0.0).then(|| (value * scale).round() as u64)
 }
 
 /// A video in watch history (extends Video with timestamp)
@@ -30,12 +66,226 @@ pub struct Video {
 pub struct HistoryEntry {
     #[serde(flatten)]
     pub video: Video,
-    /// Unix timestamp when watched
+    /// Unix timestamp when last watched
     pub timestamp: i64,
+    /// Number of times played
+    #[serde(default = "default_play_count")]
+    pub play_count: u32,
+    /// Unix timestamp when first watched (0 in files written before this existed)
+    #[serde(default)]
+    pub first_played: i64,
 }
 
-/// A channel result from channel search
+fn default_play_count() -> u32 {
+    1
+}
+
+/// Result of merging imported entries into existing data
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeStats {
+    /// Entries that weren't there before
+    pub added: usize,
+    /// Existing entries updated with newer data
+    pub updated: usize,
+}
+
+/// Ordering of the history menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySort {
+    /// Most recently played first
+    #[default]
+    Recent,
+    /// Most played first
+    Frequent,
+}
+
+/// Where `--random` picks from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RandomSource {
+    /// Anything played before
+    #[default]
+    History,
+    /// Recent uploads from subscriptions
+    Feed,
+    Favorites,
+}
+
+/// Order of search results (`--sort-by`, ctrl-s in the list)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultSort {
+    /// YouTube's order
+    #[default]
+    Relevance,
+    /// Most viewed first
+    Views,
+    /// Newest first
+    Date,
+    /// Longest first
+    Duration,
+}
+
+impl ResultSort {
+    /// The order ctrl-s switches to next
+    pub fn next(self) -> Self {
+        match self {
+            Self::Relevance => Self::Views,
+            Self::Views => Self::Date,
+            Self::Date => Self::Duration,
+            Self::Duration => Self::Relevance,
+        }
+    }
+
+    /// Sort videos in YouTube's order into this one. Videos missing the
+    /// number (live streams, older cached results) go last.
+    pub fn apply(self, videos: &mut [Video]) {
+        use std::cmp::Reverse;
+        match self {
+            Self::Relevance => {}
+            Self::Views => videos.sort_by_key(|v| Reverse(v.view_count)),
+            Self::Date => videos.sort_by_key(|v| crate::utils::time::parse_age(&v.published).unwrap_or(u64::MAX)),
+            Self::Duration => videos.sort_by_key(|v| Reverse(v.duration_secs)),
+        }
+    }
+}
+
+/// Codec for audio-only downloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// Widely supported, lossy (transcoded)
+    #[default]
+    Mp3,
+    /// YouTube's native audio codec, no transcoding
+    Opus,
+    /// AAC, plays everywhere Apple does
+    M4a,
+    /// Lossless container (of a lossy source)
+    Flac,
+}
+
+impl AudioFormat {
+    /// Name as yt-dlp expects it for `--audio-format`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Flac => "flac",
+        }
+    }
+}
+
+/// How finished downloads are sorted into folders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizeBy {
+    /// Everything straight into download_dir
+    #[default]
+    Off,
+    /// download_dir/<channel>/
+    Channel,
+    /// download_dir/<artist>/, using the artist parsed from the title (channel if none)
+    Artist,
+}
+
+/// A starred video with optional tags (e.g. "focus", "sleep")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    #[serde(flatten)]
+    pub video: Video,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Unix timestamp when starred
+    pub added: i64,
+}
+
+/// A finished download, remembered so it isn't fetched twice
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRecord {
+    /// YouTube video ID
+    pub id: String,
+    pub title: String,
+    /// Whether video was included (audio and video downloads are tracked separately)
+    pub video: bool,
+    /// Final file path
+    pub path: String,
+    /// Unix timestamp when downloaded
+    pub timestamp: i64,
+}
+
+/// A download that was started but hasn't finished yet
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDownload {
+    pub video: Video,
+    /// Whether video was included
+    pub include_video: bool,
+    /// Where the finished file goes
+    pub output_dir: String,
+    /// Unix timestamp when first started
+    pub started: i64,
+}
+
+/// What a scheduled download does when its time comes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DownloadJob {
+    /// `yt-chill download --playlist <url>`
+    Playlist { url: String },
+    /// `yt-chill archive <channel>`
+    Archive {
+        channel: String,
+        limit: Option<usize>,
+        /// Unix timestamp of the oldest upload wanted
+        since: Option<i64>,
+    },
+    /// `yt-chill download --resume`
+    Resume,
+}
+
+impl DownloadJob {
+    /// One line for `yt-chill schedule list`
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Playlist { url } => format!("playlist {}", url),
+            Self::Archive { channel, limit: Some(n), .. } => format!("archive {} (newest {})", channel, n),
+            Self::Archive { channel, .. } => format!("archive {}", channel),
+            Self::Resume => "finish interrupted downloads".into(),
+        }
+    }
+}
+
+/// A download waiting for its time, e.g. overnight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledDownload {
+    /// Unix timestamp to start at
+    pub at: i64,
+    pub job: DownloadJob,
+    /// Whether video is included
+    pub video: bool,
+    /// Videos at once (None = download_jobs from the config)
+    #[serde(default)]
+    pub jobs: Option<usize>,
+}
+
+/// A downloaded file found in the download directory
+#[derive(Debug, Clone)]
+pub struct LibraryItem {
+    /// YouTube video ID from the `[id]` in the filename
+    pub id: String,
+    pub title: String,
+    /// Channel, when known from the download archive or folder name
+    pub author: String,
+    pub path: std::path::PathBuf,
+    /// Whether the file has a video stream (by extension)
+    pub has_video: bool,
+    /// Last modified, as a Unix timestamp
+    pub modified: i64,
+}
+
+/// A channel result from channel search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Channel {
     pub id: String,
     pub name: String,
@@ -55,19 +305,78 @@ pub struct Subscription {
     pub name: String,
     /// @handle or /c/channelname or /channel/ID
     pub handle: String,
+    /// UC... channel ID, when known (needed for RSS feed URLs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    /// Videos to pull into the feed from this channel (default: 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Temporarily leave this channel out of the feed
+    #[serde(default)]
+    pub muted: bool,
+    /// Include this channel in new-video notifications
+    #[serde(default = "default_true")]
+    pub notify: bool,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            handle: String::new(),
+            channel_id: None,
+            limit: None,
+            muted: false,
+            notify: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
 }
 
 // ============================================
 // Config Types
 // ============================================
 
-/// Video player type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "lowercase")]
+/// Video player type. In config this is "mpv", "syncplay", "vlc", "mpd", or any other
+/// string, which is taken as a command template with `{url}` / `{title}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(from = "String", into = "String")]
 pub enum PlayerType {
     #[default]
     Mpv,
     Syncplay,
+    Vlc,
+    /// Add to a running MPD server's queue
+    Mpd,
+    /// Command template, e.g. "celluloid {url}"
+    Custom(String),
+}
+
+impl From<String> for PlayerType {
+    fn from(s: String) -> Self {
+        match s.trim() {
+            "mpv" => PlayerType::Mpv,
+            "syncplay" => PlayerType::Syncplay,
+            "vlc" => PlayerType::Vlc,
+            "mpd" => PlayerType::Mpd,
+            _ => PlayerType::Custom(s),
+        }
+    }
+}
+
+impl From<PlayerType> for String {
+    fn from(player: PlayerType) -> Self {
+        match player {
+            PlayerType::Mpv => "mpv".into(),
+            PlayerType::Syncplay => "syncplay".into(),
+            PlayerType::Vlc => "vlc".into(),
+            PlayerType::Mpd => "mpd".into(),
+            PlayerType::Custom(template) => template,
+        }
+    }
 }
 
 /// Menu selector type
@@ -77,11 +386,27 @@ pub enum SelectorType {
     #[default]
     Fzf,
     Rofi,
+    Dmenu,
+    Wofi,
     Dialoguer,
+    /// Built-in fuzzy finder (no external tools)
+    Builtin,
+}
+
+/// Where history, subscriptions and cache metadata are persisted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Plain JSON/txt files (always available)
+    #[default]
+    Json,
+    /// Single SQLite database (requires the `sqlite` feature)
+    Sqlite,
 }
 
 /// User configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Search result limit (default: 15)
     pub limit: usize,
@@ -91,14 +416,215 @@ pub struct Config {
     pub download_dir: String,
     /// Max history entries (default: 100)
     pub max_history_entries: usize,
+    /// Maximum number of past search queries kept
+    pub max_search_history: usize,
     /// Editor command (default: "nvim")
     pub editor: String,
     /// Video player
     pub player: PlayerType,
     /// Menu selector
     pub selector: SelectorType,
+    /// Extra arguments per selector, e.g. `rofi = ["-theme", "nord"]`
+    pub selector_args: BTreeMap<String, Vec<String>>,
+    /// Named searches run with `yt-chill @name`, e.g. `focus = "lofi hip hop radio"`
+    pub alias: BTreeMap<String, String>,
     /// Show desktop notifications
     pub notify: bool,
+    /// Show thumbnails next to fzf results (needs kitty or chafa)
+    pub thumbnail_previews: bool,
+    /// Go back to the main menu after playback or a download instead of the list
+    pub return_to_menu: bool,
+    /// Match searches against watch history too, listing past plays first
+    pub unified_search: bool,
+    /// Low-bitrate streams, no thumbnails and shorter feeds, for metered connections
+    pub data_saver: bool,
+    /// Tallest video to stream or download in video mode, e.g. 720 (0 = no limit)
+    pub max_resolution: u32,
+    /// yt-dlp binary to use (empty = the one in PATH)
+    pub yt_dlp_path: String,
+    /// Storage backend
+    pub storage: StorageBackend,
+    /// History menu ordering
+    pub history_sort: HistorySort,
+    /// Channel names never shown in search or feed results
+    pub blocked_channels: Vec<String>,
+    /// Title keywords that hide a video from search or feed results
+    pub blocked_keywords: Vec<String>,
+    /// Hide search and feed results shorter than this, e.g. "1h" (empty = no minimum)
+    pub min_duration: String,
+    /// Hide search and feed results longer than this, e.g. "3h" (empty = no maximum)
+    pub max_duration: String,
+    /// Default download rate limit, e.g. "500K" (empty = unlimited)
+    pub limit_rate: String,
+    /// How many videos to download at once from a playlist or multi-select
+    pub download_jobs: usize,
+    /// External downloader for yt-dlp, e.g. "aria2c" (empty = built-in)
+    pub external_downloader: String,
+    /// Arguments passed to the external downloader, e.g. "-x 16 -s 16"
+    pub external_downloader_args: String,
+    /// Codec for audio downloads
+    pub audio_format: AudioFormat,
+    /// yt-dlp audio quality: 0 (best) to 10 (worst), or a bitrate like "192K" (empty = yt-dlp default)
+    pub audio_quality: String,
+    /// Write tags and cover art into downloaded files
+    pub embed_metadata: bool,
+    /// yt-dlp pattern splitting video titles into tags (empty = keep the title as-is)
+    pub title_pattern: String,
+    /// yt-dlp output filename template, relative to download_dir
+    pub output_template: String,
+    /// Sort downloads into per-channel or per-artist folders
+    pub organize_downloads: OrganizeBy,
+    /// Shell command run before playback starts, with YT_CHILL_URL/TITLE/ID in the environment
+    pub pre_play_hook: String,
+    /// Text file kept up to date with what mpv is playing, for OBS text sources (empty = off)
+    pub now_playing_file: String,
+    /// MPD server for player = "mpd", as `[password@]host[:port]` (empty = $MPD_HOST or localhost:6600)
+    pub mpd_host: String,
+    /// Extra mpv arguments, e.g. ["--audio-device=pulse/headphones", "--cache-secs=60"]
+    pub mpv_args: Vec<String>,
+    /// Syncplay server used when hosting a room
+    pub syncplay_server: String,
+    /// Token `--serve` requires as `Authorization: Bearer <token>` (empty = none)
+    pub serve_token: String,
+    /// Browser whose YouTube cookies yt-dlp uses, e.g. "firefox" (empty = none)
+    pub cookies_from_browser: String,
+    /// HTTP or SOCKS proxy for all YouTube traffic, e.g. "socks5h://127.0.0.1:9050" (empty = none)
+    pub proxy: String,
+    /// Timeouts and retries for YouTube requests
+    pub network: NetworkConfig,
+    /// How long cached results stay fresh
+    pub cache: CacheConfig,
+    /// Icons and colors
+    pub theme: ThemeConfig,
+    /// Settings used with `--podcast`
+    pub podcast: PodcastConfig,
+    /// Where to send JSON events about playback and downloads
+    pub events: EventsConfig,
+}
+
+/// `[network]` section of the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Seconds to wait for a connection
+    pub connect_timeout_secs: u64,
+    /// Seconds to wait for a whole response
+    pub timeout_secs: u64,
+    /// Total tries per request when it times out, can't connect, or
+    /// gets a 429/5xx response (1 = no retries)
+    pub max_attempts: u32,
+    /// Ceiling on YouTube requests per minute (0 = unlimited)
+    pub requests_per_minute: u32,
+}
+
+/// `[events]` section of the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Shell command run per event, with the JSON on stdin and in $YT_CHILL_EVENT (empty = none)
+    pub command: String,
+    /// Unix socket to write each event to as a line of JSON (empty = none)
+    pub socket: String,
+}
+
+/// `[podcast]` section of the config, applied with `--podcast`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PodcastConfig {
+    /// Playback speed
+    pub speed: f64,
+    /// Pick up where you left off
+    pub resume: bool,
+    /// Only search for videos longer than 20 minutes
+    pub long_form: bool,
+    /// Show the current chapter while playing
+    pub chapters: bool,
+}
+
+impl Default for PodcastConfig {
+    fn default() -> Self {
+        Self {
+            speed: 1.25,
+            resume: true,
+            long_form: true,
+            chapters: true,
+        }
+    }
+}
+
+/// Icons shown in menus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    #[default]
+    Emoji,
+    /// Nerd Font glyphs
+    Nerd,
+    None,
+}
+
+/// `[theme]` section of the config. Colors are names like "cyan" or
+/// "bright black", or "dimmed" / "none".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub icons: IconStyle,
+    /// Channel names in result lists
+    pub channel_color: String,
+    /// Durations in result lists
+    pub duration_color: String,
+    /// Replay counts in the history list
+    pub play_count_color: String,
+    /// The LIVE marker on live streams
+    pub live_color: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            icons: IconStyle::default(),
+            channel_color: "cyan".into(),
+            duration_color: "dimmed".into(),
+            play_count_color: "yellow".into(),
+            live_color: "red".into(),
+        }
+    }
+}
+
+/// `[cache]` section of the config; TTLs are in seconds, 0 = don't cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Search results
+    pub search_ttl_secs: u64,
+    /// Recent uploads from subscribed channels
+    pub feed_ttl_secs: u64,
+    /// Details for a single video
+    pub video_ttl_secs: u64,
+    /// Evict least recently used entries beyond this size (0 = unlimited)
+    pub max_cache_size_mb: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            search_ttl_secs: 3600,
+            feed_ttl_secs: 900,
+            video_ttl_secs: 86400,
+            max_cache_size_mb: 100,
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: 10,
+            timeout_secs: 30,
+            max_attempts: 3,
+            requests_per_minute: 30,
+        }
+    }
 }
 
 impl Default for Config {
@@ -108,14 +634,70 @@ impl Default for Config {
             video_mode: false, // Audio-only by default
             download_dir: String::new(), // Set at runtime to ~/Downloads
             max_history_entries: 100,
+            max_search_history: 50,
             editor: "nvim".into(),
             player: PlayerType::default(),
             selector: SelectorType::default(),
+            selector_args: BTreeMap::new(),
+            alias: BTreeMap::new(),
             notify: true,
+            thumbnail_previews: true,
+            return_to_menu: false,
+            unified_search: false,
+            data_saver: false,
+            max_resolution: 0,
+            yt_dlp_path: String::new(),
+            storage: StorageBackend::default(),
+            history_sort: HistorySort::default(),
+            blocked_channels: Vec::new(),
+            blocked_keywords: Vec::new(),
+            min_duration: String::new(),
+            max_duration: String::new(),
+            limit_rate: String::new(),
+            download_jobs: 3,
+            external_downloader: String::new(),
+            external_downloader_args: String::new(),
+            audio_format: AudioFormat::default(),
+            audio_quality: String::new(),
+            embed_metadata: true,
+            title_pattern: "%(artist)s - %(title)s".into(),
+            output_template: "%(title)s [%(id)s].%(ext)s".into(),
+            organize_downloads: OrganizeBy::default(),
+            pre_play_hook: String::new(),
+            now_playing_file: String::new(),
+            mpd_host: String::new(),
+            mpv_args: Vec::new(),
+            syncplay_server: "syncplay.pl:8999".into(),
+            serve_token: String::new(),
+            cookies_from_browser: String::new(),
+            proxy: String::new(),
+            network: NetworkConfig::default(),
+            cache: CacheConfig::default(),
+            theme: ThemeConfig::default(),
+            podcast: PodcastConfig::default(),
+            events: EventsConfig::default(),
         }
     }
 }
 
+/// A problem found while loading the config file
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    /// Offending key (empty for whole-file problems)
+    pub field: String,
+    /// What is wrong
+    pub problem: String,
+    /// How to fix it
+    pub suggestion: Option<String>,
+}
+
+/// Loaded config plus any problems that were worked around
+#[derive(Debug, Clone)]
+pub struct ConfigReport {
+    pub config: Config,
+    pub issues: Vec<ConfigIssue>,
+}
+
 // ============================================
 // CLI Option Types
 // ============================================
@@ -157,36 +739,225 @@ pub struct PlayOptions {
     pub video: bool,
     /// yt-dlp format string
     pub format: Option<String>,
+    /// Tallest video stream to pick when there's no `format` (None = best available)
+    pub max_height: Option<u32>,
+    /// Stop playback after this much time
+    pub end: Option<std::time::Duration>,
+    /// Fade audio out over this long before `end`
+    pub fade_out: Option<std::time::Duration>,
+    /// Title of what's playing (for hooks and custom players)
+    pub title: Option<String>,
+    /// Channel of what's playing (for the now-playing file)
+    pub channel: Option<String>,
+    /// YouTube ID of what's playing (for hooks)
+    pub id: Option<String>,
+    /// Shell command run before the player starts
+    pub pre_play_hook: Option<String>,
+    /// Which player to launch
+    pub player: PlayerType,
+    /// MPD server as `[password@]host[:port]` (player = "mpd")
+    pub mpd_host: Option<String>,
+    /// Extra arguments appended to the mpv command line
+    pub mpv_args: Vec<String>,
+    /// Browser to borrow YouTube cookies from, e.g. "firefox"
+    pub cookies_from_browser: Option<String>,
+    /// Proxy URL, e.g. "socks5://127.0.0.1:9050"
+    pub proxy: Option<String>,
+    /// Start live streams from the beginning instead of the live edge
+    pub live_from_start: bool,
+    /// Playback speed (mpv; None = normal)
+    pub speed: Option<f64>,
+    /// Save the position on quit and resume from it next time (mpv)
+    pub resume: bool,
+    /// Show the current chapter and progress in the terminal (mpv)
+    pub chapters: bool,
+    /// Repeat this section (start, end in seconds) over and over (mpv)
+    pub ab_loop: Option<(u64, u64)>,
 }
 
-/// Options for video download
+/// How a playback session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackOutcome {
+    /// Played to the end (or the requested stop time)
+    Finished,
+    /// User quit the player
+    Quit,
+}
+
+/// A video played during a time-boxed session
 #[derive(Debug, Clone)]
+pub struct PlayedItem {
+    pub video: Video,
+    /// Wall-clock time spent playing it
+    pub listened: std::time::Duration,
+}
+
+/// Options for video download
+#[derive(Debug, Clone, Default)]
 pub struct DownloadOptions {
     /// Include video (false = audio-only)
     pub video: bool,
     /// yt-dlp format string
     pub format: Option<String>,
+    /// Tallest video to download when there's no `format` (None = best available)
+    pub max_height: Option<u32>,
     /// Output directory
     pub output_dir: String,
+    /// yt-dlp output template relative to `output_dir` (None = default)
+    pub output_template: Option<String>,
+    /// Folder to sort the file into under `output_dir`
+    pub organize: OrganizeBy,
+    /// Download again even if the archive says we already have it
+    pub force: bool,
+    /// Maximum download rate passed to yt-dlp, e.g. "500K"
+    pub limit_rate: Option<String>,
+    /// Codec for audio-only downloads
+    pub audio_format: AudioFormat,
+    /// yt-dlp `--audio-quality` value
+    pub audio_quality: Option<String>,
+    /// Embed tags and the thumbnail as cover art
+    pub embed_metadata: bool,
+    /// Pattern for parsing artist/title tags out of the video title
+    pub title_pattern: Option<String>,
+    /// External downloader yt-dlp hands fragments to, e.g. "aria2c"
+    pub external_downloader: Option<String>,
+    /// Extra arguments for the external downloader
+    pub external_downloader_args: Option<String>,
+    /// Browser to borrow YouTube cookies from, e.g. "firefox"
+    pub cookies_from_browser: Option<String>,
+    /// Proxy URL, e.g. "socks5://127.0.0.1:9050"
+    pub proxy: Option<String>,
+    /// Desktop notification when downloads finish
+    pub notify: bool,
 }
 
 /// Available video format/quality
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VideoFormat {
     /// Format code for yt-dlp
     pub code: String,
-    /// "1080p", "720p", etc.
+    /// Container, e.g. "webm" or "m4a"
+    pub ext: String,
+    /// "1080p", "720p", etc. ("audio only" for audio streams)
     pub resolution: String,
+    /// Height in pixels, for picking a quality (None = audio only)
+    pub height: Option<u32>,
     /// Framerate if available
     pub fps: Option<u32>,
-    /// Estimated size
-    pub filesize: Option<String>,
+    /// Average bitrate in kbit/s
+    pub bitrate: Option<f64>,
+    /// Size in bytes, exact or estimated
+    pub filesize: Option<u64>,
+    pub has_audio: bool,
+    pub has_video: bool,
+}
+
+/// A chapter of a video, in seconds from the start
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub title: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Whether a video is, was or will be a live stream (yt-dlp's `live_status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveStatus {
+    #[default]
+    NotLive,
+    IsLive,
+    IsUpcoming,
+    WasLive,
+    PostLive,
+}
+
+/// Everything yt-dlp knows about a video (`yt-dlp -J`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VideoMetadata {
+    pub id: String,
+    pub title: String,
+    pub uploader: String,
+    /// UC... channel ID
+    pub channel_id: Option<String>,
+    pub upload_date: Option<chrono::NaiveDate>,
+    /// Length in seconds (None for live streams)
+    pub duration: Option<u64>,
+    pub view_count: Option<u64>,
+    pub live_status: LiveStatus,
+    pub chapters: Vec<Chapter>,
+    /// Best last, the way yt-dlp lists them
+    pub formats: Vec<VideoFormat>,
 }
 
 // ============================================
 // Selector Types
 // ============================================
 
+/// What to do with a picked result (fzf key bindings; Enter plays)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SelectAction {
+    #[default]
+    Play,
+    Download,
+    Queue,
+    CopyUrl,
+    /// Show the list in the next `ResultSort` order
+    Sort,
+    /// Subscribe to the video's channel
+    Subscribe,
+}
+
+impl SelectAction {
+    /// fzf `--expect` key for each non-default action
+    pub const KEYS: [(&'static str, SelectAction); 5] = [
+        ("ctrl-d", SelectAction::Download),
+        ("ctrl-q", SelectAction::Queue),
+        ("ctrl-y", SelectAction::CopyUrl),
+        ("ctrl-s", SelectAction::Sort),
+        ("ctrl-b", SelectAction::Subscribe),
+    ];
+
+    /// Keys bound in a list. ctrl-s only where the list can be re-sorted
+    /// (search results); elsewhere it would just play.
+    pub fn bound(sortable: bool) -> Vec<(&'static str, SelectAction)> {
+        Self::KEYS
+            .iter()
+            .filter(|(_, action)| sortable || *action != SelectAction::Sort)
+            .copied()
+            .collect()
+    }
+
+    /// Hint shown with the list when `keys` are bound
+    pub fn hint(keys: &[(&str, SelectAction)]) -> String {
+        let mut hint = String::from("enter play");
+        for (key, action) in keys {
+            hint.push_str(&format!(" · {} {}", key, action.label()));
+        }
+        hint
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SelectAction::Play => "play",
+            SelectAction::Download => "download",
+            SelectAction::Queue => "queue",
+            SelectAction::CopyUrl => "copy URL",
+            SelectAction::Sort => "sort",
+            SelectAction::Subscribe => "subscribe",
+        }
+    }
+
+    /// Action bound to an fzf key (anything else, including Enter, plays)
+    pub fn from_key(key: &str) -> Self {
+        Self::KEYS
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, action)| *action)
+            .unwrap_or_default()
+    }
+}
+
 /// Item displayed in selector menu
 #[derive(Debug, Clone)]
 pub struct MenuItem<T> {
@@ -194,6 +965,15 @@ pub struct MenuItem<T> {
     pub label: String,
     /// Underlying value
     pub value: T,
+    /// Video ID whose thumbnail is shown in the preview pane
+    pub preview: Option<String>,
+}
+
+impl<T> MenuItem<T> {
+    /// An item without a thumbnail
+    pub fn new(label: impl Into<String>, value: T) -> Self {
+        Self { label: label.into(), value, preview: None }
+    }
 }
 
 // ============================================
@@ -209,23 +989,102 @@ pub enum AppState {
     Search,
     /// Browse history
     History,
+    /// Browse favorites by tag
+    Favorites,
+    /// Browse downloaded files
+    Library,
     /// Browse subscription feed
     Feed,
     /// Add subscription
     Subscribe,
     /// Play/download selected video
     Play,
+    /// Download or queue several selected videos
+    Batch,
+    /// Change config values
+    Settings,
     /// Exit application
     Exit,
 }
 
+/// The list a saved session was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionList {
+    Search,
+    History,
+    Favorites,
+    Feed,
+}
+
+impl SessionList {
+    /// The list `state` shows, if it's one worth coming back to
+    pub fn from_state(state: AppState) -> Option<Self> {
+        match state {
+            AppState::Search => Some(Self::Search),
+            AppState::History => Some(Self::History),
+            AppState::Favorites => Some(Self::Favorites),
+            AppState::Feed => Some(Self::Feed),
+            _ => None,
+        }
+    }
+
+    pub fn state(self) -> AppState {
+        match self {
+            Self::Search => AppState::Search,
+            Self::History => AppState::History,
+            Self::Favorites => AppState::Favorites,
+            Self::Feed => AppState::Feed,
+        }
+    }
+}
+
+/// Where the last interactive session was, for `--resume-session`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub list: SessionList,
+    /// What was searched for
+    #[serde(default)]
+    pub query: String,
+    /// Favorites tag being browsed (None = all favorites)
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// The list as it was shown
+    pub results: Vec<Video>,
+    /// Position of the last pick in `results`
+    pub selected: Option<usize>,
+    /// Unix timestamp
+    pub saved_at: i64,
+}
+
 // ============================================
 // Cache Types
 // ============================================
 
+/// Recent uploads from one channel, as last fetched for the feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelFeed {
+    /// Unix timestamp of the fetch
+    pub last_fetched: i64,
+    /// How many videos were asked for
+    pub limit: usize,
+    pub videos: Vec<Video>,
+}
+
+impl ChannelFeed {
+    /// Whether this still answers a request for `limit` videos, `ttl` seconds
+    /// being how long a fetch stays fresh
+    pub fn is_fresh(&self, limit: usize, ttl: u64, now: i64) -> bool {
+        self.limit >= limit && now - self.last_fetched < ttl as i64
+    }
+}
+
 /// Cached data with TTL
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry<T> {
+    /// Cache format version the entry was written with (0 before versioning)
+    #[serde(default)]
+    pub version: u32,
     pub data: T,
     pub timestamp: i64,
     /// Time-to-live in seconds
@@ -0,0 +1,246 @@
+//! Built-in fuzzy selector: typeahead filtering in the terminal, no fzf needed
+
+use crate::types::{MenuItem, SelectAction};
+use crate::utils::fuzzy::fuzzy_score;
+use console::{Key, Term, strip_ansi_codes, truncate_str};
+use std::io::{IsTerminal, Write};
+
+/// Rows of results shown at most (the list scrolls beyond this)
+const MAX_ROWS: usize = 15;
+
+#[derive(Default)]
+pub struct BuiltinSelector;
+
+/// Indices of the items matching `query`, best match first (original order
+/// for an empty query or equal scores)
+pub fn filter(labels: &[String], query: &str) -> Vec<usize> {
+    if query.trim().is_empty() {
+        return (0..labels.len()).collect();
+    }
+
+    let mut scored: Vec<(usize, i64)> = labels
+        .iter()
+        .enumerate()
+        .filter_map(|(i, label)| fuzzy_score(query, label).map(|score| (i, score)))
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+/// fzf-style name for a control key, e.g. "ctrl-d"
+fn ctrl_key_name(key: &Key) -> Option<String> {
+    match key {
+        Key::Char(c) if (*c as u32) >= 1 && (*c as u32) <= 26 => {
+            Some(format!("ctrl-{}", (b'a' + *c as u8 - 1) as char))
+        }
+        _ => None,
+    }
+}
+
+/// What the user did in the list
+struct Outcome {
+    action: SelectAction,
+    indices: Vec<usize>,
+}
+
+impl BuiltinSelector {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn select<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+    ) -> Option<T> {
+        let outcome = self.run(items, prompt, start, false, &[])?;
+        items.get(*outcome.indices.first()?).map(|item| item.value.clone())
+    }
+
+    /// Select an item along with the action key (one of `keys`) it was picked with
+    pub fn select_with_action<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+        keys: &[(&'static str, SelectAction)],
+    ) -> Option<(SelectAction, T)> {
+        let outcome = self.run(items, prompt, start, false, keys)?;
+        let item = items.get(*outcome.indices.first()?)?;
+        Some((outcome.action, item.value.clone()))
+    }
+
+    /// Let the user mark several items with Tab
+    pub fn select_many<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+    ) -> Vec<T> {
+        self.run(items, prompt, 0, true, &[])
+            .map(|outcome| outcome.indices)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|index| items.get(index).map(|item| item.value.clone()))
+            .collect()
+    }
+
+    pub fn is_available(&self) -> bool {
+        std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+    }
+
+    /// Draw the list on stderr, starting on item `start`, and handle keys
+    /// until the user picks or cancels
+    fn run<T>(&self, items: &[MenuItem<T>], prompt: &str, start: usize, multi: bool, actions: &[(&'static str, SelectAction)]) -> Option<Outcome> {
+        if items.is_empty() {
+            return None;
+        }
+
+        let term = Term::stderr();
+        let labels: Vec<String> = items.iter().map(|i| strip_ansi_codes(&i.label).to_string()).collect();
+        let mut query = String::new();
+        let mut matches = filter(&labels, &query);
+        let mut cursor = start.min(items.len() - 1);
+        let mut scroll = 0;
+        let mut marked: Vec<usize> = Vec::new();
+        let mut drawn = 0;
+
+        let result = loop {
+            let (rows, cols) = term.size();
+            let height = MAX_ROWS.min(rows as usize / 2).max(3);
+            if cursor < scroll {
+                scroll = cursor;
+            } else if cursor >= scroll + height {
+                scroll = cursor + 1 - height;
+            }
+
+            // Redraw: results, then the hint, then the prompt line with the cursor
+            let _ = term.clear_line();
+            let _ = term.clear_last_lines(drawn);
+            let mut lines = Vec::new();
+            for (row, &index) in matches.iter().enumerate().skip(scroll).take(height) {
+                let pointer = if row == cursor { "▌" } else { " " };
+                let mark = if marked.contains(&index) { "●" } else { " " };
+                let label = truncate_str(&items[index].label, (cols as usize).saturating_sub(4), "…");
+                lines.push(format!("{}{} {}", pointer, mark, label));
+            }
+            let mut info = format!("  {}/{}", matches.len(), items.len());
+            if multi {
+                info.push_str(&format!(" ({} marked, Tab to mark)", marked.len()));
+            } else if !actions.is_empty() {
+                info.push_str(&format!(" · {}", SelectAction::hint(actions)));
+            }
+            lines.push(truncate_str(&info, cols as usize, "…").to_string());
+            for line in &lines {
+                let _ = term.write_line(line);
+            }
+            let _ = write!(&term, "{} > {}", prompt, query);
+            let _ = term.flush();
+            drawn = lines.len();
+
+            let Ok(key) = term.read_key() else {
+                break None;
+            };
+            match key {
+                Key::Enter => {
+                    let Some(&current) = matches.get(cursor) else {
+                        continue;
+                    };
+                    let indices = if multi && !marked.is_empty() { marked.clone() } else { vec![current] };
+                    break Some(Outcome { action: SelectAction::Play, indices });
+                }
+                Key::Escape | Key::CtrlC => break None,
+                Key::ArrowUp => cursor = cursor.saturating_sub(1),
+                Key::ArrowDown if cursor + 1 < matches.len() => cursor += 1,
+                Key::PageUp => cursor = cursor.saturating_sub(height),
+                Key::PageDown => cursor = (cursor + height).min(matches.len().saturating_sub(1)),
+                Key::Tab if multi => {
+                    if let Some(&current) = matches.get(cursor) {
+                        match marked.iter().position(|&m| m == current) {
+                            Some(pos) => {
+                                marked.remove(pos);
+                            }
+                            None => marked.push(current),
+                        }
+                        if cursor + 1 < matches.len() {
+                            cursor += 1;
+                        }
+                    }
+                }
+                Key::Backspace => {
+                    query.pop();
+                    matches = filter(&labels, &query);
+                    cursor = 0;
+                }
+                ref k if ctrl_key_name(k).is_some() => {
+                    let name = ctrl_key_name(k).unwrap_or_default();
+                    match name.as_str() {
+                        "ctrl-p" => cursor = cursor.saturating_sub(1),
+                        "ctrl-n" if cursor + 1 < matches.len() => cursor += 1,
+                        "ctrl-u" => {
+                            query.clear();
+                            matches = filter(&labels, &query);
+                            cursor = 0;
+                        }
+                        _ if actions.iter().any(|(k, _)| *k == name) => {
+                            if let Some(&current) = matches.get(cursor) {
+                                break Some(Outcome {
+                                    action: SelectAction::from_key(&name),
+                                    indices: vec![current],
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Key::Char(c) if !c.is_control() => {
+                    query.push(c);
+                    matches = filter(&labels, &query);
+                    cursor = 0;
+                }
+                _ => {}
+            }
+        };
+
+        // Leave the terminal as we found it
+        let _ = term.clear_line();
+        let _ = term.clear_last_lines(drawn);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_ranks_matches() {
+        let labels: Vec<String> = ["Lofi Girl radio", "Jazz for work", "lofi hip hop beats"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(filter(&labels, ""), vec![0, 1, 2]);
+        assert_eq!(filter(&labels, "jazz"), vec![1]);
+        let lofi = filter(&labels, "lofi");
+        assert_eq!(lofi.len(), 2);
+        assert!(!lofi.contains(&1));
+        assert_eq!(ctrl_key_name(&Key::Char('\u{4}')).as_deref(), Some("ctrl-d"));
+        assert_eq!(ctrl_key_name(&Key::Char('d')), None);
+    }
+
+    #[test]
+    fn test_sort_key_only_where_sortable() {
+        let sortable = SelectAction::bound(true);
+        assert!(sortable.contains(&("ctrl-s", SelectAction::Sort)));
+        assert!(SelectAction::hint(&sortable).contains("ctrl-s sort"));
+
+        let fixed = SelectAction::bound(false);
+        assert_eq!(fixed.len(), SelectAction::KEYS.len() - 1);
+        assert!(fixed.iter().all(|(_, action)| *action != SelectAction::Sort));
+        assert_eq!(
+            SelectAction::hint(&fixed),
+            "enter play · ctrl-d download · ctrl-q queue · ctrl-y copy URL · ctrl-b subscribe"
+        );
+    }
+}
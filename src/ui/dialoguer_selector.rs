@@ -1,12 +1,23 @@
 //! dialoguer selector implementation (fallback)
 
+use super::theme::is_plain;
 use crate::types::MenuItem;
-use dialoguer::{theme::ColorfulTheme, Select};
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use dialoguer::{FuzzySelect, MultiSelect};
+
+/// Colorful prompts, or undecorated ones with `--plain`
+pub fn prompt_theme() -> Box<dyn Theme> {
+    if is_plain() {
+        Box::new(SimpleTheme)
+    } else {
+        Box::new(ColorfulTheme::default())
+    }
+}
 
 pub struct DialoguerSelector;
 
 impl DialoguerSelector {
-    pub fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self
     }
 
@@ -14,6 +25,7 @@ impl DialoguerSelector {
         &self,
         items: &[MenuItem<T>],
         prompt: &str,
+        start: usize,
     ) -> Option<T> {
         if items.is_empty() {
             return None;
@@ -21,10 +33,11 @@ impl DialoguerSelector {
 
         let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
 
-        let selection = Select::with_theme(&ColorfulTheme::default())
+        // Typing filters the list, like fzf does
+        let selection = FuzzySelect::with_theme(prompt_theme().as_ref())
             .with_prompt(prompt)
             .items(&labels)
-            .default(0)
+            .default(start.min(items.len() - 1))
             .interact_opt()
             .ok()
             .flatten()?;
@@ -32,7 +45,32 @@ impl DialoguerSelector {
         items.get(selection).map(|item| item.value.clone())
     }
 
-    #[allow(dead_code)]
+    /// Let the user mark several items with Space
+    pub fn select_many<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+    ) -> Vec<T> {
+        if items.is_empty() {
+            return Vec::new();
+        }
+
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+
+        let selection = MultiSelect::with_theme(prompt_theme().as_ref())
+            .with_prompt(format!("{} (Space to mark, Enter to confirm)", prompt))
+            .items(&labels)
+            .interact_opt()
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+
+        selection
+            .into_iter()
+            .filter_map(|index| items.get(index).map(|item| item.value.clone()))
+            .collect()
+    }
+
     pub fn is_available(&self) -> bool {
         true  // Always available as fallback
     }
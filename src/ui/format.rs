@@ -0,0 +1,185 @@
+//! Label formatting for selector menus and command output
+
+use super::theme::{Icon, paint, theme, with_icon};
+use crate::types::{Channel, HistoryEntry, Video};
+use std::sync::OnceLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns for the channel name
+const CHANNEL_WIDTH: usize = 18;
+
+/// Columns for the duration, right-aligned ("1:02:03" fits, "🔴 LIVE" too)
+const DURATION_WIDTH: usize = 8;
+
+/// Space between columns
+const GAP: &str = "  ";
+
+/// Titles never get narrower than this, even in tiny terminals
+const MIN_TITLE_WIDTH: usize = 20;
+
+/// Columns taken by selector chrome (fzf pointer, marker, scrollbar)
+const SELECTOR_MARGIN: usize = 4;
+
+/// Width labels are fitted to when the terminal size is unknown
+const FALLBACK_WIDTH: usize = 100;
+
+static LIST_WIDTH: OnceLock<usize> = OnceLock::new();
+
+/// Terminal width in columns, if stdout is a terminal
+pub fn terminal_width() -> Option<usize> {
+    console::Term::stdout()
+        .size_checked()
+        .map(|(_, cols)| cols as usize)
+}
+
+/// Fit labels into `width` columns (first call wins), e.g. when a preview
+/// pane takes part of the terminal
+pub fn set_list_width(width: usize) {
+    LIST_WIDTH.get_or_init(|| width);
+}
+
+/// Columns available for a label
+fn list_width() -> usize {
+    let width = LIST_WIDTH
+        .get()
+        .copied()
+        .or_else(terminal_width)
+        .unwrap_or(FALLBACK_WIDTH);
+    width.saturating_sub(SELECTOR_MARGIN)
+}
+
+/// Truncate `s` to at most `width` display columns (ending in "…" if cut),
+/// then pad with spaces to exactly `width`. Wide CJK characters and emoji
+/// count as two columns.
+pub fn fit(s: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut used = 0;
+
+    if s.width() <= width {
+        out.push_str(s);
+        used = s.width();
+    } else if width > 0 {
+        for c in s.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width - 1 {
+                break;
+            }
+            out.push(c);
+            used += w;
+        }
+        out.push('…');
+        used += 1;
+    }
+
+    out.push_str(&" ".repeat(width.saturating_sub(used)));
+    out
+}
+
+/// Like [`fit`], but cut from the front ("…/Music/song.mp3"), for paths
+/// where the end matters most
+pub fn fit_start(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return fit(s, width);
+    }
+    let mut kept = Vec::new();
+    let mut used = 1;
+    for c in s.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width {
+            break;
+        }
+        kept.push(c);
+        used += w;
+    }
+    let mut out = String::from(if width > 0 { "…" } else { "" });
+    out.extend(kept.iter().rev());
+    out.push_str(&" ".repeat(width.saturating_sub(out.width())));
+    out
+}
+
+/// Right-align `s` in `width` display columns
+fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(s.width())), s)
+}
+
+/// Title, channel and duration as aligned columns, leaving `reserved`
+/// columns at the end for a suffix
+fn video_columns(video: &Video, reserved: usize) -> String {
+    let fixed = CHANNEL_WIDTH + DURATION_WIDTH + 2 * GAP.len() + reserved;
+    let title_width = list_width().saturating_sub(fixed).max(MIN_TITLE_WIDTH);
+    let colors = theme().config;
+    let duration = if video.is_live() {
+        paint(&pad_left(&with_icon(Icon::Live, "LIVE"), DURATION_WIDTH), &colors.live_color)
+    } else {
+        paint(&pad_left(&video.duration, DURATION_WIDTH), &colors.duration_color)
+    };
+
+    format!(
+        "{}{}{}{}{}",
+        fit(&video.title, title_width),
+        GAP,
+        paint(&fit(&video.author, CHANNEL_WIDTH), &colors.channel_color),
+        GAP,
+        duration
+    )
+}
+
+/// Format video for display in selector
+pub fn format_video_label(video: &Video) -> String {
+    video_columns(video, 0)
+}
+
+/// Format a history entry, noting replays
+pub fn format_history_label(entry: &HistoryEntry) -> String {
+    with_plays(&entry.video, (entry.play_count > 1).then_some(entry.play_count))
+}
+
+/// Format a result of a search that includes history, marking videos
+/// played before with their play count
+pub fn format_search_label(video: &Video, played: Option<&HistoryEntry>) -> String {
+    with_plays(video, played.map(|e| e.play_count))
+}
+
+/// Format a channel search result: name, handle and subscriber count
+pub fn format_channel_label(channel: &Channel) -> String {
+    let colors = &theme().config;
+    let mut label = format!("{} ({})", channel.name, paint(&channel.handle, &colors.channel_color));
+    if !channel.subscribers.is_empty() {
+        label.push_str(&format!("  {}", paint(&channel.subscribers, "dimmed")));
+    }
+    label
+}
+
+/// Video columns followed by a play count, if any
+fn with_plays(video: &Video, plays: Option<u32>) -> String {
+    // Room for " ×NN" so play counts don't push the columns around
+    const PLAYS_WIDTH: usize = 5;
+    let label = video_columns(video, PLAYS_WIDTH);
+    let plays = plays.map(|n| format!("×{}", n)).unwrap_or_default();
+    format!("{} {}", label, paint(&fit(&plays, PLAYS_WIDTH - 1), &theme().config.play_count_color))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_counts_display_width() {
+        assert_eq!(fit("lofi", 6), "lofi  ");
+        assert_eq!(fit("lofi hip hop", 6), "lofi …");
+        // CJK characters are two columns wide
+        assert_eq!(fit("日本語の曲", 5), "日本…");
+        assert_eq!(fit("日本語の曲", 6), "日本… ");
+        assert_eq!(fit("日本語の曲", 6).width(), 6);
+        assert_eq!(pad_left("3:45", 8), "    3:45");
+    }
+
+    #[test]
+    fn test_fit_start_keeps_the_end() {
+        assert_eq!(fit_start("a.mp3", 7), "a.mp3  ");
+        assert_eq!(fit_start("/home/me/Music/a.mp3", 10), "…sic/a.mp3");
+        // A wide character that doesn't fit leaves a space instead
+        assert_eq!(fit_start("/曲/曲.mp3", 6), "….mp3 ");
+        assert_eq!(fit_start("/曲/曲.mp3", 6).width(), 6);
+    }
+}
@@ -1,73 +1,163 @@
 //! fzf selector implementation
 
-use crate::types::MenuItem;
+use super::theme::is_plain;
+use crate::core::thumbnail;
+use crate::types::{MenuItem, SelectAction};
 use std::io::Write;
 use std::process::{Command, Stdio};
+use tracing::debug;
 
-pub struct FzfSelector;
+/// Share of the terminal width given to the thumbnail pane
+pub const PREVIEW_PERCENT: usize = 40;
+
+pub struct FzfSelector {
+    /// `--preview` command drawing the thumbnail for field 3 (the video ID)
+    preview_command: Option<String>,
+}
 
 impl FzfSelector {
-    pub fn new() -> Self {
-        Self
+    pub(crate) fn new() -> Self {
+        Self {
+            preview_command: None,
+        }
+    }
+
+    /// Show thumbnails for items that have a preview, if the terminal can draw them
+    pub fn with_thumbnails(mut self, enabled: bool) -> Self {
+        self.preview_command = if enabled {
+            thumbnail::preview_command("{3}")
+        } else {
+            None
+        };
+        self
     }
 
     pub fn select<T: Clone + Send + 'static>(
         &self,
         items: &[MenuItem<T>],
         prompt: &str,
+        start: usize,
     ) -> Option<T> {
+        let (_, indices) = self.run(items, prompt, start, false, &[]);
+        items.get(*indices.first()?).map(|item| item.value.clone())
+    }
+
+    /// Select an item along with the action key (one of `keys`) it was picked with
+    pub fn select_with_action<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+        keys: &[(&'static str, SelectAction)],
+    ) -> Option<(SelectAction, T)> {
+        let (key, indices) = self.run(items, prompt, start, false, keys);
+        let item = items.get(*indices.first()?)?;
+        Some((SelectAction::from_key(&key), item.value.clone()))
+    }
+
+    /// Whether results get a thumbnail pane beside them
+    pub fn has_preview(&self) -> bool {
+        self.preview_command.is_some()
+    }
+
+    /// Let the user mark several items with Tab (`--multi`)
+    pub fn select_many<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+    ) -> Vec<T> {
+        self.run(items, prompt, 0, true, &[])
+            .1
+            .into_iter()
+            .filter_map(|index| items.get(index).map(|item| item.value.clone()))
+            .collect()
+    }
+
+    /// Run fzf with the cursor on item `start` and return the key pressed (with
+    /// `expect`; empty for Enter) and the indices of the chosen items (empty if cancelled)
+    fn run<T>(&self, items: &[MenuItem<T>], prompt: &str, start: usize, multi: bool, expect: &[(&'static str, SelectAction)]) -> (String, Vec<usize>) {
+        let cancelled = (String::new(), Vec::new());
         if items.is_empty() {
-            return None;
+            return cancelled;
         }
 
-        // Build input: one item per line with index prefix
+        // Build input: one item per line as index, label, preview video ID
         let input: String = items
             .iter()
             .enumerate()
-            .map(|(i, item)| format!("{}\t{}", i, item.label))
+            .map(|(i, item)| format!("{}\t{}\t{}", i, item.label, item.preview.as_deref().unwrap_or("")))
             .collect::<Vec<_>>()
             .join("\n");
 
+        let mut args = vec![
+            "--prompt".to_string(), format!("{} > ", prompt),
+            "--height".into(), "40%".into(),
+            "--reverse".into(),
+            "--ansi".into(),
+            "--delimiter".into(), "\t".into(),
+            "--with-nth".into(), "2".into(),  // Show only the label
+        ];
+        if start > 0 {
+            args.push(format!("--bind=load:pos({})", start + 1));
+        }
+        if multi {
+            args.push("--multi".into());
+        }
+        if is_plain() {
+            args.push("--no-color".into());
+        }
+        if !expect.is_empty() {
+            let keys: Vec<&str> = expect.iter().map(|(k, _)| *k).collect();
+            args.push(format!("--expect={}", keys.join(",")));
+            args.extend(["--header".into(), SelectAction::hint(expect)]);
+        }
+        if let Some(ref command) = self.preview_command
+            && items.iter().any(|item| item.preview.is_some())
+        {
+            args.extend([
+                "--preview".into(), command.clone(),
+                "--preview-window".into(), format!("right,{}%", PREVIEW_PERCENT),
+            ]);
+        }
+
         // Spawn fzf
-        let mut child = Command::new("fzf")
-            .args([
-                "--prompt", &format!("{} > ", prompt),
-                "--height", "40%",
-                "--reverse",
-                "--ansi",
-                "--delimiter", "\t",
-                "--with-nth", "2",  // Show only the label
-            ])
+        debug!("Running fzf {}", args.join(" "));
+        let Ok(mut child) = Command::new("fzf")
+            .args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .spawn()
-            .ok()?;
+        else {
+            return cancelled;
+        };
 
         // Write input to stdin
-        if let Some(mut stdin) = child.stdin.take() {
-            stdin.write_all(input.as_bytes()).ok()?;
+        if let Some(mut stdin) = child.stdin.take()
+            && stdin.write_all(input.as_bytes()).is_err()
+        {
+            return cancelled;
         }
 
         // Get output
-        let output = child.wait_with_output().ok()?;
+        let Ok(output) = child.wait_with_output() else {
+            return cancelled;
+        };
 
         if !output.status.success() {
-            return None;  // User cancelled
+            return cancelled;  // User cancelled
         }
 
+        // With --expect the first line is the key pressed
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let line = stdout.trim();
-
-        if line.is_empty() {
-            return None;
-        }
-
-        // Extract index from selection
-        let index_str = line.split('\t').next()?;
-        let index: usize = index_str.parse().ok()?;
+        let mut lines = stdout.lines();
+        let key = if !expect.is_empty() { lines.next().unwrap_or("").to_string() } else { String::new() };
 
-        items.get(index).map(|item| item.value.clone())
+        // Extract the index from each selected line
+        let indices = lines
+            .filter_map(|line| line.split('\t').next()?.parse().ok())
+            .collect();
+        (key, indices)
     }
 
     pub fn is_available(&self) -> bool {
@@ -0,0 +1,151 @@
+//! dmenu-style launcher selectors: dmenu (X11), rofi and wofi (Wayland)
+
+use crate::types::MenuItem;
+use crate::utils::paths::find_executable;
+use console::strip_ansi_codes;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tracing::debug;
+
+/// Which launcher to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Launcher {
+    Dmenu,
+    Rofi,
+    Wofi,
+}
+
+impl Launcher {
+    /// Executable name, also the key for `selector_args` in the config
+    pub fn binary(self) -> &'static str {
+        match self {
+            Launcher::Dmenu => "dmenu",
+            Launcher::Rofi => "rofi",
+            Launcher::Wofi => "wofi",
+        }
+    }
+
+    /// Arguments for a case-insensitive prompt reading choices from stdin
+    fn base_args(self, prompt: &str, multi: bool) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            Launcher::Dmenu => vec!["-i".into(), "-l".into(), "20".into(), "-p".into(), prompt.into()],
+            // rofi can print the index of the choice instead of its text
+            Launcher::Rofi => vec!["-dmenu".into(), "-i".into(), "-p".into(), prompt.into(), "-format".into(), "i".into()],
+            Launcher::Wofi => vec!["--dmenu".into(), "-i".into(), "-p".into(), prompt.into()],
+        };
+        if multi && self == Launcher::Rofi {
+            args.push("-multi-select".into());
+        }
+        args
+    }
+}
+
+pub struct LauncherSelector {
+    launcher: Launcher,
+    /// Extra arguments from `selector_args`, e.g. a rofi theme
+    extra_args: Vec<String>,
+}
+
+impl LauncherSelector {
+    pub fn new(launcher: Launcher, extra_args: Vec<String>) -> Self {
+        Self { launcher, extra_args }
+    }
+
+    pub fn select<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+    ) -> Option<T> {
+        let index = *self.run(items, prompt, start, false).first()?;
+        items.get(index).map(|item| item.value.clone())
+    }
+
+    /// Several items with rofi's multi-select (Shift+Enter); dmenu and wofi pick one
+    pub fn select_many<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+    ) -> Vec<T> {
+        self.run(items, prompt, 0, true)
+            .into_iter()
+            .filter_map(|index| items.get(index).map(|item| item.value.clone()))
+            .collect()
+    }
+
+    pub fn is_available(&self) -> bool {
+        find_executable(self.launcher.binary()).is_some()
+    }
+
+    /// Run the launcher (highlighting item `start` where supported) and
+    /// return the indices of the chosen items
+    fn run<T>(&self, items: &[MenuItem<T>], prompt: &str, start: usize, multi: bool) -> Vec<usize> {
+        if items.is_empty() {
+            return Vec::new();
+        }
+
+        // Launchers don't render ANSI colors
+        let labels: Vec<String> = items.iter().map(|i| strip_ansi_codes(&i.label).to_string()).collect();
+
+        let mut args = self.launcher.base_args(prompt, multi);
+        if self.launcher == Launcher::Rofi && start > 0 {
+            args.extend(["-selected-row".into(), start.to_string()]);
+        }
+        args.extend(self.extra_args.iter().cloned());
+
+        debug!("Running {} {}", self.launcher.binary(), args.join(" "));
+        let Ok(mut child) = Command::new(self.launcher.binary())
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+        else {
+            return Vec::new();
+        };
+
+        if let Some(mut stdin) = child.stdin.take()
+            && stdin.write_all(labels.join("\n").as_bytes()).is_err()
+        {
+            return Vec::new();
+        }
+
+        let Ok(output) = child.wait_with_output() else {
+            return Vec::new();
+        };
+        if !output.status.success() {
+            return Vec::new(); // Cancelled
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_choices(self.launcher, &stdout, &labels)
+    }
+}
+
+/// Map launcher output back to item indices: rofi prints indices, dmenu
+/// and wofi print the chosen label
+fn parse_choices(launcher: Launcher, stdout: &str, labels: &[String]) -> Vec<usize> {
+    stdout
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| match launcher {
+            Launcher::Rofi => line.trim().parse().ok(),
+            Launcher::Dmenu | Launcher::Wofi => labels.iter().position(|label| label == line),
+        })
+        .filter(|&index| index < labels.len())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choices() {
+        let labels = vec!["Lofi Girl".to_string(), "Jazz".to_string()];
+        assert_eq!(parse_choices(Launcher::Rofi, "1\n0\n", &labels), vec![1, 0]);
+        assert_eq!(parse_choices(Launcher::Dmenu, "Jazz\n", &labels), vec![1]);
+        // Typed text that isn't in the list picks nothing
+        assert!(parse_choices(Launcher::Wofi, "jaz\n", &labels).is_empty());
+        assert!(parse_choices(Launcher::Rofi, "7\n", &labels).is_empty());
+    }
+}
@@ -1,5 +1,13 @@
-//! UI selectors: fzf, dialoguer
+//! UI selectors: fzf, built-in, dmenu/rofi/wofi, dialoguer; mpv's now-playing
+//! line; first-run setup
 
+pub mod builtin;
 pub mod dialoguer_selector;
+pub mod format;
 pub mod fzf;
+pub mod launcher;
+#[cfg(unix)]
+pub mod now_playing;
 pub mod selector;
+pub mod setup;
+pub mod theme;
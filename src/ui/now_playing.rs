@@ -0,0 +1,331 @@
+//! Live now-playing line for mpv: title, elapsed/total, pause state and
+//! volume, kept up to date over mpv's JSON IPC. Keys typed into the
+//! terminal are relayed to mpv, so its own bindings keep working.
+
+use super::format::{fit, terminal_width};
+use super::theme::{Icon, with_icon};
+use crate::core::events;
+use crate::core::mpv_ipc::MpvIpc;
+use crate::utils::time::format_clock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+use unicode_width::UnicodeWidthStr;
+
+/// Terminal settings to put back when the line goes away
+static SAVED_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// What mpv is playing, as far as the line is concerned
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Status {
+    title: Option<String>,
+    elapsed: Option<f64>,
+    duration: Option<f64>,
+    paused: bool,
+    volume: Option<f64>,
+    chapter: Option<String>,
+    loop_a: Option<f64>,
+    loop_b: Option<f64>,
+}
+
+impl Status {
+    /// mpv properties to observe for [`Status::update`]
+    pub const PROPERTIES: &[&str] = &[
+        "media-title",
+        "time-pos",
+        "duration",
+        "pause",
+        "volume",
+        "chapter-metadata/title",
+        "ab-loop-a",
+        "ab-loop-b",
+    ];
+
+    /// Apply a `property-change` event. Returns the new pause state if it changed.
+    pub fn update(&mut self, event: &Value) -> Option<bool> {
+        let was_paused = self.paused;
+        self.apply(event);
+        (self.paused != was_paused).then_some(self.paused)
+    }
+
+    /// mpv's title for what's playing
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or("")
+    }
+
+    fn apply(&mut self, event: &Value) {
+        if event.get("event").and_then(Value::as_str) != Some("property-change") {
+            return;
+        }
+        let Some(name) = event.get("name").and_then(Value::as_str) else {
+            return;
+        };
+        let data = event.get("data").unwrap_or(&Value::Null);
+        match name {
+            "media-title" => self.title = data.as_str().map(str::to_string),
+            "time-pos" => self.elapsed = data.as_f64(),
+            "duration" => self.duration = data.as_f64(),
+            "pause" => self.paused = data.as_bool().unwrap_or(false),
+            "volume" => self.volume = data.as_f64(),
+            "chapter-metadata/title" => self.chapter = data.as_str().map(str::to_string),
+            "ab-loop-a" => self.loop_a = data.as_f64(),
+            "ab-loop-b" => self.loop_b = data.as_f64(),
+            _ => {}
+        }
+    }
+
+    /// The line, fitted into `width` columns (the title gets cut first)
+    pub fn render(&self, width: usize) -> String {
+        let Some(elapsed) = self.elapsed else {
+            return "⏳ Convincing YouTube to share... 🙄".to_string();
+        };
+
+        let mut info = format_clock(elapsed as u64);
+        if let Some(duration) = self.duration {
+            info.push_str(&format!(" / {}", format_clock(duration as u64)));
+        }
+        if let Some(volume) = self.volume {
+            info.push_str(&format!("  vol {}%", volume.round() as u64));
+        }
+        match (self.loop_a, self.loop_b) {
+            (Some(a), Some(b)) => {
+                info.push_str(&format!("  loop {}-{}", format_clock(a as u64), format_clock(b as u64)))
+            }
+            (Some(a), None) => info.push_str(&format!("  loop {}-", format_clock(a as u64))),
+            _ => {}
+        }
+
+        let state = if self.paused {
+            with_icon(Icon::Paused, "Paused")
+        } else {
+            with_icon(Icon::Playing, "")
+        };
+        let mut title = self.title.clone().unwrap_or_default();
+        if let Some(ref chapter) = self.chapter {
+            title.push_str(&format!(" · {}", chapter));
+        }
+
+        let prefix = if state.trim().is_empty() {
+            String::new()
+        } else {
+            format!("{} ", state.trim())
+        };
+        // One spare column so the cursor never wraps the line
+        let room = width
+            .saturating_sub(prefix.width() + info.width() + 3)
+            .max(1);
+        format!("{}{}  {}", prefix, fit(&title, room).trim_end(), info)
+    }
+}
+
+/// Whether to show the line: only with a terminal to draw on and read keys from
+pub fn enabled() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Show the line for the mpv listening on `socket` until it exits, relaying
+/// keys typed in the terminal. mpv should run with `--terminal=no` so it
+/// doesn't compete for stdin.
+pub async fn show(socket: PathBuf) {
+    let Ok(mut ipc) = MpvIpc::connect(&socket).await else {
+        return;
+    };
+    for (id, property) in Status::PROPERTIES.iter().enumerate() {
+        if ipc.observe(id as u64 + 1, property).await.is_err() {
+            return;
+        }
+    }
+
+    let (keys, mut key_rx) = mpsc::channel(16);
+    let stop = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || read_keys(keys, stop))
+    };
+
+    let mut status = Status::default();
+    let mut shown = String::new();
+    draw(&status.render(terminal_width().unwrap_or(80)), &mut shown);
+    loop {
+        tokio::select! {
+            event = ipc.next_event() => {
+                let Some(event) = event else { break };
+                if let Some(paused) = status.update(&event) {
+                    events::emit(events::pause_event(paused, status.title())).await;
+                }
+                draw(&status.render(terminal_width().unwrap_or(80)), &mut shown);
+            }
+            Some(key) = key_rx.recv() => {
+                let sent = match key.as_str() {
+                    // Skip to the next track (or end this one)
+                    "n" => ipc.command(&["playlist-next", "force"]).await,
+                    // Exit code 4, which we report as the user quitting
+                    "q" => ipc.command(&["quit", "4"]).await,
+                    _ => ipc.keypress(&key).await,
+                };
+                if sent.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    let _ = tokio::task::spawn_blocking(move || reader.join()).await;
+    clear();
+}
+
+/// Erase the line, e.g. before printing something else
+pub fn clear() {
+    print!("\r\x1b[K");
+    std::io::stdout().flush().ok();
+}
+
+/// Redraw the line if it changed
+fn draw(line: &str, shown: &mut String) {
+    if line != shown {
+        print!("\r\x1b[K{}", line);
+        std::io::stdout().flush().ok();
+        *shown = line.to_string();
+    }
+}
+
+/// Read keys from the terminal until `stop` is set
+fn read_keys(keys: mpsc::Sender<String>, stop: Arc<AtomicBool>) {
+    if !enter_raw_mode() {
+        return;
+    }
+    let mut stdin = std::io::stdin();
+    let mut buf = [0u8; 16];
+    while !stop.load(Ordering::Relaxed) {
+        // Returns after a tenth of a second without input, see enter_raw_mode
+        let n = match stdin.read(&mut buf) {
+            Ok(n) => n,
+            Err(_) => break,
+        };
+        for key in key_names(&buf[..n]) {
+            if keys.blocking_send(key).is_err() {
+                break;
+            }
+        }
+    }
+    leave_raw_mode();
+}
+
+/// mpv key names for raw terminal input, e.g. "SPACE", "LEFT" or "p"
+pub fn key_names(bytes: &[u8]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let name = match bytes[i] {
+            0x1b if bytes.get(i + 1) == Some(&b'[') => {
+                let (name, len) = match bytes.get(i + 2..i + 4) {
+                    Some([b'5', b'~']) => ("PGUP", 4),
+                    Some([b'6', b'~']) => ("PGDWN", 4),
+                    _ => match bytes.get(i + 2) {
+                        Some(b'A') => ("UP", 3),
+                        Some(b'B') => ("DOWN", 3),
+                        Some(b'C') => ("RIGHT", 3),
+                        Some(b'D') => ("LEFT", 3),
+                        Some(b'H') => ("HOME", 3),
+                        Some(b'F') => ("END", 3),
+                        _ => ("", 2),
+                    },
+                };
+                i += len;
+                if !name.is_empty() {
+                    names.push(name.to_string());
+                }
+                continue;
+            }
+            0x1b => "ESC".to_string(),
+            b' ' => "SPACE".to_string(),
+            b'\r' | b'\n' => "ENTER".to_string(),
+            0x7f => "BS".to_string(),
+            b'#' => "SHARP".to_string(),
+            c if c.is_ascii_graphic() => (c as char).to_string(),
+            _ => String::new(),
+        };
+        i += 1;
+        if !name.is_empty() {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Unbuffered, unechoed input with reads that time out, so the key reader
+/// can notice when to stop. Ctrl+C still raises SIGINT.
+fn enter_raw_mode() -> bool {
+    // SAFETY: termios is plain data, filled in by tcgetattr before use
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+            return false;
+        }
+        if let Ok(mut saved) = SAVED_TERMIOS.lock() {
+            *saved = Some(termios);
+        }
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+        termios.c_cc[libc::VMIN] = 0;
+        termios.c_cc[libc::VTIME] = 1;
+        libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) == 0
+    }
+}
+
+/// Put the terminal back the way [`enter_raw_mode`] found it
+pub fn leave_raw_mode() {
+    let saved = SAVED_TERMIOS.lock().ok().and_then(|mut saved| saved.take());
+    if let Some(termios) = saved {
+        // SAFETY: restoring settings tcgetattr returned
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(key_names(b" "), vec!["SPACE"]);
+        assert_eq!(key_names(b"\x1b[C\x1b[Dq"), vec!["RIGHT", "LEFT", "q"]);
+        assert_eq!(key_names(b"\x1b[5~#"), vec!["PGUP", "SHARP"]);
+        assert_eq!(key_names(b"\x1b"), vec!["ESC"]);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut status = Status::default();
+        assert!(status.render(80).contains("Convincing YouTube"));
+
+        for (name, data) in [
+            ("media-title", json!("Lofi Beats")),
+            ("time-pos", json!(83.4)),
+            ("duration", json!(225.0)),
+            ("volume", json!(80.0)),
+            ("pause", json!(true)),
+        ] {
+            status.update(&json!({"event": "property-change", "name": name, "data": data}));
+        }
+        let line = status.render(80);
+        assert!(line.contains("Paused"));
+        assert!(line.contains("Lofi Beats"));
+        assert!(line.ends_with("1:23 / 3:45  vol 80%"));
+
+        // Narrow terminals cut the title, not the progress
+        let narrow = status.render(36);
+        assert!(narrow.contains('…'));
+        assert!(narrow.ends_with("1:23 / 3:45  vol 80%"));
+    }
+}
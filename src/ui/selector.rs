@@ -1,12 +1,16 @@
 //! Selector trait and factory
 
-use crate::types::{MenuItem, SelectorType};
+use crate::types::{Config, MenuItem, SelectAction, SelectorType};
+use super::builtin::BuiltinSelector;
 use super::fzf::FzfSelector;
+use super::launcher::{Launcher, LauncherSelector};
 use super::dialoguer_selector::DialoguerSelector;
 
 /// Selector enum for interactive menus
 pub enum Selector {
     Fzf(FzfSelector),
+    Builtin(BuiltinSelector),
+    Launcher(LauncherSelector),
     Dialoguer(DialoguerSelector),
 }
 
@@ -17,46 +21,110 @@ impl Selector {
         items: &[MenuItem<T>],
         prompt: &str,
     ) -> Option<T> {
+        self.select_at(items, prompt, 0)
+    }
+
+    /// Select an item, starting with the cursor on item `start`
+    /// (to come back to the same place in a list)
+    pub fn select_at<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+    ) -> Option<T> {
+        match self {
+            Selector::Fzf(s) => s.select(items, prompt, start),
+            Selector::Builtin(s) => s.select(items, prompt, start),
+            Selector::Launcher(s) => s.select(items, prompt, start),
+            Selector::Dialoguer(s) => s.select(items, prompt, start),
+        }
+    }
+
+    /// Select an item and an action for it, starting on item `start`. fzf and
+    /// the built-in selector bind keys to actions (see `SelectAction::bound`;
+    /// `sortable` adds ctrl-s); launchers and dialoguer always play.
+    pub fn select_with_action<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+        start: usize,
+        sortable: bool,
+    ) -> Option<(SelectAction, T)> {
+        let keys = SelectAction::bound(sortable);
+        match self {
+            Selector::Fzf(s) => s.select_with_action(items, prompt, start, &keys),
+            Selector::Builtin(s) => s.select_with_action(items, prompt, start, &keys),
+            Selector::Launcher(s) => s.select(items, prompt, start).map(|v| (SelectAction::Play, v)),
+            Selector::Dialoguer(s) => s.select(items, prompt, start).map(|v| (SelectAction::Play, v)),
+        }
+    }
+
+    /// Select any number of items (empty if cancelled)
+    pub fn select_many<T: Clone + Send + 'static>(
+        &self,
+        items: &[MenuItem<T>],
+        prompt: &str,
+    ) -> Vec<T> {
+        match self {
+            Selector::Fzf(s) => s.select_many(items, prompt),
+            Selector::Builtin(s) => s.select_many(items, prompt),
+            Selector::Launcher(s) => s.select_many(items, prompt),
+            Selector::Dialoguer(s) => s.select_many(items, prompt),
+        }
+    }
+
+    /// Whether a preview pane takes part of the terminal width
+    pub fn has_preview(&self) -> bool {
         match self {
-            Selector::Fzf(s) => s.select(items, prompt),
-            Selector::Dialoguer(s) => s.select(items, prompt),
+            Selector::Fzf(s) => s.has_preview(),
+            Selector::Builtin(_) | Selector::Launcher(_) | Selector::Dialoguer(_) => false,
         }
     }
 
     /// Check if selector is available
-    #[allow(dead_code)]
     pub fn is_available(&self) -> bool {
         match self {
             Selector::Fzf(s) => s.is_available(),
+            Selector::Builtin(s) => s.is_available(),
+            Selector::Launcher(s) => s.is_available(),
             Selector::Dialoguer(s) => s.is_available(),
         }
     }
 }
 
 /// Create a selector based on type
-pub fn create_selector(selector_type: SelectorType) -> Selector {
+pub fn create_selector(selector_type: SelectorType, config: &Config) -> Selector {
     match selector_type {
         SelectorType::Fzf => {
-            let fzf = FzfSelector::new();
+            let fzf = FzfSelector::new().with_thumbnails(config.thumbnail_previews);
             if fzf.is_available() {
                 return Selector::Fzf(fzf);
             }
-            // Fall back to dialoguer
-            Selector::Dialoguer(DialoguerSelector::new())
-        }
-        SelectorType::Rofi => {
-            // TODO: Implement rofi selector
-            Selector::Dialoguer(DialoguerSelector::new())
+            fallback_selector()
         }
+        SelectorType::Builtin => fallback_selector(),
+        SelectorType::Rofi => launcher_selector(Launcher::Rofi, config),
+        SelectorType::Dmenu => launcher_selector(Launcher::Dmenu, config),
+        SelectorType::Wofi => launcher_selector(Launcher::Wofi, config),
         SelectorType::Dialoguer => Selector::Dialoguer(DialoguerSelector::new()),
     }
 }
 
-/// Detect the best available selector
-pub fn detect_selector() -> SelectorType {
-    let fzf = FzfSelector::new();
-    if fzf.is_available() {
-        return SelectorType::Fzf;
+/// A dmenu-style launcher with its `selector_args`, if it's installed
+fn launcher_selector(launcher: Launcher, config: &Config) -> Selector {
+    let args = config.selector_args.get(launcher.binary()).cloned().unwrap_or_default();
+    let selector = LauncherSelector::new(launcher, args);
+    if selector.is_available() {
+        return Selector::Launcher(selector);
+    }
+    fallback_selector()
+}
+
+/// Built-in fuzzy finder when we have a terminal, dialoguer otherwise
+fn fallback_selector() -> Selector {
+    let builtin = BuiltinSelector::new();
+    if builtin.is_available() {
+        return Selector::Builtin(builtin);
     }
-    SelectorType::Dialoguer
+    Selector::Dialoguer(DialoguerSelector::new())
 }
@@ -0,0 +1,281 @@
+//! First-run setup (a few questions about the settings people most often
+//! change, instead of defaults nobody knows exist) and the settings menu
+
+use super::dialoguer_selector::prompt_theme;
+use crate::storage::config::default_download_dir;
+use crate::types::{AudioFormat, Config, HistorySort, MenuItem, PlayerType, SelectorType};
+use crate::utils::paths::{expand_tilde, find_executable};
+use colored::Colorize;
+use dialoguer::{Confirm, Input, Select};
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Settings the menu can change; the rest are in config.toml (`yt-chill -e`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Selector,
+    Player,
+    VideoMode,
+    Limit,
+    DownloadDir,
+    AudioFormat,
+    DownloadJobs,
+    MaxResolution,
+    DataSaver,
+    Notify,
+    ThumbnailPreviews,
+    UnifiedSearch,
+    HistorySort,
+    ReturnToMenu,
+}
+
+impl Setting {
+    pub const ALL: &[Setting] = &[
+        Setting::Selector,
+        Setting::Player,
+        Setting::VideoMode,
+        Setting::Limit,
+        Setting::DownloadDir,
+        Setting::AudioFormat,
+        Setting::DownloadJobs,
+        Setting::MaxResolution,
+        Setting::DataSaver,
+        Setting::Notify,
+        Setting::ThumbnailPreviews,
+        Setting::UnifiedSearch,
+        Setting::HistorySort,
+        Setting::ReturnToMenu,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Setting::Selector => "Menus",
+            Setting::Player => "Player",
+            Setting::VideoMode => "Video by default",
+            Setting::Limit => "Search results",
+            Setting::DownloadDir => "Download folder",
+            Setting::AudioFormat => "Audio download format",
+            Setting::DownloadJobs => "Downloads at once",
+            Setting::MaxResolution => "Max video height",
+            Setting::DataSaver => "Data saver",
+            Setting::Notify => "Notifications",
+            Setting::ThumbnailPreviews => "Thumbnail previews",
+            Setting::UnifiedSearch => "Search history too",
+            Setting::HistorySort => "History order",
+            Setting::ReturnToMenu => "Back to menu after playing",
+        }
+    }
+
+    /// The key in config.toml
+    pub fn key(self) -> &'static str {
+        match self {
+            Setting::Selector => "selector",
+            Setting::Player => "player",
+            Setting::VideoMode => "video_mode",
+            Setting::Limit => "limit",
+            Setting::DownloadDir => "download_dir",
+            Setting::AudioFormat => "audio_format",
+            Setting::DownloadJobs => "download_jobs",
+            Setting::MaxResolution => "max_resolution",
+            Setting::DataSaver => "data_saver",
+            Setting::Notify => "notify",
+            Setting::ThumbnailPreviews => "thumbnail_previews",
+            Setting::UnifiedSearch => "unified_search",
+            Setting::HistorySort => "history_sort",
+            Setting::ReturnToMenu => "return_to_menu",
+        }
+    }
+
+    /// The current value, as shown in the menu
+    pub fn value(self, config: &Config) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        match self {
+            Setting::Selector => name_of(&config.selector),
+            Setting::Player => name_of(&config.player),
+            Setting::VideoMode => on_off(config.video_mode),
+            Setting::Limit => config.limit.to_string(),
+            Setting::DownloadDir if config.download_dir.is_empty() => default_download_dir(),
+            Setting::DownloadDir => config.download_dir.clone(),
+            Setting::AudioFormat => name_of(&config.audio_format),
+            Setting::DownloadJobs => config.download_jobs.to_string(),
+            Setting::MaxResolution if config.max_resolution == 0 => "no limit".into(),
+            Setting::MaxResolution => format!("{}p", config.max_resolution),
+            Setting::DataSaver => on_off(config.data_saver),
+            Setting::Notify => on_off(config.notify),
+            Setting::ThumbnailPreviews => on_off(config.thumbnail_previews),
+            Setting::UnifiedSearch => on_off(config.unified_search),
+            Setting::HistorySort => name_of(&config.history_sort),
+            Setting::ReturnToMenu => on_off(config.return_to_menu),
+        }
+    }
+
+    /// Ask for a new value. Esc keeps the old one.
+    pub fn edit(self, config: &mut Config) -> dialoguer::Result<()> {
+        match self {
+            Setting::Selector => config.selector = ask_selector(config.selector)?,
+            Setting::Player => config.player = ask_player(config.player.clone())?,
+            Setting::VideoMode => config.video_mode = ask_toggle("Include video by default?", config.video_mode)?,
+            Setting::Limit => config.limit = ask_number("Search results to show", config.limit, 1)?,
+            Setting::DownloadDir => config.download_dir = ask_download_dir(&config.download_dir)?,
+            Setting::AudioFormat => config.audio_format = ask_audio_format(config.audio_format)?,
+            Setting::DownloadJobs => {
+                config.download_jobs = ask_number("Videos to download at once", config.download_jobs, 1)?
+            }
+            Setting::MaxResolution => {
+                config.max_resolution = ask_number("Tallest video in pixels, e.g. 720 (0 = no limit)", config.max_resolution, 0)?
+            }
+            Setting::DataSaver => {
+                config.data_saver = ask_toggle("Save data (low bitrate, no thumbnails, shorter feeds)?", config.data_saver)?
+            }
+            Setting::Notify => config.notify = ask_toggle("Show desktop notifications?", config.notify)?,
+            Setting::ThumbnailPreviews => {
+                config.thumbnail_previews = ask_toggle("Show thumbnails in fzf?", config.thumbnail_previews)?
+            }
+            Setting::UnifiedSearch => {
+                config.unified_search = ask_toggle("Search watch history along with YouTube?", config.unified_search)?
+            }
+            Setting::HistorySort => {
+                let choices = [
+                    (HistorySort::Recent, "recent — last played first".to_string()),
+                    (HistorySort::Frequent, "frequent — most played first".to_string()),
+                ];
+                config.history_sort = choose("History order", &choices, config.history_sort)?;
+            }
+            Setting::ReturnToMenu => {
+                config.return_to_menu = ask_toggle("Go back to the main menu after playing?", config.return_to_menu)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One line per setting with its current value
+pub fn settings_menu(config: &Config) -> Vec<MenuItem<Setting>> {
+    let width = Setting::ALL.iter().map(|s| s.name().len()).max().unwrap_or(0);
+    Setting::ALL
+        .iter()
+        .map(|&setting| {
+            let label = format!("{:width$}  {}", setting.name(), setting.value(config).cyan(), width = width);
+            MenuItem::new(label, setting)
+        })
+        .collect()
+}
+
+/// Walk through the questions, starting from `config`'s values
+pub fn run(mut config: Config) -> dialoguer::Result<Config> {
+    println!("{}", "Welcome to yt-chill! A few quick questions first.".bold());
+    println!("{}", "(Esc keeps the suggested answer; change anything later with `yt-chill -e`.)\n".dimmed());
+
+    config.selector = ask_selector(config.selector)?;
+    config.player = ask_player(config.player)?;
+    config.download_dir = ask_download_dir(&config.download_dir)?;
+    config.audio_format = ask_audio_format(config.audio_format)?;
+    config.notify = ask_toggle("Show desktop notifications?", config.notify)?;
+    Ok(config)
+}
+
+pub fn ask_selector(current: SelectorType) -> dialoguer::Result<SelectorType> {
+    let choices = [
+        (SelectorType::Fzf, tool_label("fzf", "fuzzy finder with thumbnail previews")),
+        (SelectorType::Builtin, "builtin — fuzzy finder, nothing to install".to_string()),
+        (SelectorType::Dialoguer, "dialoguer — plain arrow-key prompts".to_string()),
+        (SelectorType::Rofi, tool_label("rofi", "desktop launcher")),
+        (SelectorType::Wofi, tool_label("wofi", "Wayland launcher")),
+        (SelectorType::Dmenu, tool_label("dmenu", "X11 launcher")),
+    ];
+    choose("Menus", &choices, current)
+}
+
+pub fn ask_player(current: PlayerType) -> dialoguer::Result<PlayerType> {
+    let choices = [
+        (PlayerType::Mpv, tool_label("mpv", "recommended: gapless queue, now-playing line, resume")),
+        (PlayerType::Vlc, tool_label("vlc", "plays in VLC (cvlc for audio)")),
+        (PlayerType::Mpd, tool_label("mpd", "adds to a running MPD server's queue")),
+    ];
+    // Custom commands keep their template; it's not in the list to pick
+    if matches!(current, PlayerType::Custom(_)) {
+        return Ok(current);
+    }
+    choose("Player", &choices, current)
+}
+
+/// Where downloads go. The default is stored as empty so it follows the
+/// system's Downloads folder.
+pub fn ask_download_dir(current: &str) -> dialoguer::Result<String> {
+    let default = default_download_dir();
+    let shown = if current.is_empty() { default.clone() } else { current.to_string() };
+    let dir: String = Input::with_theme(prompt_theme().as_ref())
+        .with_prompt("Download folder")
+        .default(shown)
+        // Missing folders are created on the first download
+        .validate_with(|dir: &String| match Path::new(&expand_tilde(dir.trim())) {
+            path if path.exists() && !path.is_dir() => Err("That's a file, not a folder"),
+            _ => Ok(()),
+        })
+        .interact_text()?;
+    let dir = dir.trim().to_string();
+    Ok(if dir == default { String::new() } else { dir })
+}
+
+pub fn ask_audio_format(current: AudioFormat) -> dialoguer::Result<AudioFormat> {
+    let choices = [
+        (AudioFormat::Mp3, "mp3 — plays everywhere".to_string()),
+        (AudioFormat::Opus, "opus — YouTube's own codec, no quality lost converting".to_string()),
+        (AudioFormat::M4a, "m4a — AAC, good for Apple devices".to_string()),
+        (AudioFormat::Flac, "flac — lossless container (of a lossy source)".to_string()),
+    ];
+    choose("Audio download format", &choices, current)
+}
+
+/// Yes or no, starting on `current`; Esc keeps it
+pub fn ask_toggle(prompt: &str, current: bool) -> dialoguer::Result<bool> {
+    let answer = Confirm::with_theme(prompt_theme().as_ref())
+        .with_prompt(prompt)
+        .default(current)
+        .interact_opt()?;
+    Ok(answer.unwrap_or(current))
+}
+
+/// A whole number no smaller than `min`
+pub fn ask_number<T>(prompt: &str, current: T, min: T) -> dialoguer::Result<T>
+where
+    T: Clone + Display + FromStr + PartialOrd,
+    T::Err: Display,
+{
+    Input::with_theme(prompt_theme().as_ref())
+        .with_prompt(prompt)
+        .default(current)
+        .validate_with(|n: &T| if *n < min { Err(format!("At least {}", min)) } else { Ok(()) })
+        .interact_text()
+}
+
+/// Pick one of `choices`, starting on `current`; Esc keeps it
+fn choose<T: Clone + PartialEq>(prompt: &str, choices: &[(T, String)], current: T) -> dialoguer::Result<T> {
+    let labels: Vec<&str> = choices.iter().map(|(_, label)| label.as_str()).collect();
+    let start = choices.iter().position(|(value, _)| *value == current).unwrap_or(0);
+    let picked = Select::with_theme(prompt_theme().as_ref())
+        .with_prompt(prompt)
+        .items(&labels)
+        .default(start)
+        .interact_opt()?;
+    Ok(picked.map_or(current, |i| choices[i].0.clone()))
+}
+
+/// How a config value is written in config.toml, e.g. "fzf"
+fn name_of<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// "name — description", noting when the tool isn't installed
+fn tool_label(name: &str, description: &str) -> String {
+    if find_executable(name).is_some() {
+        format!("{} — {}", name, description)
+    } else {
+        format!("{} — {} {}", name, description, "(not installed)".dimmed())
+    }
+}
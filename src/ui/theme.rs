@@ -0,0 +1,128 @@
+//! Icons and colors from the `[theme]` config section
+
+use crate::types::{IconStyle, ThemeConfig};
+use colored::{Color, ColoredString, Colorize};
+use std::sync::OnceLock;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Active theme for this run
+#[derive(Debug, Clone, Default)]
+pub struct Theme {
+    pub config: ThemeConfig,
+    /// `--plain`: no icons, colors or decorations
+    pub plain: bool,
+}
+
+/// Icons shown in menus and messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Search,
+    History,
+    Subscribe,
+    Feed,
+    Favorites,
+    Library,
+    Settings,
+    Live,
+    Playing,
+    Paused,
+    Goodbye,
+}
+
+impl Icon {
+    fn emoji(self) -> &'static str {
+        match self {
+            Icon::Search => "🔍",
+            Icon::History => "📜",
+            Icon::Subscribe => "➕",
+            Icon::Feed => "📺",
+            Icon::Favorites => "⭐",
+            Icon::Library => "💾",
+            Icon::Settings => "⚙️",
+            Icon::Live => "🔴",
+            Icon::Playing => "🎵",
+            Icon::Paused => "⏸️",
+            Icon::Goodbye => "👋",
+        }
+    }
+
+    /// Font Awesome glyphs as patched into Nerd Fonts
+    fn nerd(self) -> &'static str {
+        match self {
+            Icon::Search => "\u{f002}",
+            Icon::History => "\u{f1da}",
+            Icon::Subscribe => "\u{f067}",
+            Icon::Feed => "\u{f26c}",
+            Icon::Favorites => "\u{f005}",
+            Icon::Library => "\u{f0a0}",
+            Icon::Settings => "\u{f013}",
+            Icon::Live => "\u{f111}",
+            Icon::Playing => "\u{f001}",
+            Icon::Paused => "\u{f04c}",
+            Icon::Goodbye => "\u{f0a6}",
+        }
+    }
+}
+
+/// Set the theme for this run (first call wins). `--plain` and `NO_COLOR`
+/// turn colors off; `--plain` drops icons too.
+pub fn set_theme(config: ThemeConfig, plain: bool) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if plain || no_color {
+        colored::control::set_override(false);
+    }
+    THEME.get_or_init(|| Theme { config, plain });
+}
+
+/// The active theme (defaults unless configured)
+pub fn theme() -> Theme {
+    THEME.get().cloned().unwrap_or_default()
+}
+
+/// Whether to skip colors and decorations in prompts
+pub fn is_plain() -> bool {
+    theme().plain
+}
+
+/// `text` with its icon in front, per the configured icon style
+pub fn with_icon(icon: Icon, text: &str) -> String {
+    let theme = theme();
+    let style = if theme.plain { IconStyle::None } else { theme.config.icons };
+    match style {
+        IconStyle::Emoji => format!("{} {}", icon.emoji(), text),
+        IconStyle::Nerd => format!("{} {}", icon.nerd(), text),
+        IconStyle::None => text.to_string(),
+    }
+}
+
+/// Whether `spec` is a color setting we understand: a color name like
+/// "cyan" or "bright black", "dimmed", or "none"
+pub fn is_valid_color(spec: &str) -> bool {
+    matches!(spec, "dimmed" | "none") || spec.parse::<Color>().is_ok()
+}
+
+/// Apply a color setting to `text`
+pub fn paint(text: &str, spec: &str) -> ColoredString {
+    match spec {
+        "dimmed" => text.dimmed(),
+        "none" => text.normal(),
+        _ => match spec.parse::<Color>() {
+            Ok(color) => text.color(color),
+            Err(_) => text.normal(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_color() {
+        assert!(is_valid_color("cyan"));
+        assert!(is_valid_color("bright black"));
+        assert!(is_valid_color("dimmed"));
+        assert!(!is_valid_color("cyann"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Small fuzzy matcher for filtering local lists (history, favorites, ...)
+
+/// Score how well `query` matches `text`; higher is better, None = no match.
+/// Every whitespace-separated term must appear in order as a subsequence,
+/// with bonuses for exact substrings, consecutive characters and word starts.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text_lower = text.to_lowercase();
+    let mut total = 0;
+
+    for term in query.to_lowercase().split_whitespace() {
+        total += score_term(term, &text_lower)?;
+    }
+
+    Some(total)
+}
+
+/// Score a single lowercase term against lowercase text
+fn score_term(term: &str, text: &str) -> Option<i64> {
+    // Exact substrings beat scattered matches by a wide margin
+    if let Some(pos) = text.find(term) {
+        let at_word_start = pos == 0 || !text[..pos].ends_with(char::is_alphanumeric);
+        return Some(100 + term.len() as i64 * 10 + if at_word_start { 20 } else { 0 });
+    }
+
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut ti = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for pc in term.chars() {
+        while ti < text.len() && text[ti] != pc {
+            ti += 1;
+        }
+        if ti == text.len() {
+            return None;
+        }
+
+        score += 1;
+        if prev_match == Some(ti.wrapping_sub(1)) {
+            score += 5;
+        }
+        if ti == 0 || !text[ti - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        prev_match = Some(ti);
+        ti += 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("lofi", "Lofi Girl - beats to relax").is_some());
+        assert!(fuzzy_score("lfgrl", "Lofi Girl").is_some());
+        assert!(fuzzy_score("jazz", "Lofi Girl").is_none());
+
+        // All terms must match
+        assert!(fuzzy_score("lofi jazz", "Lofi Girl").is_none());
+
+        // Substring matches outrank scattered ones
+        let exact = fuzzy_score("mix", "Summer Mix 2023").unwrap();
+        let scattered = fuzzy_score("mix", "Music Improv Xtra").unwrap();
+        assert!(exact > scattered);
+    }
+}
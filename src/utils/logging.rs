@@ -0,0 +1,164 @@
+//! `--verbose` / `--log-file` logging: a small `tracing` subscriber writing
+//! yt-chill's own events to stderr and/or a log file
+
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Only log our own code, not the HTTP stack underneath
+const TARGET: &str = "yt_chill";
+
+/// Level a log file gets when `-v` wasn't given
+const DEFAULT_FILE_LEVEL: Level = Level::DEBUG;
+
+/// Where log lines go, and how much of it
+struct Logger {
+    stderr: Option<Level>,
+    file: Option<(Mutex<File>, Level)>,
+    next_span: AtomicU64,
+}
+
+/// stderr level for a number of `-v`s: info, debug, then trace
+pub fn verbosity_level(verbose: u8) -> Option<Level> {
+    match verbose {
+        0 => None,
+        1 => Some(Level::INFO),
+        2 => Some(Level::DEBUG),
+        _ => Some(Level::TRACE),
+    }
+}
+
+/// Start logging for this run. Does nothing without `-v` or a log file.
+pub fn init(verbose: u8, log_file: Option<&Path>) -> std::io::Result<()> {
+    let stderr = verbosity_level(verbose);
+    let file = match log_file {
+        Some(path) => {
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            Some((Mutex::new(file), stderr.unwrap_or(DEFAULT_FILE_LEVEL).max(DEFAULT_FILE_LEVEL)))
+        }
+        None => None,
+    };
+    if stderr.is_none() && file.is_none() {
+        return Ok(());
+    }
+
+    let logger = Logger {
+        stderr,
+        file,
+        next_span: AtomicU64::new(1),
+    };
+    // Only fails if a subscriber is already set, which is fine to keep
+    let _ = tracing::subscriber::set_global_default(logger);
+    Ok(())
+}
+
+impl Logger {
+    fn max_level(&self) -> Option<Level> {
+        let file = self.file.as_ref().map(|(_, level)| *level);
+        self.stderr.max(file)
+    }
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with(TARGET) && self.max_level().is_some_and(|max| *metadata.level() <= max)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from(self.max_level()))
+    }
+
+    // Spans aren't used; they only need distinct IDs
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let level = *metadata.level();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let line = format_line(level, metadata.target(), &fields);
+
+        if self.stderr.is_some_and(|max| level <= max) {
+            eprintln!("{}", line);
+        }
+        if let Some((file, max)) = &self.file
+            && level <= *max
+            && let Ok(mut file) = file.lock()
+        {
+            let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+            let _ = writeln!(file, "{} {}", now, line);
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// An event's message and its other fields as `key=value`
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.rest, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.rest, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// "DEBUG core::youtube: GET https://... status=200"
+fn format_line(level: Level, target: &str, fields: &Fields) -> String {
+    let module = target.strip_prefix("yt_chill::").unwrap_or(target);
+    format!("{:<5} {}: {}{}", level, module, fields.message, fields.rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_level() {
+        assert_eq!(verbosity_level(0), None);
+        assert_eq!(verbosity_level(1), Some(Level::INFO));
+        assert_eq!(verbosity_level(5), Some(Level::TRACE));
+    }
+
+    #[test]
+    fn test_format_line() {
+        let fields = Fields {
+            message: "GET https://www.youtube.com/".into(),
+            rest: " status=200".into(),
+        };
+        assert_eq!(
+            format_line(Level::DEBUG, "yt_chill::core::youtube", &fields),
+            "DEBUG core::youtube: GET https://www.youtube.com/ status=200"
+        );
+    }
+}
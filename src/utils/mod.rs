@@ -1,3 +1,9 @@
 //! Utility modules
 
+pub mod clipboard;
+pub mod fuzzy;
+pub mod logging;
+pub mod notify;
 pub mod paths;
+pub mod shutdown;
+pub mod time;
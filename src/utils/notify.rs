@@ -0,0 +1,43 @@
+//! Desktop notifications (notify-send on Linux, osascript on macOS)
+
+use crate::utils::paths::find_executable;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Show a notification, ignoring failures (notifications are best-effort)
+pub async fn send(title: &str, body: &str) {
+    if find_executable("notify-send").is_some() {
+        let _ = Command::new("notify-send")
+            .args(["--app-name=yt-chill", title, body])
+            .status()
+            .await;
+    } else if find_executable("osascript").is_some() {
+        let script = format!(
+            "display notification {:?} with title {:?}",
+            body, title
+        );
+        let _ = Command::new("osascript").args(["-e", &script]).status().await;
+    }
+}
+
+/// Show a notification with a "Play" button and wait for it to be clicked or
+/// dismissed. Returns true if the user chose to play. Falls back to a plain
+/// notification (returning false) where actions aren't supported.
+pub async fn send_with_play_action(title: &str, body: &str) -> bool {
+    if find_executable("notify-send").is_none() {
+        send(title, body).await;
+        return false;
+    }
+
+    let output = Command::new("notify-send")
+        .args(["--app-name=yt-chill", "--wait", "--action=play=▶ Play", title, body])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await;
+
+    match output {
+        Ok(out) => String::from_utf8_lossy(&out.stdout).trim() == "play",
+        Err(_) => false,
+    }
+}
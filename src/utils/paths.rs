@@ -4,13 +4,40 @@
 
 use crate::error::Result;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tokio::fs;
 
 const APP_NAME: &str = "yt-chill";
 
+/// Directories set by a program embedding yt-chill, used instead of the XDG ones
+#[derive(Debug, Clone, Default)]
+pub struct AppDirs {
+    pub config: Option<PathBuf>,
+    pub cache: Option<PathBuf>,
+    pub state: Option<PathBuf>,
+}
+
+static DIRS: OnceLock<AppDirs> = OnceLock::new();
+
+/// Use these directories for this process (first call wins)
+pub fn set_dirs(dirs: AppDirs) {
+    DIRS.get_or_init(|| dirs);
+}
+
+/// The directory picked by [`set_dirs`], if any
+fn dir_override(pick: fn(&AppDirs) -> Option<&PathBuf>) -> Option<String> {
+    DIRS.get()
+        .and_then(pick)
+        .map(|dir| dir.to_string_lossy().to_string())
+}
+
 /// Get config directory path
 /// Respects XDG_CONFIG_HOME, defaults to ~/.config/yt-chill
 pub fn get_config_dir() -> String {
+    if let Some(dir) = dir_override(|d| d.config.as_ref()) {
+        return dir;
+    }
     let base = env::var("XDG_CONFIG_HOME")
         .unwrap_or_else(|_| {
             dirs::config_dir()
@@ -24,6 +51,9 @@ pub fn get_config_dir() -> String {
 /// Get cache directory path
 /// Respects XDG_CACHE_HOME, defaults to ~/.cache/yt-chill
 pub fn get_cache_dir() -> String {
+    if let Some(dir) = dir_override(|d| d.cache.as_ref()) {
+        return dir;
+    }
     let base = env::var("XDG_CACHE_HOME")
         .unwrap_or_else(|_| {
             dirs::cache_dir()
@@ -34,22 +64,186 @@ pub fn get_cache_dir() -> String {
     format!("{}/{}", base, APP_NAME)
 }
 
+/// Get the default `--log-file` path
+pub fn get_log_path() -> String {
+    format!("{}/yt-chill.log", get_cache_dir())
+}
+
+/// Get state directory path
+/// Respects XDG_STATE_HOME, defaults to ~/.local/state/yt-chill
+pub fn get_state_dir() -> String {
+    if let Some(dir) = dir_override(|d| d.state.as_ref()) {
+        return dir;
+    }
+    let base = env::var("XDG_STATE_HOME")
+        .unwrap_or_else(|_| {
+            dirs::state_dir()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("{}/.local/state", env::var("HOME").unwrap_or_default()))
+        });
+
+    format!("{}/{}", base, APP_NAME)
+}
+
 /// Get history file path
 pub fn get_history_path() -> String {
+    format!("{}/history.json", get_state_dir())
+}
+
+/// Get favorites file path
+pub fn get_favorites_path() -> String {
+    format!("{}/favorites.json", get_state_dir())
+}
+
+/// Get the file keeping past search queries
+pub fn get_search_history_path() -> String {
+    format!("{}/searches.json", get_state_dir())
+}
+
+/// Get the directory mpv saves playback positions in (podcast resume)
+pub fn get_watch_later_dir() -> String {
+    format!("{}/watch_later", get_state_dir())
+}
+
+/// Get the socket `yt-chill ctl` talks to the daemon on
+/// Lives in XDG_RUNTIME_DIR when there is one, else the state directory
+pub fn get_control_socket_path() -> String {
+    match dirs::runtime_dir() {
+        Some(dir) => format!("{}/{}.sock", dir.to_string_lossy(), APP_NAME),
+        None => format!("{}/control.sock", get_state_dir()),
+    }
+}
+
+/// Get play queue file path
+pub fn get_queue_path() -> String {
+    format!("{}/queue.json", get_state_dir())
+}
+
+/// Get download archive file path
+pub fn get_downloads_path() -> String {
+    format!("{}/downloads.json", get_state_dir())
+}
+
+/// Get the file listing downloads that haven't finished
+pub fn get_pending_downloads_path() -> String {
+    format!("{}/pending_downloads.json", get_state_dir())
+}
+
+/// Get the file the last interactive session is saved in
+pub fn get_session_path() -> String {
+    format!("{}/session.json", get_state_dir())
+}
+
+/// Get the file listing downloads scheduled for later
+pub fn get_schedule_path() -> String {
+    format!("{}/schedule.json", get_state_dir())
+}
+
+/// Get the directory downloads are staged in (`.part` files and all) until
+/// they're complete and moved to the download dir
+pub fn get_partial_downloads_dir() -> String {
+    format!("{}/partial", get_cache_dir())
+}
+
+/// Get the directory video thumbnails are cached in (for fzf previews)
+pub fn get_thumbnails_dir() -> String {
+    format!("{}/thumbnails", get_cache_dir())
+}
+
+/// Get the file caching recent uploads per subscribed channel
+pub fn get_feed_cache_path() -> String {
+    format!("{}/feed.json", get_cache_dir())
+}
+
+/// Get the file tracking the newest video seen per channel (`yt-chill watch`)
+pub fn get_last_seen_path() -> String {
+    format!("{}/last_seen.json", get_state_dir())
+}
+
+/// Get SQLite database path (used by the `sqlite` storage backend)
+pub fn get_database_path() -> String {
+    format!("{}/yt-chill.db", get_state_dir())
+}
+
+/// Get pre-XDG_STATE_HOME history file path (in the cache dir)
+pub fn get_legacy_history_path() -> String {
     format!("{}/history.json", get_cache_dir())
 }
 
-/// Get config file path
+/// Get config file path (TOML, preferred format)
 pub fn get_config_path() -> String {
+    format!("{}/config.toml", get_config_dir())
+}
+
+/// Get legacy JSON config file path
+pub fn get_legacy_config_path() -> String {
     format!("{}/config.json", get_config_dir())
 }
 
 /// Get subscriptions file path
-#[allow(dead_code)]
 pub fn get_subscriptions_path() -> String {
+    format!("{}/subscriptions.toml", get_config_dir())
+}
+
+/// Get legacy tab-separated subscriptions file path
+pub fn get_legacy_subscriptions_path() -> String {
     format!("{}/subscriptions.txt", get_config_dir())
 }
 
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", env::var("HOME").unwrap_or_default(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Find an executable by name in PATH (or check it directly if it's a path)
+pub fn find_executable(cmd: &str) -> Option<PathBuf> {
+    if cmd.contains('/') {
+        let path = PathBuf::from(expand_tilde(cmd));
+        return path.is_file().then_some(path);
+    }
+
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|dir| dir.join(cmd))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Bytes free for us on the filesystem holding `path` (or its nearest
+/// existing parent, for folders not created yet). None if it can't be told.
+#[cfg(unix)]
+pub fn free_space(path: &str) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(path)
+        .ancestors()
+        .map(|p| if p.as_os_str().is_empty() { Path::new(".") } else { p })
+        .find(|p| p.exists())?;
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs only writes to the struct we pass, and c_path is NUL-terminated
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        stat
+    };
+    // The field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn free_space(_path: &str) -> Option<u64> {
+    None
+}
+
 /// Ensure a directory exists
 pub async fn ensure_dir(path: &str) -> Result<()> {
     fs::create_dir_all(path).await?;
@@ -60,5 +254,6 @@ pub async fn ensure_dir(path: &str) -> Result<()> {
 pub async fn ensure_app_dirs() -> Result<()> {
     ensure_dir(&get_config_dir()).await?;
     ensure_dir(&get_cache_dir()).await?;
+    ensure_dir(&get_state_dir()).await?;
     Ok(())
 }
@@ -0,0 +1,70 @@
+//! Ctrl+C / SIGTERM handling
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Exit code for a SIGINT, following the shell's 128 + signal convention
+const EXIT_SIGINT: u8 = 130;
+
+/// Exit code for a SIGTERM
+#[cfg(unix)]
+const EXIT_SIGTERM: u8 = 143;
+
+/// Players running in the foreground right now
+static PLAYERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Held while a player runs in the foreground. Ctrl+C reaches the player
+/// too, and it quits on its own (mpv saving its position first), so
+/// [`signal`] leaves it alone and playback ends the usual way.
+pub struct PlayerGuard(());
+
+/// Note that a player is running until the guard is dropped
+pub fn player_running() -> PlayerGuard {
+    PLAYERS.fetch_add(1, Ordering::SeqCst);
+    PlayerGuard(())
+}
+
+impl Drop for PlayerGuard {
+    fn drop(&mut self) {
+        PLAYERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wait for Ctrl+C with no player running (or SIGTERM on Unix, which always
+/// counts) and return the exit code to use
+pub async fn signal() -> u8 {
+    loop {
+        let code = next_signal().await;
+        if code != EXIT_SIGINT || PLAYERS.load(Ordering::SeqCst) == 0 {
+            return code;
+        }
+    }
+}
+
+async fn next_signal() -> u8 {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => EXIT_SIGINT,
+                _ = term.recv() => EXIT_SIGTERM,
+            };
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+    EXIT_SIGINT
+}
+
+/// Undo anything a half-finished prompt or progress bar left behind
+pub fn restore_terminal() {
+    #[cfg(unix)]
+    crate::ui::now_playing::leave_raw_mode();
+    let mut stderr = std::io::stderr();
+    if stderr.is_terminal() {
+        // Show the cursor again (dialoguer and indicatif hide it) and end the line
+        let _ = writeln!(stderr, "\x1b[?25h");
+        let _ = stderr.flush();
+    }
+}
@@ -0,0 +1,213 @@
+//! Human-friendly duration parsing and formatting
+
+use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
+use std::time::Duration;
+
+/// Parse a duration like "2h", "90m", "1h30m", "45s" or "30d"
+/// A bare number is treated as minutes. Values too large to count in
+/// seconds are rejected.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    if input.is_empty() {
+        return None;
+    }
+
+    if let Ok(minutes) = input.parse::<u64>() {
+        return minutes.checked_mul(60).map(Duration::from_secs);
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let value: u64 = number.parse().ok()?;
+        number.clear();
+        let unit = match c {
+            'w' => 7 * 86400,
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(value.checked_mul(unit)?)?;
+    }
+
+    // Trailing digits without a unit ("1h30") are ambiguous
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(total))
+}
+
+/// Format a duration as "1h 05m", "12m 30s" or "45s"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+/// Format seconds as a clock duration like "3:45" or "1:23:45"
+pub fn format_clock(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Parse a clock duration like "3:45" or "1:23:45" into seconds
+pub fn parse_clock(input: &str) -> Option<u64> {
+    let parts: Vec<u64> = input
+        .trim()
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+
+    match parts.as_slice() {
+        [s] => Some(*s),
+        [m, s] => Some(m * 60 + s),
+        [h, m, s] => Some(h * 3600 + m * 60 + s),
+        _ => None,
+    }
+}
+
+/// Parse a clock range like "1:23-2:45" into start and end seconds
+pub fn parse_clock_range(input: &str) -> Option<(u64, u64)> {
+    let (start, end) = input.split_once('-')?;
+    let (start, end) = (parse_clock(start)?, parse_clock(end)?);
+    (start < end).then_some((start, end))
+}
+
+/// Parse YouTube's "3 days ago" (or "Streamed 2 weeks ago") into seconds.
+/// Months and years are approximate, which is fine for ordering.
+pub fn parse_age(input: &str) -> Option<u64> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    words.windows(2).find_map(|pair| {
+        let count: u64 = pair[0].parse().ok()?;
+        let unit = match pair[1].trim_end_matches('s') {
+            "second" => 1,
+            "minute" => 60,
+            "hour" => 3600,
+            "day" => 86400,
+            "week" => 7 * 86400,
+            "month" => 30 * 86400,
+            "year" => 365 * 86400,
+            _ => return None,
+        };
+        Some(count * unit)
+    })
+}
+
+/// Seconds as YouTube shows an upload's age, e.g. "3 days ago" (read back
+/// by [`parse_age`])
+pub fn format_age(secs: u64) -> String {
+    const UNITS: &[(&str, u64)] = &[
+        ("year", 365 * 86400),
+        ("month", 30 * 86400),
+        ("week", 7 * 86400),
+        ("day", 86400),
+        ("hour", 3600),
+        ("minute", 60),
+    ];
+    let (unit, count) = UNITS
+        .iter()
+        .find(|(_, size)| secs >= *size)
+        .map_or(("second", secs), |(unit, size)| (unit, secs / size));
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+/// The next time after `now` that the clock shows `time`, e.g. tonight's
+/// or tomorrow's 02:00. Skips a day when DST leaves out that time.
+pub fn next_time_of_day<Tz: TimeZone>(time: NaiveTime, now: &DateTime<Tz>) -> DateTime<Tz> {
+    let today = now.date_naive();
+    (0..3)
+        .filter_map(|days| today.checked_add_days(Days::new(days)))
+        .filter_map(|day| now.timezone().from_local_datetime(&day.and_time(time)).earliest())
+        .find(|at| at > now)
+        .unwrap_or_else(|| now.clone())
+}
+
+/// Format a unix timestamp as a local "YYYY-MM-DD HH:MM"
+pub fn format_timestamp(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("45s"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("25"), Some(Duration::from_secs(1500)));
+        assert_eq!(parse_duration("30d"), Some(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_duration("1h30"), None);
+        assert_eq!(parse_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        assert_eq!(parse_duration(&u64::MAX.to_string()), None);
+        assert_eq!(parse_duration("99999999999999999w"), None);
+        assert_eq!(parse_duration(&format!("{}s1s", u64::MAX)), None);
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("3:45"), Some(225));
+        assert_eq!(parse_clock("1:00:05"), Some(3605));
+        assert_eq!(parse_clock("LIVE"), None);
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("3 days ago"), Some(3 * 86400));
+        assert_eq!(parse_age("Streamed 1 hour ago"), Some(3600));
+        assert_eq!(parse_age("2 years ago"), Some(2 * 365 * 86400));
+        assert_eq!(parse_age(""), None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(3 * 86400 + 5), "3 days ago");
+        assert_eq!(format_age(3600), "1 hour ago");
+        assert_eq!(format_age(40 * 86400), "1 month ago");
+        assert_eq!(format_age(5), "5 seconds ago");
+        assert_eq!(parse_age(&format_age(2 * 7 * 86400)), Some(2 * 7 * 86400));
+    }
+
+    #[test]
+    fn test_next_time_of_day() {
+        let now = chrono::Utc.with_ymd_and_hms(2026, 1, 10, 12, 0, 0).unwrap();
+        let at = |h, m| next_time_of_day(NaiveTime::from_hms_opt(h, m, 0).unwrap(), &now);
+        assert_eq!(at(13, 30), chrono::Utc.with_ymd_and_hms(2026, 1, 10, 13, 30, 0).unwrap());
+        assert_eq!(at(2, 0), chrono::Utc.with_ymd_and_hms(2026, 1, 11, 2, 0, 0).unwrap());
+        assert_eq!(at(12, 0), chrono::Utc.with_ymd_and_hms(2026, 1, 11, 12, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_parse_clock_range() {
+        assert_eq!(parse_clock_range("1:23-2:45"), Some((83, 165)));
+        assert_eq!(parse_clock_range("0:30 - 1:00:00"), Some((30, 3600)));
+        assert_eq!(parse_clock_range("2:45-1:23"), None);
+        assert_eq!(parse_clock_range("1:23"), None);
+    }
+}